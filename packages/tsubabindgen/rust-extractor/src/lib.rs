@@ -0,0 +1,6741 @@
+//! The tsubabindgen extractor: parses a crate with `syn` and produces the JSON module document
+//! consumed by the generator. The `tsubabindgen-extractor` binary is a thin wrapper over [`run`].
+
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Expr, ExprLit, Fields, FnArg, GenericParam, ImplItem, Item, ItemConst, ItemEnum, ItemImpl,
+    ItemMod, ItemStruct, ItemTrait, Lit, Meta, Pat, PathArguments, ReturnType, Signature, Token,
+    TraitItem, Type, UseTree, Visibility,
+};
+
+#[derive(Serialize, Deserialize, Clone)]
+struct SkipIssue {
+    file: String,
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    snippet: String,
+    reason: String,
+}
+
+impl SkipIssue {
+    fn coded(file: &str, kind: &str, code: &str, snippet: String, reason: String) -> SkipIssue {
+        SkipIssue {
+            file: file.to_string(),
+            kind: kind.to_string(),
+            code: Some(code.to_string()),
+            snippet,
+            reason,
+        }
+    }
+}
+
+/// Location of an extracted item, so generated bindings can link back to Rust source. Byte
+/// offsets are relative to `sourceFile`; lines are 1-based and inclusive. Outer attributes and
+/// doc comments are part of the item.
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct SourceSpan {
+    #[serde(rename = "sourceFile")]
+    source_file: String,
+    #[serde(rename = "byteStart")]
+    byte_start: usize,
+    #[serde(rename = "byteEnd")]
+    byte_end: usize,
+    #[serde(rename = "lineStart")]
+    line_start: usize,
+    #[serde(rename = "lineEnd")]
+    line_end: usize,
+}
+
+fn source_span(file: &str, node: &impl Spanned) -> SourceSpan {
+    let span = node.span();
+    let bytes = span.byte_range();
+    SourceSpan {
+        source_file: file.to_string(),
+        byte_start: bytes.start,
+        byte_end: bytes.end,
+        line_start: span.start().line,
+        line_end: span.end().line,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractField {
+    name: String,
+    #[serde(rename = "exportName", skip_serializing_if = "Option::is_none")]
+    export_name: Option<String>,
+    /// `#[doc(alias = "...")]` values, in attribute order.
+    #[serde(rename = "docAliases", skip_serializing_if = "Vec::is_empty")]
+    doc_aliases: Vec<String>,
+    /// Doc comment text of struct and enum-variant fields (see `doc_comment`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs: Option<String>,
+    /// `--emit-attributes`: outer attributes other than doc comments, as normalized token text.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attributes: Vec<String>,
+    /// Impl associated types only: a generic associated type's own parameters
+    /// (`type Iter<'a> = ...`, see `ExtractAssocType`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    /// Consts only: `--extract-examples` code blocks (see `doc_examples`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(rename = "type")]
+    type_text: String,
+    #[serde(rename = "genericBase", skip_serializing_if = "Option::is_none")]
+    generic_base: Option<String>,
+    #[serde(rename = "genericArgs", skip_serializing_if = "Vec::is_empty")]
+    generic_args: Vec<String>,
+    /// Structured form of `type`; absent for pseudo-types like the `self` receiver marker.
+    #[serde(rename = "typeRepr", skip_serializing_if = "Option::is_none")]
+    type_repr: Option<TypeRepr>,
+    /// Params only: one of the contiguous trailing `Option<_>` parameters, which callers may
+    /// omit (`port?: number`). An `Option` before a required parameter stays required.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    optional: bool,
+    /// Consts only: the initializer as normalized token text. Absent for a trait const without
+    /// a default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    /// `value` is a plain literal (`42`, `-1.5`, `"id"`, `true`, `'x'`, `b'x'`) that can be
+    /// embedded as written; otherwise it is a computed expression only the runtime can evaluate.
+    #[serde(rename = "isLiteral", skip_serializing_if = "std::ops::Not::not")]
+    is_literal: bool,
+    /// Only set when the field is itself an item (`consts`).
+    #[serde(flatten)]
+    span: Option<SourceSpan>,
+}
+
+impl ExtractField {
+    fn new(name: String, type_text: String) -> ExtractField {
+        ExtractField {
+            name,
+            export_name: None,
+            doc_aliases: Vec::new(),
+            docs: None,
+            attributes: Vec::new(),
+            generics: Vec::new(),
+            examples: Vec::new(),
+            type_text,
+            generic_base: None,
+            generic_args: Vec::new(),
+            type_repr: None,
+            optional: false,
+            value: None,
+            is_literal: false,
+            span: None,
+        }
+    }
+
+    fn of_type(name: String, ty: &Type) -> ExtractField {
+        let mut out = ExtractField::new(name, type_to_string(ty));
+        if let Some((base, args)) = split_generic_path(ty) {
+            out.generic_base = Some(base);
+            out.generic_args = args;
+        }
+        out.type_repr = Some(type_repr(ty));
+        out
+    }
+
+    /// Records a const initializer as `value` / `isLiteral`.
+    fn set_value(&mut self, expr: &Expr) {
+        match literal_text(expr) {
+            Some(text) => {
+                self.value = Some(text);
+                self.is_literal = true;
+            }
+            None => self.value = Some(normalize_ws(expr.to_token_stream().to_string())),
+        }
+    }
+}
+
+/// Source text of a literal const initializer: a string, number, bool, char, or byte literal,
+/// optionally negated (numbers only) or parenthesized. `None` for anything computed.
+fn literal_text(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(_) | Lit::Int(_) | Lit::Float(_) | Lit::Bool(_) | Lit::Char(_) | Lit::Byte(_) => {
+                Some(lit.to_token_stream().to_string())
+            }
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => match unary.expr.as_ref() {
+            Expr::Lit(ExprLit { lit: lit @ (Lit::Int(_) | Lit::Float(_)), .. }) => {
+                Some(format!("-{}", lit.to_token_stream()))
+            }
+            _ => None,
+        },
+        Expr::Paren(paren) => literal_text(&paren.expr),
+        Expr::Group(group) => literal_text(&group.expr),
+        _ => None,
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractFunction {
+    kind: String,
+    name: String,
+    #[serde(rename = "exportName", skip_serializing_if = "Option::is_none")]
+    export_name: Option<String>,
+    #[serde(rename = "docAliases", skip_serializing_if = "Vec::is_empty")]
+    doc_aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attributes: Vec<String>,
+    /// `--emit-attributes`: the known code-generation attributes among `attributes` (see
+    /// `fn_attrs`).
+    #[serde(rename = "fnAttrs", skip_serializing_if = "Vec::is_empty")]
+    fn_attrs: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(rename = "typeParams")]
+    type_params: Vec<String>,
+    /// Lifetime parameters (`'a`), kept for docs and debugging; they have no TS counterpart.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    /// Const generic parameters (`<const N: usize>`), in declaration order.
+    #[serde(rename = "constParams", skip_serializing_if = "Vec::is_empty")]
+    const_params: Vec<ConstParam>,
+    /// Every generic parameter in declaration order (see `GenericParamDecl`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    #[serde(flatten)]
+    arity: GenericArity,
+    params: Vec<ExtractField>,
+    #[serde(rename = "returnType")]
+    return_type: String,
+    #[serde(rename = "returnGenericBase", skip_serializing_if = "Option::is_none")]
+    return_generic_base: Option<String>,
+    #[serde(rename = "returnGenericArgs", skip_serializing_if = "Vec::is_empty")]
+    return_generic_args: Vec<String>,
+    #[serde(rename = "returnTypeRepr", skip_serializing_if = "Option::is_none")]
+    return_type_repr: Option<TypeRepr>,
+    /// The receiver is taken by value (`self`, `mut self`, `self: Box<Self>`), so calling the
+    /// method moves the value out of the caller. Pinned references (`self: Pin<&mut Self>`) do not.
+    #[serde(rename = "consumesSelf")]
+    consumes_self: bool,
+    /// Takes no receiver and returns `Self` (through smart-pointer wrappers such as `Box<Self>`),
+    /// or the implementing type by name in an impl block: `fn new() -> Self`, `fn create() ->
+    /// Grid`. Trait methods qualify through `Self` alone.
+    #[serde(rename = "isConstructor")]
+    is_constructor: bool,
+    /// `async fn`, including async trait methods. `returnType` is the written (awaited) output
+    /// type, not the desugared `impl Future`.
+    #[serde(rename = "isAsync")]
+    is_async: bool,
+    /// C-variadic (`unsafe extern "C" fn f(fmt: *const c_char, ...)`): the trailing `...` is not
+    /// one of `params`, so a facade has to add a rest parameter or refuse the function.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    variadic: bool,
+    /// `--recognize-futures`: the `Output` type when a non-async function returns a boxed future
+    /// (`Box<dyn Future<Output = T>>`, `Pin<Box<dyn Future<Output = T> + Send>>`), so it can bind
+    /// like an `async fn` returning `T`. `returnType` stays the boxed future.
+    #[serde(rename = "futureOutput", skip_serializing_if = "Option::is_none")]
+    future_output: Option<String>,
+    #[serde(rename = "futureOutputRepr", skip_serializing_if = "Option::is_none")]
+    future_output_repr: Option<TypeRepr>,
+    /// The function declares its own generic parameters: type, const, or lifetime (`fn f<T>`,
+    /// `fn f<const N: usize>`, `fn f<'a>`), or takes an argument-position `impl Trait`, which is
+    /// an anonymous type parameter. `typeParams` only lists the named type parameters.
+    #[serde(rename = "isGeneric")]
+    is_generic: bool,
+    /// Bounds on the function's own generic parameters, inline and `where` alike.
+    #[serde(rename = "genericBounds", skip_serializing_if = "Vec::is_empty")]
+    generic_bounds: Vec<GenericBound>,
+    /// Set on methods of trait impls: the trait method this method implements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implements: Option<ImplementedMethod>,
+    /// `--include-private`: a `macro_rules!` macro without `#[macro_export]` (see
+    /// `macro_visibility`). Exported macros and other entries have none.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visibility: Option<String>,
+    /// Trait-impl methods named like an inherent method of the same target: the Rust
+    /// fully-qualified path (`<Counter as Reset>::reset`), so the two stay distinct once merged.
+    #[serde(rename = "qualifiedId", skip_serializing_if = "Option::is_none")]
+    qualified_id: Option<String>,
+    /// Inherent methods without a receiver: the path that calls them, `Target::name`, with no
+    /// turbofish (`Grid::new` in `impl<T> Grid<T>`), so factories can be invoked directly.
+    #[serde(rename = "callPath", skip_serializing_if = "Option::is_none")]
+    call_path: Option<String>,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+/// One bounded type or lifetime and its bounds, normalized like `superTraits`: inline
+/// `<T: Clone>` gives `{ type: "T", bounds: ["Clone"] }` and `where I: Iterator<Item = i32>`
+/// gives `{ type: "I", bounds: ["Iterator < Item = i32 >"] }`. Higher-ranked `for<'a>` binders
+/// stay on the bounded type text.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct GenericBound {
+    #[serde(rename = "type")]
+    bounded: String,
+    bounds: Vec<String>,
+    /// The trait bounds of `bounds` as path nodes (see `trait_bound_paths`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<PathRepr>,
+}
+
+/// A const generic parameter (`<const N: usize>` gives `{ name: "N", type: "usize" }`).
+#[derive(Serialize, Deserialize, Clone)]
+struct ConstParam {
+    name: String,
+    #[serde(rename = "type")]
+    type_text: String,
+}
+
+/// One generic parameter, tagged by `kind` (`lifetime`, `type`, or `const`). The `generics`
+/// list keeps declaration order across kinds, so `struct S<'a, T, const N: usize>` can be
+/// instantiated positionally (`S<'static, number, 4>`); `typeParams`, `lifetimes`, and
+/// `constParams` split it by kind. `type` is a const parameter's type, and `default` the
+/// default type or value text.
+#[derive(Serialize, Deserialize, Clone)]
+struct GenericParamDecl {
+    kind: String,
+    name: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    type_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+}
+
+/// Derived from `generics`: the number of parameters an item takes (`totalGenericArity`) and the
+/// names of those a TS facade cannot express (`unrepresentableGenerics`: lifetimes and const
+/// parameters, each also reported as a `generic` issue). A generator that drops them still knows
+/// `struct Bytes<const N: usize>` takes one argument and must not be written `Bytes<>`. Both are
+/// omitted for non-generic items.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct GenericArity {
+    #[serde(rename = "totalGenericArity", skip_serializing_if = "is_zero")]
+    total: usize,
+    #[serde(rename = "unrepresentableGenerics", skip_serializing_if = "Vec::is_empty")]
+    unrepresentable: Vec<String>,
+}
+
+impl GenericArity {
+    fn of(generics: &syn::Generics) -> GenericArity {
+        GenericArity {
+            total: generics.params.len(),
+            unrepresentable: generics
+                .params
+                .iter()
+                .filter_map(|param| match param {
+                    GenericParam::Type(_) => None,
+                    GenericParam::Lifetime(lp) => Some(lp.lifetime.to_string()),
+                    GenericParam::Const(cp) => Some(cp.ident.to_string()),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// A type parameter with a default (`trait Sink<T = i32>` gives `{ type: "T", default: "i32" }`).
+#[derive(Serialize, Deserialize, Clone)]
+struct GenericDefault {
+    #[serde(rename = "type")]
+    param: String,
+    default: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ImplementedMethod {
+    /// Trait path as written, without generic arguments (`Named`, `std::fmt::Display`).
+    #[serde(rename = "trait")]
+    trait_name: String,
+    method: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractStruct {
+    /// `named` (`struct S { .. }`, including `struct S {}`), `tuple` (`struct S(..);`), or `unit`
+    /// (`struct S;`), so a unit struct is not mistaken for a named struct with no public fields.
+    kind: String,
+    name: String,
+    #[serde(rename = "exportName", skip_serializing_if = "Option::is_none")]
+    export_name: Option<String>,
+    #[serde(rename = "docAliases", skip_serializing_if = "Vec::is_empty")]
+    doc_aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(rename = "typeParams")]
+    type_params: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    #[serde(flatten)]
+    arity: GenericArity,
+    #[serde(rename = "genericDefaults", skip_serializing_if = "Vec::is_empty")]
+    generic_defaults: Vec<GenericDefault>,
+    fields: Vec<ExtractField>,
+    /// Named fields left out of `fields` because they are not `pub`, so an empty or short field
+    /// list is distinguishable from dropped fields.
+    #[serde(rename = "skippedPrivateFields")]
+    skipped_private_fields: u32,
+    /// `#[derive(Default)]` or an `impl Default for` this type anywhere in the crate, so
+    /// generators can offer a zero-argument constructor.
+    #[serde(rename = "hasDefault")]
+    has_default: bool,
+    /// Traits this type implements, from every trait impl in the crate that targets it.
+    #[serde(rename = "implementedTraits", skip_serializing_if = "Vec::is_empty")]
+    implemented_traits: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    recursive: bool,
+    /// `#[tsuba(opaque)]`: a handle type whose internals are not exposed. `fields` is empty and
+    /// `skippedPrivateFields` is 0 whatever the fields' visibility, and no properties are inferred.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    opaque: bool,
+    /// The wrapped field of a `#[repr(transparent)]` newtype (`struct UserId(u64);`), so a generator
+    /// can inline it as its inner type. Set whatever the field's visibility; tuple fields are named
+    /// by position (`"0"`).
+    #[serde(rename = "transparentInner", skip_serializing_if = "Option::is_none")]
+    transparent_inner: Option<ExtractField>,
+    /// `--infer-properties`: read-only properties backed by getter methods.
+    #[serde(rename = "derivedProperties", skip_serializing_if = "Vec::is_empty")]
+    derived_properties: Vec<DerivedProperty>,
+    /// Names of the non-`pub` named fields, for matching getters named after them.
+    #[serde(skip)]
+    private_field_names: Vec<String>,
+    /// Type texts of the fields left out of `fields` (non-`pub` named fields, tuple fields, and
+    /// every field of an opaque struct), so `mark_recursive_types` sees the whole layout.
+    #[serde(skip)]
+    hidden_field_types: Vec<String>,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+/// A property inferred from a getter: `name` is the property, `getter` the method that reads it,
+/// and `type`/`typeRepr` the getter's return type.
+#[derive(Serialize, Deserialize, Clone)]
+struct DerivedProperty {
+    name: String,
+    getter: String,
+    #[serde(rename = "type")]
+    type_text: String,
+    #[serde(rename = "typeRepr", skip_serializing_if = "Option::is_none")]
+    type_repr: Option<TypeRepr>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct ExtractEnumVariant {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs: Option<String>,
+    fields: Vec<ExtractField>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractEnum {
+    name: String,
+    #[serde(rename = "exportName", skip_serializing_if = "Option::is_none")]
+    export_name: Option<String>,
+    #[serde(rename = "docAliases", skip_serializing_if = "Vec::is_empty")]
+    doc_aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(rename = "typeParams")]
+    type_params: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    #[serde(flatten)]
+    arity: GenericArity,
+    #[serde(rename = "genericDefaults", skip_serializing_if = "Vec::is_empty")]
+    generic_defaults: Vec<GenericDefault>,
+    variants: Vec<ExtractEnumVariant>,
+    /// Every variant is a unit variant (`A`, not `A()` or `A {}`), so the enum can map to a
+    /// numeric TS enum instead of a discriminated union. Vacuously true for an empty enum.
+    #[serde(rename = "isCLike")]
+    is_c_like: bool,
+    /// `--serde-aware`: how serde tags the enum on the wire (see `serde_tagging`).
+    #[serde(rename = "serdeTagging", skip_serializing_if = "Option::is_none")]
+    serde_tagging: Option<SerdeTagging>,
+    #[serde(rename = "hasDefault")]
+    has_default: bool,
+    /// Traits this type implements, from every trait impl in the crate that targets it.
+    #[serde(rename = "implementedTraits", skip_serializing_if = "Vec::is_empty")]
+    implemented_traits: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    recursive: bool,
+    /// `#[tsuba(opaque)]`: a handle type whose internals are not exposed. `variants` is empty and
+    /// `isCLike` is false.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    opaque: bool,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+/// An enum's serde representation: `mode` is `external` (serde's default, `{"Variant": payload}`),
+/// `internal` (`tag` names the field holding the variant name), `adjacent` (variant name under
+/// `tag`, payload under `content`), or `untagged`.
+#[derive(Serialize, Deserialize, Clone)]
+struct SerdeTagging {
+    mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractTrait {
+    name: String,
+    #[serde(rename = "exportName", skip_serializing_if = "Option::is_none")]
+    export_name: Option<String>,
+    #[serde(rename = "docAliases", skip_serializing_if = "Vec::is_empty")]
+    doc_aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+    #[serde(rename = "typeParams")]
+    type_params: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    #[serde(flatten)]
+    arity: GenericArity,
+    #[serde(rename = "genericDefaults", skip_serializing_if = "Vec::is_empty")]
+    generic_defaults: Vec<GenericDefault>,
+    #[serde(rename = "superTraits")]
+    super_traits: Vec<String>,
+    /// The trait supertraits as path nodes (`std::fmt::Debug` keeps its segments); lifetime
+    /// supertraits (`'static`) have none.
+    #[serde(rename = "superTraitPaths", skip_serializing_if = "Vec::is_empty")]
+    super_trait_paths: Vec<PathRepr>,
+    /// Associated types in declaration order. Their names are also folded into `typeParams`.
+    #[serde(rename = "assocTypes")]
+    assoc_types: Vec<ExtractAssocType>,
+    /// Associated consts in declaration order; `value` is the default, if any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    consts: Vec<ExtractField>,
+    methods: Vec<ExtractFunction>,
+    /// The trait body is empty (`trait Marker {}`, `trait Both: A + B {}`, auto traits): no
+    /// methods, associated types, or consts, so it maps to an empty interface. Supertraits
+    /// do not count.
+    #[serde(rename = "isMarker")]
+    is_marker: bool,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+/// A trait's associated type: `type Out: Into<String> + Send;` has `bounds: ["Into < String >",
+/// "Send"]`, normalized like `superTraits`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractAssocType {
+    name: String,
+    bounds: Vec<String>,
+    /// Default type text (`type Out = String;`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+    /// A generic associated type's own parameters (`type Iter<'a>` has one lifetime), like
+    /// `generics` on items. Empty for a plain associated type.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    /// Bounds on those parameters, inline and `where` (`where Self: 'a`).
+    #[serde(rename = "genericBounds", skip_serializing_if = "Vec::is_empty")]
+    generic_bounds: Vec<GenericBound>,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractReexport {
+    name: String,
+    source: String,
+    /// Crate-absolute parts of the module that defines the re-exported item, when `source`
+    /// resolves inside the crate (following `self::`/`super::`/`crate::` prefixes and chains of
+    /// re-exports).
+    #[serde(rename = "sourceModule", skip_serializing_if = "Option::is_none")]
+    source_module: Option<Vec<String>>,
+    /// The item's name in `sourceModule`, which differs from the last `source` segment when a
+    /// re-export it passed through renamed it.
+    #[serde(rename = "sourceName", skip_serializing_if = "Option::is_none")]
+    source_name: Option<String>,
+    /// `source` starts outside the crate (`pub use serde_json::Value;`, `pub use ::log::info;`):
+    /// its first segment is not `crate`/`self`/`super`, a module, an item, or a `use` import of
+    /// the re-exporting module.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    foreign: bool,
+    /// `--include-private`: the restriction of a `pub(crate) use`, `pub(super) use`, or
+    /// `pub(in path) use` re-export; absent for `pub use`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visibility: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct PendingMethods {
+    target: String,
+    /// `"&"` or `"&mut"` when the impl is on a reference to `target` (`impl Display for &Foo`).
+    #[serde(rename = "targetReference", skip_serializing_if = "Option::is_none")]
+    target_reference: Option<String>,
+    /// Generic arguments written on the target (`["T"]` for `impl<T> Service<T> for Wrapper<T>`,
+    /// `["i32"]` for `impl Service<i32> for Wrapper<i32>`).
+    #[serde(rename = "targetGenericArgs", skip_serializing_if = "Vec::is_empty")]
+    target_generic_args: Vec<String>,
+    /// The impl's own type parameters, so blanket and concrete instantiations can be told apart.
+    #[serde(rename = "typeParams")]
+    type_params: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    generics: Vec<GenericParamDecl>,
+    #[serde(flatten)]
+    arity: GenericArity,
+    /// Bounds on the impl's parameters, inline and `where`, shaped like a function's
+    /// `genericBounds`: `impl<T: Serialize> Named for Wrapper<T>` only applies when `T: Serialize`.
+    #[serde(rename = "genericBounds", skip_serializing_if = "Vec::is_empty")]
+    generic_bounds: Vec<GenericBound>,
+    /// Implemented trait path text (`Service < T >`); absent for inherent impls.
+    #[serde(rename = "trait", skip_serializing_if = "Option::is_none")]
+    trait_path: Option<String>,
+    #[serde(rename = "traitGenericBase", skip_serializing_if = "Option::is_none")]
+    trait_generic_base: Option<String>,
+    #[serde(rename = "traitGenericArgs", skip_serializing_if = "Vec::is_empty")]
+    trait_generic_args: Vec<String>,
+    methods: Vec<ExtractFunction>,
+    /// Associated consts (`const MAX: u32 = 8;`), with `type` the const's type.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    consts: Vec<ExtractField>,
+    /// Associated type aliases (`type Output = i32;`), with `type` the aliased type.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    types: Vec<ExtractField>,
+    /// `--preserve-impl-groups`: the impl block's doc comment (see `doc_comment`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs: Option<String>,
+    /// `--preserve-impl-groups`: where the impl block is, so an entry can be told apart from
+    /// other blocks on the same target.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    span: Option<SourceSpan>,
+    /// The target path as written (`inner::Id`), for `--resolve-paths` alias resolution.
+    #[serde(skip)]
+    target_path: Vec<String>,
+    /// The trait path without generic arguments, as in `implements.trait`.
+    #[serde(skip)]
+    trait_name: Option<String>,
+    /// Byte offset of the impl block in its file, the last tiebreak of `pending_order`.
+    #[serde(skip)]
+    source_start: usize,
+}
+
+/// Canonical order of a module's impl blocks: by target, then by source position, so blocks on
+/// one target keep their written order even after `--resolve-paths` renames an alias target.
+/// Methods keep their order within a block. Generators attach methods in this order, so it
+/// decides which of two same-named methods on one target is seen first.
+fn pending_order(a: &PendingMethods, b: &PendingMethods) -> std::cmp::Ordering {
+    a.target.cmp(&b.target).then(a.source_start.cmp(&b.source_start))
+}
+
+impl PendingMethods {
+    /// Records the implemented trait and points every method's `implements` at it.
+    fn set_trait(&mut self, trait_ty: &Type) {
+        let (base, args) = split_generic_path(trait_ty).unzip();
+        self.trait_path = Some(type_to_string(trait_ty));
+        self.trait_generic_base = base;
+        self.trait_generic_args = args.unwrap_or_default();
+        let trait_name = match trait_ty {
+            Type::Path(path) => path_base(&path.path),
+            other => type_to_string(other),
+        };
+        for method in &mut self.methods {
+            method.implements = Some(ImplementedMethod {
+                trait_name: trait_name.clone(),
+                method: method.name.clone(),
+            });
+        }
+        self.trait_name = Some(trait_name);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ExtractModule {
+    file: String,
+    parts: Vec<String>,
+    consts: Vec<ExtractField>,
+    enums: Vec<ExtractEnum>,
+    structs: Vec<ExtractStruct>,
+    traits: Vec<ExtractTrait>,
+    functions: Vec<ExtractFunction>,
+    reexports: Vec<ExtractReexport>,
+    #[serde(rename = "pendingMethods")]
+    pending_methods: Vec<PendingMethods>,
+    issues: Vec<SkipIssue>,
+    /// `--flatten`: on the root module, every item moved into it from a child module.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    flattened: Vec<FlattenedName>,
+    /// `--with-deps`: every item copied into this module from a path dependency it re-exports.
+    #[serde(rename = "dependencyItems", skip_serializing_if = "Vec::is_empty")]
+    dependency_items: Vec<DependencyItem>,
+    /// The module named by `[package.metadata.tsuba].prelude`: the crate's intended public
+    /// surface, which generators may emit as the primary, flat API.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    prelude: bool,
+    /// Position of the module in the depth-first walk of `mod` declarations, for
+    /// `--preserve-source-order`.
+    #[serde(skip)]
+    source_order: usize,
+}
+
+/// An item `--flatten` moved into the root module: its new `name` there, and the module and
+/// Rust name it is defined under, so bindings can still reach `inner::Thing`.
+#[derive(Serialize, Deserialize, Clone)]
+struct FlattenedName {
+    name: String,
+    module: Vec<String>,
+    #[serde(rename = "rustName")]
+    rust_name: String,
+}
+
+/// An item `--with-deps` copied in for a `pub use dep::path::Item;`: its `name` here, and the
+/// crate, module, and Rust name it is defined under.
+#[derive(Serialize, Deserialize, Clone)]
+struct DependencyItem {
+    name: String,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    module: Vec<String>,
+    #[serde(rename = "rustName")]
+    rust_name: String,
+}
+
+#[derive(Serialize)]
+pub struct ExtractOutput {
+    schema: u32,
+    /// SHA-256 (lowercase hex) of the compact document serialized without this field.
+    #[serde(rename = "contentHash", skip_serializing_if = "Option::is_none")]
+    content_hash: Option<String>,
+    #[serde(rename = "crate")]
+    crate_meta: CrateMeta,
+    modules: Vec<ExtractModule>,
+    /// `--emit-index`: every defined public name, with where it is defined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<BTreeMap<String, Vec<IndexLocation>>>,
+}
+
+/// One definition of a name in the `--emit-index` map. Re-exports are not definitions; their
+/// `sourceModule` already points here.
+#[derive(Serialize)]
+struct IndexLocation {
+    module: Vec<String>,
+    kind: &'static str,
+    #[serde(flatten)]
+    span: SourceSpan,
+}
+
+/// One `--json-lines-per-item` record: a single item with the crate, module, and full path it
+/// belongs to.
+#[derive(Serialize)]
+struct ItemRecord<'a> {
+    schema: u32,
+    #[serde(rename = "crate")]
+    crate_name: &'a str,
+    module: &'a [String],
+    file: &'a str,
+    kind: &'static str,
+    /// `crate_name::module::Item` (`crate_name::module::Type::method` for methods); absent for
+    /// issues.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    /// Impl target name, for methods.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<&'a str>,
+    item: RecordItem<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RecordItem<'a> {
+    Const(&'a ExtractField),
+    Enum(&'a ExtractEnum),
+    Struct(&'a ExtractStruct),
+    Trait(&'a ExtractTrait),
+    Function(&'a ExtractFunction),
+    Reexport(&'a ExtractReexport),
+    Issue(&'a SkipIssue),
+}
+
+fn macro_stub(name: String, span: SourceSpan) -> ExtractFunction {
+    ExtractFunction {
+        kind: "macro".to_string(),
+        name,
+        params: vec![ExtractField::new("tokens".to_string(), "Tokens".to_string())],
+        return_type: "Tokens".to_string(),
+        span,
+        ..Default::default()
+    }
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+fn normalize_ws(text: String) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn normalize_path_for_json(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn relative_module_label(crate_root: &Path, canonical: &Path) -> String {
+    if let Ok(rel) = canonical.strip_prefix(crate_root) {
+        let out = normalize_path_for_json(rel);
+        if out.is_empty() {
+            return ".".to_string();
+        }
+        return out;
+    }
+    normalize_path_for_json(canonical)
+}
+
+fn type_to_string(ty: &Type) -> String {
+    normalize_ws(ty.to_token_stream().to_string())
+}
+
+/// A path's segment names joined with `::`, without generic arguments.
+fn path_base(path: &syn::Path) -> String {
+    let mut base = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::");
+    if path.leading_colon.is_some() {
+        base.insert_str(0, "::");
+    }
+    base
+}
+
+/// `Option<T>`, bare or as `std::option::Option` / `core::option::Option`.
+fn is_option_type(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    if path.qself.is_some() {
+        return false;
+    }
+    let names = path.path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+    let qualified = match names.as_slice() {
+        [name] => name == "Option",
+        [krate, module, name] => matches!(krate.as_str(), "std" | "core") && module == "option" && name == "Option",
+        _ => false,
+    };
+    let has_one_type_arg = path.path.segments.last().is_some_and(|segment| {
+        matches!(&segment.arguments, PathArguments::AngleBracketed(generic) if generic.args.len() == 1)
+    });
+    qualified && has_one_type_arg
+}
+
+/// Splits a generic path type like `std::collections::HashMap<K, V>` into its base path and the
+/// argument texts of the last segment. Associated-type bindings are kept as text
+/// (`Iterator<Item = i32>` gives `["Item = i32"]`). `None` for non-path types and paths without
+/// angle-bracketed arguments.
+fn split_generic_path(ty: &Type) -> Option<(String, Vec<String>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(generic) = &last.arguments else {
+        return None;
+    };
+    let base = path_base(&path.path);
+    let args = generic
+        .args
+        .iter()
+        .map(|arg| normalize_ws(arg.to_token_stream().to_string()))
+        .collect();
+    Some((base, args))
+}
+
+/// Structured type tree emitted next to type texts (`typeRepr` / `returnTypeRepr`), so generators
+/// can map types without re-parsing Rust syntax.
+#[derive(Serialize, Deserialize, Clone)]
+struct TypeRepr {
+    #[serde(flatten)]
+    shape: TypeShape,
+    /// Transparent smart-pointer wrappers peeled off this type, outermost first:
+    /// `Arc<Vec<T>>` is the `Vec<T>` path with `wrappers: ["Arc"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    wrappers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum TypeShape {
+    Path(PathRepr),
+    Reference {
+        mutable: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        lifetime: Option<String>,
+        inner: Box<TypeRepr>,
+    },
+    Pointer {
+        mutable: bool,
+        inner: Box<TypeRepr>,
+    },
+    Tuple {
+        elements: Vec<TypeRepr>,
+    },
+    Slice {
+        inner: Box<TypeRepr>,
+    },
+    Array {
+        inner: Box<TypeRepr>,
+        len: String,
+        /// The enclosing function's const generic parameter that `len` names (`[u8; N]`).
+        #[serde(rename = "constParam", skip_serializing_if = "Option::is_none")]
+        const_param: Option<String>,
+    },
+    Fn {
+        params: Vec<TypeRepr>,
+        #[serde(rename = "returnType")]
+        return_type: Box<TypeRepr>,
+    },
+    Dyn {
+        bounds: Vec<PathRepr>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lifetimes: Vec<String>,
+    },
+    Impl {
+        bounds: Vec<PathRepr>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lifetimes: Vec<String>,
+    },
+    /// Bare `Self`, left for generators to substitute with the implementing type.
+    #[serde(rename = "self")]
+    SelfType,
+    /// An associated type of the enclosing trait or impl: `Self::Output`, or `<Self as
+    /// Trait>::Output` with `trait` set. Generators substitute it per impl from the impl's
+    /// `types`. `args` and `lifetimes` are a generic associated type's arguments (`Self::Item<'a>`).
+    AssocType {
+        name: String,
+        #[serde(rename = "trait", skip_serializing_if = "Option::is_none")]
+        trait_path: Option<PathRepr>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<TypeRepr>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        lifetimes: Vec<String>,
+    },
+    Never,
+    Infer,
+    /// Anything not modeled above (qualified `<T as Trait>::X` paths, type macros), as text.
+    Other {
+        text: String,
+    },
+}
+
+/// A path type or trait bound: segment names plus the last segment's generic arguments.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct PathRepr {
+    path: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    args: Vec<TypeRepr>,
+    /// Associated-type bindings (`Iterator<Item = i32>`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    bindings: Vec<TypeBinding>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    /// Const arguments and associated-type constraints, as text.
+    #[serde(rename = "otherArgs", skip_serializing_if = "Vec::is_empty")]
+    other_args: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct TypeBinding {
+    name: String,
+    /// Generic arguments of a generic associated type (`LendingIterator<Item<'a> = &'a str>`
+    /// binds `Item` with `lifetimes: ["'a"]`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<TypeRepr>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lifetimes: Vec<String>,
+    #[serde(rename = "type")]
+    ty: TypeRepr,
+}
+
+impl TypeRepr {
+    fn new(shape: TypeShape) -> TypeRepr {
+        TypeRepr {
+            shape,
+            wrappers: Vec::new(),
+        }
+    }
+
+    fn unit() -> TypeRepr {
+        TypeRepr::new(TypeShape::Tuple { elements: Vec::new() })
+    }
+
+    /// Whether an `impl Trait` appears anywhere in this type (`&mut impl Write`,
+    /// `Option<impl Fn()>`).
+    fn contains_impl(&self) -> bool {
+        match &self.shape {
+            TypeShape::Impl { .. } => true,
+            TypeShape::Path(path) => path.contains_impl(),
+            TypeShape::Reference { inner, .. }
+            | TypeShape::Pointer { inner, .. }
+            | TypeShape::Slice { inner }
+            | TypeShape::Array { inner, .. } => inner.contains_impl(),
+            TypeShape::Tuple { elements } => elements.iter().any(TypeRepr::contains_impl),
+            TypeShape::Fn { params, return_type } => {
+                params.iter().any(TypeRepr::contains_impl) || return_type.contains_impl()
+            }
+            TypeShape::Dyn { bounds, .. } => bounds.iter().any(PathRepr::contains_impl),
+            TypeShape::AssocType { args, .. } => args.iter().any(TypeRepr::contains_impl),
+            TypeShape::SelfType | TypeShape::Never | TypeShape::Infer | TypeShape::Other { .. } => false,
+        }
+    }
+
+    /// Sets `constParam` on every array whose length is one of `names` (`[u8; N]`, `[[T; N]; 2]`).
+    fn link_const_params(&mut self, names: &[String]) {
+        match &mut self.shape {
+            TypeShape::Array {
+                inner,
+                len,
+                const_param,
+            } => {
+                if names.contains(len) {
+                    *const_param = Some(len.clone());
+                }
+                inner.link_const_params(names);
+            }
+            TypeShape::Path(path) => path.link_const_params(names),
+            TypeShape::Reference { inner, .. } | TypeShape::Pointer { inner, .. } | TypeShape::Slice { inner } => {
+                inner.link_const_params(names)
+            }
+            TypeShape::Tuple { elements } => elements.iter_mut().for_each(|e| e.link_const_params(names)),
+            TypeShape::Fn { params, return_type } => {
+                params.iter_mut().for_each(|p| p.link_const_params(names));
+                return_type.link_const_params(names);
+            }
+            TypeShape::Dyn { bounds, .. } | TypeShape::Impl { bounds, .. } => {
+                bounds.iter_mut().for_each(|b| b.link_const_params(names))
+            }
+            TypeShape::AssocType { args, .. } => args.iter_mut().for_each(|a| a.link_const_params(names)),
+            TypeShape::SelfType | TypeShape::Never | TypeShape::Infer | TypeShape::Other { .. } => {}
+        }
+    }
+}
+
+impl PathRepr {
+    fn contains_impl(&self) -> bool {
+        self.args.iter().any(TypeRepr::contains_impl) || self.bindings.iter().any(|b| b.ty.contains_impl())
+    }
+
+    fn link_const_params(&mut self, names: &[String]) {
+        self.args.iter_mut().for_each(|arg| arg.link_const_params(names));
+        self.bindings.iter_mut().for_each(|b| b.ty.link_const_params(names));
+    }
+}
+
+/// Single-argument smart pointers (and `Cow`) that add no facade-visible shape.
+const TRANSPARENT_WRAPPERS: &[&str] = &["Box", "Rc", "Arc", "Cow"];
+
+/// Standard-library crates whose qualified wrapper paths (`std::sync::Arc`) are recognized;
+/// any other qualified `Box` is a user type.
+const STD_CRATES: &[&str] = &["std", "alloc", "core"];
+
+fn path_repr(path: &syn::Path) -> PathRepr {
+    let mut out = PathRepr {
+        path: path.segments.iter().map(|segment| segment.ident.to_string()).collect(),
+        args: Vec::new(),
+        bindings: Vec::new(),
+        lifetimes: Vec::new(),
+        other_args: Vec::new(),
+    };
+    let generic = match path.segments.last().map(|segment| &segment.arguments) {
+        Some(PathArguments::AngleBracketed(generic)) => generic,
+        // `Fn(A, B) -> R` sugar: inputs become args and the output an `Output` binding.
+        Some(PathArguments::Parenthesized(sugar)) => {
+            out.args = sugar.inputs.iter().map(type_repr).collect();
+            out.bindings.push(TypeBinding {
+                name: "Output".to_string(),
+                args: Vec::new(),
+                lifetimes: Vec::new(),
+                ty: match &sugar.output {
+                    ReturnType::Type(_, ty) => type_repr(ty),
+                    ReturnType::Default => TypeRepr::unit(),
+                },
+            });
+            return out;
+        }
+        _ => return out,
+    };
+    for arg in &generic.args {
+        match arg {
+            syn::GenericArgument::Type(ty) => out.args.push(type_repr(ty)),
+            syn::GenericArgument::Lifetime(lifetime) => out.lifetimes.push(lifetime.to_string()),
+            syn::GenericArgument::AssocType(assoc) => {
+                let mut binding = TypeBinding {
+                    name: assoc.ident.to_string(),
+                    args: Vec::new(),
+                    lifetimes: Vec::new(),
+                    ty: type_repr(&assoc.ty),
+                };
+                for arg in assoc.generics.iter().flat_map(|generics| &generics.args) {
+                    match arg {
+                        syn::GenericArgument::Type(ty) => binding.args.push(type_repr(ty)),
+                        syn::GenericArgument::Lifetime(lifetime) => binding.lifetimes.push(lifetime.to_string()),
+                        _ => {}
+                    }
+                }
+                out.bindings.push(binding);
+            }
+            other => out.other_args.push(normalize_ws(other.to_token_stream().to_string())),
+        }
+    }
+    out
+}
+
+/// The wrapper name when `path` is `Box<T>` / `Rc<T>` / `Arc<T>` / `Cow<'a, T>` (bare or
+/// std-qualified) with a single type argument. `Cow`'s lifetime is dropped; its inner node is the
+/// borrowed form it owns (`Cow<'a, str>` is `str`).
+fn transparent_wrapper(path: &syn::Path, repr: &PathRepr) -> Option<String> {
+    let name = repr.path.last()?;
+    let qualified_ok = repr.path.len() == 1 || STD_CRATES.contains(&repr.path[0].as_str());
+    let single_arg = repr.args.len() == 1 && repr.bindings.is_empty() && repr.other_args.is_empty();
+    let lifetimes_ok = repr.lifetimes.is_empty() || name == "Cow";
+    let is_wrapper = TRANSPARENT_WRAPPERS.contains(&name.as_str()) && path.leading_colon.is_none();
+    (is_wrapper && qualified_ok && single_arg && lifetimes_ok).then(|| name.clone())
+}
+
+fn bounds_repr<'a>(
+    bounds: impl Iterator<Item = &'a syn::TypeParamBound>,
+) -> (Vec<PathRepr>, Vec<String>) {
+    let mut traits = Vec::new();
+    let mut lifetimes = Vec::new();
+    for bound in bounds {
+        match bound {
+            syn::TypeParamBound::Trait(trait_bound) => traits.push(path_repr(&trait_bound.path)),
+            syn::TypeParamBound::Lifetime(lifetime) => lifetimes.push(lifetime.to_string()),
+            _ => {}
+        }
+    }
+    (traits, lifetimes)
+}
+
+fn type_repr(ty: &Type) -> TypeRepr {
+    match ty {
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => {
+            TypeRepr::new(TypeShape::SelfType)
+        }
+        Type::Path(path)
+            if path.qself.is_none() && path.path.segments.len() == 2 && path.path.segments[0].ident == "Self" =>
+        {
+            assoc_type_repr(&path.path, None)
+        }
+        Type::Path(syn::TypePath { qself: Some(qself), path })
+            if is_self_type(&qself.ty) && qself.position > 0 && path.segments.len() == qself.position + 1 =>
+        {
+            let trait_path = syn::Path {
+                leading_colon: path.leading_colon,
+                segments: path.segments.iter().take(qself.position).cloned().collect(),
+            };
+            assoc_type_repr(path, Some(path_repr(&trait_path)))
+        }
+        Type::Path(path) if path.qself.is_none() => {
+            let mut repr = path_repr(&path.path);
+            if let Some(wrapper) = transparent_wrapper(&path.path, &repr) {
+                let mut inner = repr.args.remove(0);
+                inner.wrappers.insert(0, wrapper);
+                return inner;
+            }
+            TypeRepr::new(TypeShape::Path(repr))
+        }
+        Type::Reference(reference) => TypeRepr::new(TypeShape::Reference {
+            mutable: reference.mutability.is_some(),
+            lifetime: reference.lifetime.as_ref().map(|lifetime| lifetime.to_string()),
+            inner: Box::new(type_repr(&reference.elem)),
+        }),
+        Type::Ptr(ptr) => TypeRepr::new(TypeShape::Pointer {
+            mutable: ptr.mutability.is_some(),
+            inner: Box::new(type_repr(&ptr.elem)),
+        }),
+        Type::Tuple(tuple) => TypeRepr::new(TypeShape::Tuple {
+            elements: tuple.elems.iter().map(type_repr).collect(),
+        }),
+        Type::Slice(slice) => TypeRepr::new(TypeShape::Slice {
+            inner: Box::new(type_repr(&slice.elem)),
+        }),
+        Type::Array(array) => TypeRepr::new(TypeShape::Array {
+            inner: Box::new(type_repr(&array.elem)),
+            len: normalize_ws(array.len.to_token_stream().to_string()),
+            const_param: None,
+        }),
+        Type::BareFn(bare_fn) => TypeRepr::new(TypeShape::Fn {
+            params: bare_fn.inputs.iter().map(|arg| type_repr(&arg.ty)).collect(),
+            return_type: Box::new(match &bare_fn.output {
+                ReturnType::Type(_, ty) => type_repr(ty),
+                ReturnType::Default => TypeRepr::unit(),
+            }),
+        }),
+        Type::TraitObject(object) => {
+            let (bounds, lifetimes) = bounds_repr(object.bounds.iter());
+            TypeRepr::new(TypeShape::Dyn { bounds, lifetimes })
+        }
+        Type::ImplTrait(imp) => {
+            let (bounds, lifetimes) = bounds_repr(imp.bounds.iter());
+            TypeRepr::new(TypeShape::Impl { bounds, lifetimes })
+        }
+        Type::Paren(paren) => type_repr(&paren.elem),
+        Type::Group(group) => type_repr(&group.elem),
+        Type::Never(_) => TypeRepr::new(TypeShape::Never),
+        Type::Infer(_) => TypeRepr::new(TypeShape::Infer),
+        other => TypeRepr::new(TypeShape::Other {
+            text: type_to_string(other),
+        }),
+    }
+}
+
+fn is_self_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self"))
+}
+
+/// The `assoc_type` node for a path whose last segment names an associated type of `Self`.
+fn assoc_type_repr(path: &syn::Path, trait_path: Option<PathRepr>) -> TypeRepr {
+    let repr = path_repr(path);
+    TypeRepr::new(TypeShape::AssocType {
+        name: repr.path.last().cloned().unwrap_or_default(),
+        trait_path,
+        args: repr.args,
+        lifetimes: repr.lifetimes,
+    })
+}
+
+fn return_type_to_string(ret: &ReturnType) -> String {
+    match ret {
+        ReturnType::Default => "()".to_string(),
+        ReturnType::Type(_, ty) => type_to_string(ty),
+    }
+}
+
+fn parse_type_params(
+    generics: &syn::Generics,
+    file: &str,
+    owner_kind: &str,
+    owner_name: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    for param in &generics.params {
+        match param {
+            GenericParam::Type(tp) => out.push(tp.ident.to_string()),
+            GenericParam::Lifetime(lp) => issues.push(SkipIssue {
+                file: file.to_string(),
+                kind: "generic".to_string(),
+                code: None,
+                snippet: lp.to_token_stream().to_string(),
+                reason: format!(
+                    "{owner_kind} '{owner_name}' lifetime generic parameters are not representable in TS facades; they are recorded in `lifetimes`."
+                ),
+            }),
+            GenericParam::Const(cp) => issues.push(SkipIssue {
+                file: file.to_string(),
+                kind: "generic".to_string(),
+                code: None,
+                snippet: cp.to_token_stream().to_string(),
+                reason: format!(
+                    "{owner_kind} '{owner_name}' const generic parameters are not representable in TS facades{}.",
+                    if owner_kind == "Function" {
+                        "; they are recorded in `constParams`"
+                    } else {
+                        "; they are recorded in `generics`"
+                    }
+                ),
+            }),
+        }
+    }
+    out
+}
+
+/// Const generic parameters in declaration order, with their type text.
+fn const_params(generics: &syn::Generics) -> Vec<ConstParam> {
+    generics
+        .const_params()
+        .map(|cp| ConstParam {
+            name: cp.ident.to_string(),
+            type_text: type_to_string(&cp.ty),
+        })
+        .collect()
+}
+
+fn bound_texts(bounds: impl IntoIterator<Item = impl ToTokens>) -> Vec<String> {
+    bounds
+        .into_iter()
+        .map(|bound| normalize_ws(bound.to_token_stream().to_string()))
+        .collect()
+}
+
+/// Path nodes for the trait bounds in `bounds`, in order, so `std::fmt::Debug` and
+/// `crate::traits::Reader` keep their segments and generic arguments. Lifetime bounds and
+/// relaxed bounds (`?Sized`), which add no requirement, are left out.
+fn trait_bound_paths<'a>(bounds: impl IntoIterator<Item = &'a syn::TypeParamBound>) -> Vec<PathRepr> {
+    bounds
+        .into_iter()
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(trait_bound) if matches!(trait_bound.modifier, syn::TraitBoundModifier::None) => {
+                Some(path_repr(&trait_bound.path))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Inline parameter bounds in declaration order, then `where` predicates as written.
+/// Unbounded parameters are omitted.
+fn generic_bounds(generics: &syn::Generics) -> Vec<GenericBound> {
+    let mut out = Vec::new();
+    for param in &generics.params {
+        let (bounded, bounds, paths) = match param {
+            GenericParam::Type(tp) => (tp.ident.to_string(), bound_texts(&tp.bounds), trait_bound_paths(&tp.bounds)),
+            GenericParam::Lifetime(lp) => (lp.lifetime.to_string(), bound_texts(&lp.bounds), Vec::new()),
+            GenericParam::Const(_) => continue,
+        };
+        if !bounds.is_empty() {
+            out.push(GenericBound { bounded, bounds, paths });
+        }
+    }
+    for predicate in generics.where_clause.iter().flat_map(|clause| &clause.predicates) {
+        let (bounded, bounds, paths) = match predicate {
+            syn::WherePredicate::Type(pt) => {
+                let ty = type_to_string(&pt.bounded_ty);
+                let bounded = match &pt.lifetimes {
+                    Some(binder) => normalize_ws(format!("{} {ty}", binder.to_token_stream())),
+                    None => ty,
+                };
+                (bounded, bound_texts(&pt.bounds), trait_bound_paths(&pt.bounds))
+            }
+            syn::WherePredicate::Lifetime(pl) => (pl.lifetime.to_string(), bound_texts(&pl.bounds), Vec::new()),
+            _ => continue,
+        };
+        out.push(GenericBound { bounded, bounds, paths });
+    }
+    out
+}
+
+fn generic_defaults(generics: &syn::Generics) -> Vec<GenericDefault> {
+    generics
+        .type_params()
+        .filter_map(|param| {
+            Some(GenericDefault {
+                param: param.ident.to_string(),
+                default: type_to_string(param.default.as_ref()?),
+            })
+        })
+        .collect()
+}
+
+fn lifetime_params(generics: &syn::Generics) -> Vec<String> {
+    generics.lifetimes().map(|param| param.lifetime.to_string()).collect()
+}
+
+fn generic_params(generics: &syn::Generics) -> Vec<GenericParamDecl> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(lp) => GenericParamDecl {
+                kind: "lifetime".to_string(),
+                name: lp.lifetime.to_string(),
+                type_text: None,
+                default: None,
+            },
+            GenericParam::Type(tp) => GenericParamDecl {
+                kind: "type".to_string(),
+                name: tp.ident.to_string(),
+                type_text: None,
+                default: tp.default.as_ref().map(type_to_string),
+            },
+            GenericParam::Const(cp) => GenericParamDecl {
+                kind: "const".to_string(),
+                name: cp.ident.to_string(),
+                type_text: Some(type_to_string(&cp.ty)),
+                default: cp.default.as_ref().map(|expr| normalize_ws(expr.to_token_stream().to_string())),
+            },
+        })
+        .collect()
+}
+
+fn export_name(attrs: &[syn::Attribute], options: &Options) -> Option<String> {
+    tool_attrs(attrs, &options.attr_namespace).rename
+}
+
+/// `--emit-attributes`: each outer attribute's normalized token text, without the `#[...]`
+/// brackets (`derive (Clone , Debug)`, `must_use`). Doc comments (`#[doc = "..."]`) are left out;
+/// other `doc` attributes like `doc(hidden)` are kept.
+fn attributes(attrs: &[syn::Attribute], options: &Options) -> Vec<String> {
+    if !options.emit_attributes {
+        return Vec::new();
+    }
+    attrs
+        .iter()
+        .filter(|attr| matches!(attr.style, syn::AttrStyle::Outer))
+        .filter(|attr| !(attr.path().is_ident("doc") && matches!(attr.meta, Meta::NameValue(_))))
+        .map(|attr| normalize_ws(attr.meta.to_token_stream().to_string()))
+        .collect()
+}
+
+/// Function attributes that only affect code generation, recognized with their arguments:
+/// `inline` (`inline(always)`, `inline(never)`), `cold`, and `track_caller`.
+const FN_ATTRS: &[&str] = &["inline", "cold", "track_caller"];
+
+/// `--emit-attributes`: the `FN_ATTRS` on a function, in attribute order, as compact text
+/// (`inline(always)`, `cold`). They do not change the facade, but documentation generators can
+/// surface them.
+fn fn_attrs(attrs: &[syn::Attribute], options: &Options) -> Vec<String> {
+    if !options.emit_attributes {
+        return Vec::new();
+    }
+    attrs
+        .iter()
+        .filter(|attr| matches!(attr.style, syn::AttrStyle::Outer))
+        .filter_map(|attr| {
+            let name = FN_ATTRS.iter().find(|name| attr.path().is_ident(name))?;
+            Some(match &attr.meta {
+                Meta::List(list) => format!("{name}({})", list.tokens.to_string().replace(' ', "")),
+                _ => name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Doc comment text: the `///` / `/** */` lines (`#[doc = "..."]`) joined with newlines, with
+/// the single space after `///` removed. `None` when there are no doc lines.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        let Meta::NameValue(pair) = &attr.meta else {
+            continue;
+        };
+        if !pair.path.is_ident("doc") {
+            continue;
+        }
+        if let Expr::Lit(ExprLit { lit: Lit::Str(text), .. }) = &pair.value {
+            for line in text.value().split('\n') {
+                lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end().to_string());
+            }
+        }
+    }
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+    while lines.first().is_some_and(String::is_empty) {
+        lines.remove(0);
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// `--extract-examples`: the code blocks of the doc comment that rustdoc treats as Rust (doc
+/// tests), in order. Lines are kept verbatim, including hidden `# ` lines; only the fence's own
+/// indentation is removed.
+fn doc_examples(attrs: &[syn::Attribute], options: &Options) -> Vec<String> {
+    if !options.extract_examples {
+        return Vec::new();
+    }
+    doc_comment(attrs).map(|docs| rust_code_blocks(&docs)).unwrap_or_default()
+}
+
+/// Rustdoc attributes that mark a fenced block as Rust; `edition20xx` and `ignore-<target>` too.
+const RUST_FENCE_TAGS: &[&str] = &["rust", "ignore", "should_panic", "no_run", "compile_fail", "test_harness", "standalone_crate"];
+
+/// Fenced code blocks (```` ``` ```` or `~~~`, three or more) of Markdown text whose info string
+/// is empty or has a Rust tag, as rustdoc decides: ```` ```rust,no_run ```` is Rust, ```` ```text ````
+/// is not. An unclosed block runs to the end of the text.
+fn rust_code_blocks(docs: &str) -> Vec<String> {
+    struct Fence {
+        marker: char,
+        len: usize,
+        indent: usize,
+        rust: bool,
+        lines: Vec<String>,
+    }
+    let mut blocks = Vec::new();
+    let mut open: Option<Fence> = None;
+    for line in docs.lines() {
+        let text = line.trim_start_matches(' ');
+        let indent = line.len() - text.len();
+        let marker = text.chars().next().filter(|c| matches!(c, '`' | '~'));
+        let len = marker.map_or(0, |marker| text.chars().take_while(|c| *c == marker).count());
+        match &mut open {
+            Some(fence) => {
+                if marker == Some(fence.marker) && len >= fence.len && text[len..].trim().is_empty() {
+                    if fence.rust {
+                        blocks.push(fence.lines.join("\n"));
+                    }
+                    open = None;
+                } else {
+                    let strip = indent.min(fence.indent);
+                    fence.lines.push(line[strip..].to_string());
+                }
+            }
+            None if len >= 3 && indent < 4 => {
+                let info = text[len..].trim();
+                if marker == Some('`') && info.contains('`') {
+                    continue;
+                }
+                let mut tags = info.split(|c: char| c == ',' || c.is_whitespace()).filter(|tag| !tag.is_empty());
+                let rust = info.is_empty()
+                    || tags.any(|tag| {
+                        RUST_FENCE_TAGS.contains(&tag) || tag.starts_with("edition") || tag.starts_with("ignore-")
+                    });
+                open = Some(Fence {
+                    marker: marker.unwrap_or('`'),
+                    len,
+                    indent,
+                    rust,
+                    lines: Vec::new(),
+                });
+            }
+            None => {}
+        }
+    }
+    if let Some(fence) = open.filter(|fence| fence.rust) {
+        blocks.push(fence.lines.join("\n"));
+    }
+    blocks
+}
+
+/// Collects `#[doc(alias = "a")]` and `#[doc(alias("a", "b"))]` values.
+fn doc_aliases(attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut out = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+        let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        for meta in metas {
+            match &meta {
+                Meta::NameValue(pair) if pair.path.is_ident("alias") => {
+                    if let Expr::Lit(ExprLit { lit: Lit::Str(text), .. }) = &pair.value {
+                        out.push(text.value());
+                    }
+                }
+                Meta::List(list) if list.path.is_ident("alias") => {
+                    let names = list.parse_args_with(Punctuated::<syn::LitStr, Token![,]>::parse_terminated);
+                    if let Ok(names) = names {
+                        out.extend(names.iter().map(syn::LitStr::value));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+/// Whether a receiver of type `ty` (`Self` for `self` and `mut self`) moves the value: `Self`
+/// and owning pointers such as `Box<Self>` or `Rc<Self>` do, references do not. `Pin<P>`
+/// follows its pointer, so `self: Pin<&mut Self>` borrows and `self: Pin<Box<Self>>` consumes.
+fn receiver_consumes(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(_) => false,
+        Type::Paren(paren) => receiver_consumes(&paren.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(last) = path.path.segments.last() else {
+                return true;
+            };
+            match &last.arguments {
+                PathArguments::AngleBracketed(generic) if last.ident == "Pin" => match generic.args.first() {
+                    Some(syn::GenericArgument::Type(inner)) => receiver_consumes(inner),
+                    _ => true,
+                },
+                _ => true,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// `T` for `Box<dyn Future<Output = T>>`, optionally pinned (`Pin<Box<...>>`) and with extra
+/// bounds (`+ Send + 'a`). `Box`, `Pin`, and `Future` may be written bare or by their `std`/`core`/
+/// `alloc` paths; `Future` also as `futures::Future`. Type aliases such as `BoxFuture` are not
+/// followed.
+fn boxed_future_output(ty: &Type) -> Option<&Type> {
+    const PIN: &[&[&str]] = &[&["Pin"], &["std", "pin", "Pin"], &["core", "pin", "Pin"]];
+    const BOX: &[&[&str]] = &[&["Box"], &["std", "boxed", "Box"], &["alloc", "boxed", "Box"]];
+    const FUTURE: &[&[&str]] = &[
+        &["Future"],
+        &["std", "future", "Future"],
+        &["core", "future", "Future"],
+        &["futures", "Future"],
+        &["futures", "future", "Future"],
+    ];
+    fn is_one_of(path: &syn::Path, known: &[&[&str]]) -> bool {
+        let names = path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+        known.iter().any(|known| names == *known)
+    }
+    fn last_args(path: &syn::Path) -> Option<Vec<&syn::GenericArgument>> {
+        match &path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(args) => Some(args.args.iter().collect()),
+            _ => None,
+        }
+    }
+    fn wrapped<'a>(ty: &'a Type, known: &[&[&str]]) -> Option<&'a Type> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        if path.qself.is_some() || !is_one_of(&path.path, known) {
+            return None;
+        }
+        match last_args(&path.path)?.as_slice() {
+            [syn::GenericArgument::Type(inner)] => Some(inner),
+            _ => None,
+        }
+    }
+    let boxed = wrapped(ty, PIN).unwrap_or(ty);
+    let Type::TraitObject(object) = wrapped(boxed, BOX)? else {
+        return None;
+    };
+    object.bounds.iter().find_map(|bound| match bound {
+        syn::TypeParamBound::Trait(bound) if is_one_of(&bound.path, FUTURE) => {
+            last_args(&bound.path)?.into_iter().find_map(|arg| match arg {
+                syn::GenericArgument::AssocType(binding) if binding.ident == "Output" => Some(&binding.ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    })
+}
+
+fn parse_signature(
+    sig: &Signature,
+    attrs: &[syn::Attribute],
+    options: &Options,
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+    kind: &str,
+    span: SourceSpan,
+) -> ExtractFunction {
+    let type_params = parse_type_params(&sig.generics, file, "Function", &sig.ident.to_string(), issues);
+    let mut params = Vec::new();
+    let mut consumes_self = false;
+    let mut has_receiver = false;
+    for input in &sig.inputs {
+        match input {
+            FnArg::Receiver(receiver) => {
+                has_receiver = true;
+                consumes_self = receiver_consumes(&receiver.ty);
+                let name = if receiver.reference.is_some() && receiver.mutability.is_some() {
+                    "&mut self".to_string()
+                } else if receiver.reference.is_some() {
+                    "&self".to_string()
+                } else {
+                    "self".to_string()
+                };
+                let mut param = ExtractField::new(name, "self".to_string());
+                param.attributes = attributes(&receiver.attrs, options);
+                params.push(param);
+            }
+            FnArg::Typed(arg) => {
+                let name = if let Pat::Ident(ident) = arg.pat.as_ref() {
+                    ident.ident.to_string()
+                } else {
+                    issues.push(SkipIssue {
+                        file: file.to_string(),
+                        kind: "param".to_string(),
+                        code: None,
+                        snippet: arg.pat.to_token_stream().to_string(),
+                        reason: "Non-identifier function parameters are not representable in TS facades and were replaced by an 'unsupported' name.".to_string(),
+                    });
+                    "unsupported".to_string()
+                };
+                // Parameter attributes (`#[allow(unused)] x: i32`) sit on `arg.attrs`, not the
+                // pattern, so the name and type read the same with or without them.
+                let mut param = ExtractField::of_type(name, arg.ty.as_ref());
+                param.optional = is_option_type(arg.ty.as_ref());
+                param.attributes = attributes(&arg.attrs, options);
+                params.push(param);
+            }
+        }
+    }
+    // Only the trailing run stays optional: clear every flag up to the last required param.
+    if let Some(last_required) = params.iter().rposition(|param| !param.optional) {
+        for param in &mut params[..last_required] {
+            param.optional = false;
+        }
+    }
+    let (return_generic_base, return_generic_args) = match &sig.output {
+        ReturnType::Type(_, ty) => match split_generic_path(ty) {
+            Some((base, args)) => (Some(base), args),
+            None => (None, Vec::new()),
+        },
+        ReturnType::Default => (None, Vec::new()),
+    };
+    let is_generic = !sig.generics.params.is_empty()
+        || params.iter().any(|param| param.type_repr.as_ref().is_some_and(TypeRepr::contains_impl));
+    let return_type_repr = match &sig.output {
+        ReturnType::Type(_, ty) => type_repr(ty),
+        ReturnType::Default => TypeRepr::unit(),
+    };
+    let is_constructor = !has_receiver && matches!(return_type_repr.shape, TypeShape::SelfType);
+    let mut function = ExtractFunction {
+        kind: kind.to_string(),
+        name: sig.ident.to_string(),
+        type_params,
+        lifetimes: lifetime_params(&sig.generics),
+        const_params: const_params(&sig.generics),
+        generics: generic_params(&sig.generics),
+        arity: GenericArity::of(&sig.generics),
+        params,
+        return_type: return_type_to_string(&sig.output),
+        return_generic_base,
+        return_generic_args,
+        return_type_repr: Some(return_type_repr),
+        consumes_self,
+        is_constructor,
+        is_async: sig.asyncness.is_some(),
+        variadic: sig.variadic.is_some(),
+        is_generic,
+        generic_bounds: generic_bounds(&sig.generics),
+        export_name: export_name(attrs, options),
+        doc_aliases: doc_aliases(attrs),
+        attributes: attributes(attrs, options),
+        fn_attrs: fn_attrs(attrs, options),
+        examples: doc_examples(attrs, options),
+        span,
+        ..Default::default()
+    };
+    if let (true, None, ReturnType::Type(_, ty)) = (options.recognize_futures, sig.asyncness, &sig.output) {
+        function.set_future_output(ty);
+    }
+    function.link_const_params();
+    function
+}
+
+impl ExtractFunction {
+    /// Sets `futureOutput` from a boxed-future return type (see `boxed_future_output`).
+    fn set_future_output(&mut self, return_type: &Type) {
+        if let Some(output) = boxed_future_output(return_type) {
+            self.future_output = Some(type_to_string(output));
+            self.future_output_repr = Some(type_repr(output));
+        }
+    }
+
+    /// Points array lengths in the signature at `constParams` (see `TypeRepr::link_const_params`).
+    /// Rerun after `--resolve-paths` rebuilds a type node.
+    fn link_const_params(&mut self) {
+        if self.const_params.is_empty() {
+            return;
+        }
+        let names = self.const_params.iter().map(|cp| cp.name.clone()).collect::<Vec<_>>();
+        let params = self.params.iter_mut().filter_map(|param| param.type_repr.as_mut());
+        for repr in params.chain(self.return_type_repr.as_mut()) {
+            repr.link_const_params(&names);
+        }
+    }
+}
+
+fn parse_const(item: &ItemConst, options: &Options, file: &str) -> ExtractField {
+    let mut out = ExtractField::of_type(item.ident.to_string(), item.ty.as_ref());
+    out.export_name = export_name(&item.attrs, options);
+    out.doc_aliases = doc_aliases(&item.attrs);
+    out.attributes = attributes(&item.attrs, options);
+    out.examples = doc_examples(&item.attrs, options);
+    out.set_value(&item.expr);
+    out.span = Some(source_span(file, item));
+    out
+}
+
+fn parse_struct(
+    item: &ItemStruct,
+    options: &Options,
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> ExtractStruct {
+    let type_params = parse_type_params(&item.generics, file, "Struct", &item.ident.to_string(), issues);
+    let opaque = tool_attrs(&item.attrs, &options.attr_namespace).opaque;
+    let mut fields = Vec::new();
+    let mut skipped_private_fields = 0;
+    let mut private_field_names = Vec::new();
+    let kind = match &item.fields {
+        Fields::Named(_) => "named",
+        Fields::Unnamed(_) => "tuple",
+        Fields::Unit => "unit",
+    };
+    match &item.fields {
+        _ if opaque => {}
+        Fields::Named(named) => {
+            for field in &named.named {
+                if !is_public(&field.vis) {
+                    skipped_private_fields += 1;
+                    private_field_names.extend(field.ident.as_ref().map(ToString::to_string));
+                    continue;
+                }
+                if let Some(name) = &field.ident {
+                    let mut out = ExtractField::of_type(name.to_string(), &field.ty);
+                    out.export_name = export_name(&field.attrs, options);
+                    out.doc_aliases = doc_aliases(&field.attrs);
+                    out.docs = doc_comment(&field.attrs);
+                    out.attributes = attributes(&field.attrs, options);
+                    fields.push(out);
+                }
+            }
+        }
+        Fields::Unnamed(_) => issues.push(SkipIssue {
+            file: file.to_string(),
+            kind: "struct".to_string(),
+            code: None,
+            snippet: item.ident.to_string(),
+            reason: "Tuple structs are not representable as TS class fields and were emitted without fields."
+                .to_string(),
+        }),
+        Fields::Unit => {}
+    }
+    let hidden_field_types = item
+        .fields
+        .iter()
+        .filter(|field| opaque || kind == "tuple" || !is_public(&field.vis))
+        .map(|field| type_to_string(&field.ty))
+        .collect();
+    ExtractStruct {
+        kind: kind.to_string(),
+        name: item.ident.to_string(),
+        export_name: export_name(&item.attrs, options),
+        doc_aliases: doc_aliases(&item.attrs),
+        attributes: attributes(&item.attrs, options),
+        examples: doc_examples(&item.attrs, options),
+        type_params,
+        lifetimes: lifetime_params(&item.generics),
+        generics: generic_params(&item.generics),
+        arity: GenericArity::of(&item.generics),
+        generic_defaults: generic_defaults(&item.generics),
+        fields,
+        skipped_private_fields,
+        has_default: derives(&item.attrs, "Default"),
+        implemented_traits: Vec::new(),
+        recursive: false,
+        opaque,
+        transparent_inner: if opaque { None } else { transparent_inner(item) },
+        derived_properties: Vec::new(),
+        private_field_names,
+        hidden_field_types,
+        span: source_span(file, item),
+    }
+}
+
+fn parse_enum(
+    item: &ItemEnum,
+    options: &Options,
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> ExtractEnum {
+    let type_params = parse_type_params(&item.generics, file, "Enum", &item.ident.to_string(), issues);
+    let opaque = tool_attrs(&item.attrs, &options.attr_namespace).opaque;
+    let variant_field = |name: String, field: &syn::Field| {
+        let mut out = ExtractField::of_type(name, &field.ty);
+        out.export_name = export_name(&field.attrs, options);
+        out.doc_aliases = doc_aliases(&field.attrs);
+        out.docs = doc_comment(&field.attrs);
+        out.attributes = attributes(&field.attrs, options);
+        out
+    };
+    let mut variants = Vec::new();
+    for variant in item.variants.iter().filter(|_| !opaque) {
+        let fields = match &variant.fields {
+            Fields::Unit => Vec::new(),
+            Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|field| {
+                    let name = field
+                        .ident
+                        .as_ref()
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|| "value".to_string());
+                    variant_field(name, field)
+                })
+                .collect(),
+            Fields::Unnamed(unnamed) => unnamed
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(idx, field)| variant_field(format!("_{idx}"), field))
+                .collect(),
+        };
+        variants.push(ExtractEnumVariant {
+            name: variant.ident.to_string(),
+            docs: doc_comment(&variant.attrs),
+            fields,
+        });
+    }
+    ExtractEnum {
+        name: item.ident.to_string(),
+        export_name: export_name(&item.attrs, options),
+        doc_aliases: doc_aliases(&item.attrs),
+        attributes: attributes(&item.attrs, options),
+        examples: doc_examples(&item.attrs, options),
+        type_params,
+        lifetimes: lifetime_params(&item.generics),
+        generics: generic_params(&item.generics),
+        arity: GenericArity::of(&item.generics),
+        generic_defaults: generic_defaults(&item.generics),
+        variants,
+        is_c_like: !opaque && item.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit)),
+        serde_tagging: options.serde_aware.then(|| serde_tagging(&item.attrs)),
+        has_default: derives(&item.attrs, "Default"),
+        implemented_traits: Vec::new(),
+        recursive: false,
+        opaque,
+        span: source_span(file, item),
+    }
+}
+
+fn parse_trait(
+    item: &ItemTrait,
+    options: &Options,
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> ExtractTrait {
+    let mut type_params = parse_type_params(&item.generics, file, "Trait", &item.ident.to_string(), issues);
+    let mut methods = Vec::new();
+    let mut assoc_types = Vec::new();
+    let mut consts = Vec::new();
+
+    for trait_item in &item.items {
+        match trait_item {
+            TraitItem::Fn(method) => methods.push(parse_signature(
+                &method.sig,
+                &method.attrs,
+                options,
+                file,
+                issues,
+                "fn",
+                source_span(file, method),
+            )),
+            TraitItem::Type(assoc_type) => {
+                let assoc = assoc_type.ident.to_string();
+                let is_generic = !assoc_type.generics.params.is_empty();
+                if is_generic {
+                    issues.push(SkipIssue::coded(
+                        file,
+                        "generic",
+                        "generic_associated_type",
+                        assoc_type.generics.to_token_stream().to_string(),
+                        format!(
+                            "Associated type '{}::{assoc}' is generic, which TS facades cannot express; it is left out of `typeParams`, and its own parameters are recorded in `assocTypes`.",
+                            item.ident
+                        ),
+                    ));
+                }
+                assoc_types.push(ExtractAssocType {
+                    name: assoc.clone(),
+                    bounds: assoc_type
+                        .bounds
+                        .iter()
+                        .map(|bound| normalize_ws(bound.to_token_stream().to_string()))
+                        .collect(),
+                    default: assoc_type.default.as_ref().map(|(_, ty)| type_to_string(ty)),
+                    generics: generic_params(&assoc_type.generics),
+                    generic_bounds: generic_bounds(&assoc_type.generics),
+                    span: source_span(file, assoc_type),
+                });
+                // A plain type parameter would be a wrong, non-generic binding for a GAT.
+                if !is_generic && !type_params.contains(&assoc) {
+                    type_params.push(assoc);
+                }
+            }
+            TraitItem::Const(c) => {
+                issues.push(SkipIssue {
+                    file: file.to_string(),
+                    kind: "trait".to_string(),
+                    code: None,
+                    snippet: c.to_token_stream().to_string(),
+                    reason: format!(
+                        "Associated const '{}::{}' is not representable in TS trait facades; it is recorded in `consts`.",
+                        item.ident, c.ident
+                    ),
+                });
+                let mut out = ExtractField::of_type(c.ident.to_string(), &c.ty);
+                out.export_name = export_name(&c.attrs, options);
+                out.doc_aliases = doc_aliases(&c.attrs);
+                out.attributes = attributes(&c.attrs, options);
+                out.examples = doc_examples(&c.attrs, options);
+                if let Some((_, expr)) = &c.default {
+                    out.set_value(expr);
+                }
+                out.span = Some(source_span(file, c));
+                consts.push(out);
+            }
+            other => issues.push(SkipIssue {
+                file: file.to_string(),
+                kind: "trait".to_string(),
+                code: None,
+                snippet: other.to_token_stream().to_string(),
+                reason: "Unsupported trait member kind was skipped.".to_string(),
+            }),
+        }
+    }
+
+    let super_traits = item
+        .supertraits
+        .iter()
+        .map(|bound| normalize_ws(bound.to_token_stream().to_string()))
+        .collect::<Vec<_>>();
+
+    ExtractTrait {
+        name: item.ident.to_string(),
+        export_name: export_name(&item.attrs, options),
+        doc_aliases: doc_aliases(&item.attrs),
+        attributes: attributes(&item.attrs, options),
+        examples: doc_examples(&item.attrs, options),
+        type_params,
+        lifetimes: lifetime_params(&item.generics),
+        generics: generic_params(&item.generics),
+        arity: GenericArity::of(&item.generics),
+        generic_defaults: generic_defaults(&item.generics),
+        super_traits,
+        super_trait_paths: trait_bound_paths(&item.supertraits),
+        assoc_types,
+        consts,
+        methods,
+        is_marker: item.items.is_empty(),
+        span: source_span(file, item),
+    }
+}
+
+/// Nominal name of an impl's self type, matched by name like pending methods.
+fn impl_target_name(self_ty: &Type) -> Option<String> {
+    match self_ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Like `impl_target_name`, but also accepts `&T` / `&mut T` self types, returning the
+/// reference form alongside the underlying nominal name.
+fn impl_target(self_ty: &Type) -> Option<(String, Option<&'static str>)> {
+    match self_ty {
+        Type::Reference(reference) => {
+            let form = if reference.mutability.is_some() { "&mut" } else { "&" };
+            impl_target_name(reference.elem.as_ref()).map(|name| (name, Some(form)))
+        }
+        other => impl_target_name(other).map(|name| (name, None)),
+    }
+}
+
+/// `impl Default for T` (not `impl !Default for T`).
+fn implements_default(item: &ItemImpl) -> bool {
+    item.trait_.as_ref().is_some_and(|(negative, path, _)| {
+        negative.is_none() && path.segments.last().is_some_and(|segment| segment.ident == "Default")
+    })
+}
+
+/// An impl member's type with every bare `Self` replaced by the impl's self type, so
+/// `const ORIGIN: Self` in `impl Point` reads `Point` (and `Option<Self>` in `impl<T> Wrapper<T>`
+/// reads `Option<Wrapper<T>>`). Associated paths like `Self::Output` are kept as written.
+fn replace_self(ty: &Type, self_ty: &Type) -> Type {
+    struct SelfReplacer<'a>(&'a Type);
+    impl VisitMut for SelfReplacer<'_> {
+        fn visit_type_mut(&mut self, node: &mut Type) {
+            match node {
+                Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => *node = self.0.clone(),
+                _ => visit_mut::visit_type_mut(self, node),
+            }
+        }
+    }
+    let mut ty = ty.clone();
+    SelfReplacer(self_ty).visit_type_mut(&mut ty);
+    ty
+}
+
+/// `replace_self` for an impl const initializer: every path starting with `Self` (`Self::origin()`,
+/// `Self { x: 0 }`, `Self::DIM * 2`, `as Self`) starts with the self type's path instead, its
+/// generic arguments in turbofish form (`Wrapper::<T>::new()`). Expressions of impls on
+/// references or other non-path self types are returned unchanged.
+fn replace_self_in_expr(expr: &Expr, self_ty: &Type) -> Expr {
+    struct SelfPathReplacer<'a>(&'a syn::Path);
+    impl VisitMut for SelfPathReplacer<'_> {
+        fn visit_path_mut(&mut self, node: &mut syn::Path) {
+            if node.leading_colon.is_none() && node.segments.first().is_some_and(|segment| segment.ident == "Self") {
+                let rest = node.segments.iter().skip(1).cloned().collect::<Vec<_>>();
+                node.segments = self.0.segments.clone();
+                node.segments.extend(rest);
+                node.leading_colon = self.0.leading_colon;
+            }
+            visit_mut::visit_path_mut(self, node);
+        }
+    }
+    let mut expr = expr.clone();
+    if let Type::Path(syn::TypePath { qself: None, path }) = self_ty {
+        let mut path = path.clone();
+        for segment in &mut path.segments {
+            if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                args.colon2_token = Some(Default::default());
+            }
+        }
+        SelfPathReplacer(&path).visit_expr_mut(&mut expr);
+    }
+    expr
+}
+
+fn parse_impl(
+    item: &ItemImpl,
+    options: &Options,
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> Option<PendingMethods> {
+    let Some((target, target_reference)) = impl_target(item.self_ty.as_ref()) else {
+        issues.push(SkipIssue {
+            file: file.to_string(),
+            kind: "impl".to_string(),
+            code: None,
+            snippet: item.self_ty.to_token_stream().to_string(),
+            reason: "Unsupported impl target (expected a nominal path type or a reference to one).".to_string(),
+        });
+        return None;
+    };
+
+    let mut methods = Vec::new();
+    let mut consts = Vec::new();
+    let mut types = Vec::new();
+    // Trait impl members cannot be `pub`; they are as visible as the trait, so only inherent
+    // members are filtered on their own visibility.
+    let include_inherited_visibility = item.trait_.is_some();
+    let visible = |vis: &Visibility| include_inherited_visibility || is_public(vis);
+    for impl_item in &item.items {
+        match impl_item {
+            ImplItem::Fn(m) if visible(&m.vis) => {
+                let mut method = parse_signature(
+                    &m.sig,
+                    &m.attrs,
+                    options,
+                    file,
+                    issues,
+                    "fn",
+                    source_span(file, m),
+                );
+                // `fn new() -> Grid` names the target instead of writing `Self`.
+                method.is_constructor |= m.sig.receiver().is_none()
+                    && matches!(&m.sig.output, ReturnType::Type(_, ty) if impl_target_name(ty).as_ref() == Some(&target));
+                methods.push(method);
+            }
+            ImplItem::Const(c) if visible(&c.vis) => {
+                let mut out = ExtractField::of_type(c.ident.to_string(), &replace_self(&c.ty, &item.self_ty));
+                out.export_name = export_name(&c.attrs, options);
+                out.doc_aliases = doc_aliases(&c.attrs);
+                out.attributes = attributes(&c.attrs, options);
+                out.examples = doc_examples(&c.attrs, options);
+                out.set_value(&replace_self_in_expr(&c.expr, &item.self_ty));
+                out.span = Some(source_span(file, c));
+                consts.push(out);
+            }
+            ImplItem::Type(t) if visible(&t.vis) => {
+                let name = t.ident.to_string();
+                if !t.generics.params.is_empty() {
+                    issues.push(SkipIssue::coded(
+                        file,
+                        "generic",
+                        "generic_associated_type",
+                        t.generics.to_token_stream().to_string(),
+                        format!(
+                            "Associated type '{target}::{name}' is generic, which TS facades cannot express; its own parameters are recorded in `types`."
+                        ),
+                    ));
+                }
+                let mut out = ExtractField::of_type(name, &replace_self(&t.ty, &item.self_ty));
+                out.generics = generic_params(&t.generics);
+                out.span = Some(source_span(file, t));
+                types.push(out);
+            }
+            ImplItem::Fn(_) | ImplItem::Const(_) | ImplItem::Type(_) => {}
+            other => issues.push(SkipIssue {
+                file: file.to_string(),
+                kind: "impl".to_string(),
+                code: None,
+                snippet: other.to_token_stream().to_string(),
+                reason: format!("Unsupported impl member kind in impl for '{target}' was skipped."),
+            }),
+        }
+    }
+
+    if methods.is_empty() && consts.is_empty() && types.is_empty() {
+        return None;
+    }
+    // Lifetime/const impl params are reported as issues; lifetimes still show up in the target
+    // and trait argument texts.
+    let type_params = parse_type_params(&item.generics, file, "Impl", &target, issues);
+    let target_ty = match item.self_ty.as_ref() {
+        Type::Reference(reference) => reference.elem.as_ref(),
+        other => other,
+    };
+    let target_generic_args = split_generic_path(target_ty).map(|(_, args)| args).unwrap_or_default();
+    let target_path = match target_ty {
+        Type::Path(path) => path.path.segments.iter().map(|segment| segment.ident.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    let mut pending = PendingMethods {
+        target,
+        target_reference: target_reference.map(str::to_string),
+        target_generic_args,
+        type_params,
+        lifetimes: lifetime_params(&item.generics),
+        generics: generic_params(&item.generics),
+        arity: GenericArity::of(&item.generics),
+        generic_bounds: generic_bounds(&item.generics),
+        trait_path: None,
+        trait_generic_base: None,
+        trait_generic_args: Vec::new(),
+        methods,
+        consts,
+        types,
+        docs: options.preserve_impl_groups.then(|| doc_comment(&item.attrs)).flatten(),
+        span: options.preserve_impl_groups.then(|| source_span(file, item)),
+        target_path,
+        trait_name: None,
+        source_start: item.span().byte_range().start,
+    };
+    if let Some((_, path, _)) = &item.trait_ {
+        pending.set_trait(&Type::Path(syn::TypePath {
+            qself: None,
+            path: path.clone(),
+        }));
+    }
+    Some(pending)
+}
+
+fn use_path_to_string(parts: &[String]) -> String {
+    parts.join("::")
+}
+
+fn parse_use_tree(
+    tree: &UseTree,
+    prefix: &[String],
+    file: &str,
+    issues: &mut Vec<SkipIssue>,
+) -> Vec<ExtractReexport> {
+    match tree {
+        UseTree::Path(path) => {
+            let mut next = prefix.to_vec();
+            next.push(path.ident.to_string());
+            parse_use_tree(path.tree.as_ref(), &next, file, issues)
+        }
+        UseTree::Name(name) => {
+            let mut source = prefix.to_vec();
+            source.push(name.ident.to_string());
+            vec![ExtractReexport {
+                name: name.ident.to_string(),
+                source: use_path_to_string(&source),
+                source_module: None,
+                source_name: None,
+                foreign: false,
+                visibility: None,
+            }]
+        }
+        UseTree::Rename(rename) => {
+            let mut source = prefix.to_vec();
+            source.push(rename.ident.to_string());
+            vec![ExtractReexport {
+                name: rename.rename.to_string(),
+                source: use_path_to_string(&source),
+                source_module: None,
+                source_name: None,
+                foreign: false,
+                visibility: None,
+            }]
+        }
+        UseTree::Group(group) => {
+            let mut out = Vec::new();
+            for item in &group.items {
+                out.extend(parse_use_tree(item, prefix, file, issues));
+            }
+            out
+        }
+        UseTree::Glob(glob) => {
+            issues.push(SkipIssue {
+                file: file.to_string(),
+                kind: "reexport".to_string(),
+                code: None,
+                snippet: glob.to_token_stream().to_string(),
+                reason: "Glob re-exports are not supported in TS facades; explicit item re-exports are required."
+                    .to_string(),
+            });
+            Vec::new()
+        }
+    }
+}
+
+fn parse_reexports(item: &syn::ItemUse, file: &str, issues: &mut Vec<SkipIssue>) -> Vec<ExtractReexport> {
+    let mut reexports = parse_use_tree(&item.tree, &[], file, issues);
+    let visibility = restricted_visibility(&item.vis);
+    for reexport in &mut reexports {
+        reexport.foreign = item.leading_colon.is_some();
+        reexport.visibility.clone_from(&visibility);
+    }
+    reexports
+}
+
+/// Whether a re-export `source` seen from a module starts at an external crate. Names the module
+/// imports are followed through their own `use` paths (`use serde::de; pub use de::Error;` is
+/// foreign).
+fn is_foreign_source(source: &str, scope: &ModuleScope, module: &ExtractModule) -> bool {
+    let mut first = source.split("::").find(|segment| !segment.is_empty()).unwrap_or_default().to_string();
+    for _ in 0..MAX_RESOLVE_DEPTH {
+        if matches!(first.as_str(), "crate" | "self" | "super")
+            || scope.declared.contains(&first)
+            || scope.child_modules.contains(&first)
+            || declares_item(module, &first)
+        {
+            return false;
+        }
+        // The import's own path is written relative to the same module; one that starts with
+        // the imported name itself (`use serde_json;`) names a crate.
+        match scope.imports.get(&first).and_then(|path| path.first()) {
+            Some(next) if *next != first => first = next.clone(),
+            _ => return true,
+        }
+    }
+    false
+}
+
+fn is_macro_rules(item: &syn::ItemMacro) -> bool {
+    item.ident.is_some() && item.mac.path.is_ident("macro_rules")
+}
+
+/// `--include-private`: how far a non-exported `macro_rules!` macro named `name`, defined in the
+/// module at `parts`, reaches. A restricted `use` of it in the same module (`pub(crate) use
+/// name;`, the 2018 path-based form, also written `self::name` or `crate::<parts>::name`) gives
+/// that visibility (`pub(crate)`, `pub(super)`, `pub(in crate::a)`); otherwise the macro is
+/// `local`, usable only textually after its definition.
+fn macro_visibility(items: &[Item], name: &str, parts: &[String]) -> String {
+    let names_macro = |path: &[String]| {
+        let local = match path.first().map(String::as_str) {
+            Some("self") => &path[1..],
+            Some("crate") if path[1..].starts_with(parts) => &path[1 + parts.len()..],
+            _ => path,
+        };
+        local == [name]
+    };
+    for item in items {
+        let Item::Use(u) = item else {
+            continue;
+        };
+        let mut imports = HashMap::new();
+        collect_use_imports(&u.tree, &[], &mut imports);
+        if !imports.get(name).is_some_and(|path| names_macro(path)) {
+            continue;
+        }
+        match &u.vis {
+            Visibility::Public(_) => return "pub".to_string(),
+            Visibility::Restricted(_) => return restricted_visibility(&u.vis).unwrap_or_default(),
+            Visibility::Inherited => {}
+        }
+    }
+    "local".to_string()
+}
+
+/// `pub(crate)`, `pub(super)`, `pub(self)`, or `pub(in path)` for a restricted visibility.
+fn restricted_visibility(vis: &Visibility) -> Option<String> {
+    let Visibility::Restricted(restricted) = vis else {
+        return None;
+    };
+    let path = normalize_ws(restricted.path.to_token_stream().to_string()).replace(" :: ", "::");
+    Some(match restricted.in_token {
+        Some(_) => format!("pub(in {path})"),
+        None => format!("pub({path})"),
+    })
+}
+
+/// Whether a `use` item is extracted as re-exports: `pub use` always, restricted ones
+/// (`pub(crate) use`) under `--include-private`.
+fn is_extracted_use(item: &syn::ItemUse, include_private: bool) -> bool {
+    is_public(&item.vis) || (include_private && matches!(item.vis, Visibility::Restricted(_)))
+}
+
+fn has_macro_export(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("macro_export"))
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
+/// Whether `#[derive(...)]` lists `name` (matched on the last path segment, so
+/// `std::default::Default` counts as `Default`).
+fn derives(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated).ok())
+        .flatten()
+        .any(|path| path.segments.last().is_some_and(|segment| segment.ident == name))
+}
+
+/// `--serde-aware`: the tagging strategy from `#[serde(tag = "...")]`, `#[serde(tag = "...",
+/// content = "...")]`, and `#[serde(untagged)]`, read across every `serde` attribute on the enum.
+/// Other serde keys are ignored, and so are forms under `cfg_attr`. Combinations serde rejects
+/// read as the strongest one: `untagged`, then `tag` with `content`, then `tag`.
+fn serde_tagging(attrs: &[syn::Attribute]) -> SerdeTagging {
+    let mut tag = None;
+    let mut content = None;
+    let mut untagged = false;
+    let metas = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).ok())
+        .flatten();
+    for meta in metas {
+        match &meta {
+            Meta::Path(path) if path.is_ident("untagged") => untagged = true,
+            Meta::NameValue(pair) => {
+                let Expr::Lit(ExprLit { lit: Lit::Str(text), .. }) = &pair.value else {
+                    continue;
+                };
+                if pair.path.is_ident("tag") {
+                    tag = Some(text.value());
+                } else if pair.path.is_ident("content") {
+                    content = Some(text.value());
+                }
+            }
+            _ => {}
+        }
+    }
+    let (mode, tag, content) = match (tag, content) {
+        _ if untagged => ("untagged", None, None),
+        (Some(tag), Some(content)) => ("adjacent", Some(tag), Some(content)),
+        (Some(tag), None) => ("internal", Some(tag), None),
+        (None, _) => ("external", None, None),
+    };
+    SerdeTagging { mode: mode.to_string(), tag, content }
+}
+
+fn has_repr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated).ok())
+        .flatten()
+        .any(|meta| meta.path().is_ident(name))
+}
+
+/// The single non-`PhantomData` field of a `#[repr(transparent)]` struct. Other zero-sized
+/// fields cannot be recognized syntactically, so a struct with more than one such field has none.
+fn transparent_inner(item: &ItemStruct) -> Option<ExtractField> {
+    if !has_repr(&item.attrs, "transparent") {
+        return None;
+    }
+    let mut candidates = item.fields.iter().enumerate().filter(|(_, field)| match &field.ty {
+        Type::Path(path) => path.path.segments.last().is_none_or(|segment| segment.ident != "PhantomData"),
+        _ => true,
+    });
+    let (index, field) = candidates.next()?;
+    if candidates.next().is_some() {
+        return None;
+    }
+    let name = field.ident.as_ref().map_or_else(|| index.to_string(), ToString::to_string);
+    let mut out = ExtractField::of_type(name, &field.ty);
+    out.docs = doc_comment(&field.attrs);
+    Some(out)
+}
+
+/// Item-level directives from the tool attribute namespace (`#[tsuba(...)]` by default).
+#[derive(Default)]
+struct ToolAttrs {
+    skip: bool,
+    rename: Option<String>,
+    opaque: bool,
+}
+
+fn tool_attrs(attrs: &[syn::Attribute], namespace: &str) -> ToolAttrs {
+    let mut out = ToolAttrs::default();
+    for attr in attrs {
+        if !attr.path().is_ident(namespace) {
+            continue;
+        }
+        let Ok(metas) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+            continue;
+        };
+        for meta in metas {
+            match &meta {
+                Meta::Path(path) if path.is_ident("skip") => out.skip = true,
+                Meta::Path(path) if path.is_ident("opaque") => out.opaque = true,
+                Meta::NameValue(pair) if pair.path.is_ident("rename") => {
+                    if let Expr::Lit(ExprLit { lit: Lit::Str(text), .. }) = &pair.value {
+                        out.rename = Some(text.value());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+/// Public items that can be excluded by `#[tsuba(skip)]`, as `(kind, name, attrs)`.
+fn skippable_item(item: &Item) -> Option<(&'static str, String, &[syn::Attribute])> {
+    match item {
+        Item::Mod(m) if is_public(&m.vis) => Some(("Module", m.ident.to_string(), &m.attrs)),
+        Item::Fn(f) if is_public(&f.vis) => Some(("Function", f.sig.ident.to_string(), &f.attrs)),
+        Item::Struct(s) if is_public(&s.vis) => Some(("Struct", s.ident.to_string(), &s.attrs)),
+        Item::Enum(e) if is_public(&e.vis) => Some(("Enum", e.ident.to_string(), &e.attrs)),
+        Item::Trait(t) if is_public(&t.vis) => Some(("Trait", t.ident.to_string(), &t.attrs)),
+        _ => None,
+    }
+}
+
+/// Public items and impl blocks whose `#[cfg(...)]` gates are evaluated, as `(kind, name,
+/// attrs)`. An impl is named by its target, after the trait for trait impls (`Display for
+/// Wrapper`).
+/// With `include_private`, non-exported `macro_rules!` macros and restricted re-exports are
+/// gated too.
+fn cfg_gated_item(item: &Item, include_private: bool) -> Option<(&'static str, String, &[syn::Attribute])> {
+    if let Some(found) = skippable_item(item) {
+        return Some(found);
+    }
+    match item {
+        Item::Const(c) if is_public(&c.vis) => Some(("Const", c.ident.to_string(), &c.attrs)),
+        Item::Use(u) if is_extracted_use(u, include_private) => {
+            Some(("Re-export", normalize_ws(u.tree.to_token_stream().to_string()), &u.attrs))
+        }
+        Item::Macro(m) if has_macro_export(&m.attrs) || (include_private && is_macro_rules(m)) => {
+            let name = m.ident.as_ref().map(ToString::to_string).unwrap_or_default();
+            Some(("Macro", name, &m.attrs))
+        }
+        Item::Impl(i) => {
+            let target = type_to_string(&i.self_ty);
+            let name = match &i.trait_ {
+                Some((_, path, _)) => format!("{} for {target}", normalize_ws(path.to_token_stream().to_string())),
+                None => target,
+            };
+            Some(("Impl", name, &i.attrs))
+        }
+        _ => None,
+    }
+}
+
+/// One `--cfg` setting: a bare name (`--cfg tokio_unstable`) or a key/value pair
+/// (`--cfg target_os=linux`).
+#[derive(Clone)]
+struct CfgOption {
+    key: String,
+    value: Option<String>,
+}
+
+impl CfgOption {
+    fn parse(text: &str) -> Result<CfgOption, String> {
+        let (key, value) = match text.split_once('=') {
+            Some((key, value)) => {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                (key.trim(), Some(value.to_string()))
+            }
+            None => (text.trim(), None),
+        };
+        if syn::parse_str::<syn::Ident>(key).is_err() {
+            return Err(format!("Invalid --cfg '{text}' (expected 'name' or 'key=value')."));
+        }
+        Ok(CfgOption {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+/// The configuration `#[cfg(...)]` predicates are evaluated against: the enabled features and
+/// the `--cfg` settings.
+struct CfgSet {
+    features: BTreeSet<String>,
+    cfgs: Vec<CfgOption>,
+}
+
+impl CfgSet {
+    /// `Some(enabled)`, or `None` when the predicate depends on a cfg that was not specified
+    /// (platform cfgs, ...). `feature = "..."` is always known; other keys are known once any
+    /// `--cfg key=value` names them, and bare names only when passed as `--cfg name`. `test` is
+    /// the exception: the extracted library is never a test build, so it is off unless passed.
+    fn eval(&self, meta: &Meta) -> Option<bool> {
+        match meta {
+            Meta::Path(path) => {
+                let name = path.get_ident()?.to_string();
+                let set = self.cfgs.iter().any(|cfg| cfg.key == name && cfg.value.is_none());
+                (set || name == "test").then_some(set)
+            }
+            Meta::NameValue(pair) => {
+                let key = pair.path.get_ident()?.to_string();
+                let Expr::Lit(ExprLit { lit: Lit::Str(value), .. }) = &pair.value else {
+                    return None;
+                };
+                let value = value.value();
+                if key == "feature" {
+                    return Some(self.features.contains(&value));
+                }
+                let mut known = false;
+                for cfg in self.cfgs.iter().filter(|cfg| cfg.key == key) {
+                    if let Some(set) = &cfg.value {
+                        if *set == value {
+                            return Some(true);
+                        }
+                        known = true;
+                    }
+                }
+                known.then_some(false)
+            }
+            Meta::List(list) => {
+                let args = list
+                    .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                    .ok()?;
+                let values = args.iter().map(|arg| self.eval(arg)).collect::<Vec<_>>();
+                if list.path.is_ident("all") {
+                    if values.contains(&Some(false)) {
+                        Some(false)
+                    } else {
+                        values.iter().all(Option::is_some).then_some(true)
+                    }
+                } else if list.path.is_ident("any") {
+                    if values.contains(&Some(true)) {
+                        Some(true)
+                    } else {
+                        values.iter().all(Option::is_some).then_some(false)
+                    }
+                } else if list.path.is_ident("not") && values.len() == 1 {
+                    values[0].map(|value| !value)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Evaluates every `#[cfg(...)]` on an item (several gates must all hold). Returns the
+    /// result and the predicate texts, or `None` when the item is not gated.
+    fn eval_attrs(&self, attrs: &[syn::Attribute]) -> Option<(Option<bool>, String)> {
+        let mut result = Some(true);
+        let mut texts = Vec::new();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("cfg")) {
+            let value = attr.parse_args::<Meta>().ok().and_then(|meta| self.eval(&meta));
+            texts.push(format!("#[{}]", normalize_ws(attr.meta.to_token_stream().to_string())));
+            result = match (result, value) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+        }
+        (!texts.is_empty()).then(|| (result, texts.join(", ")))
+    }
+}
+
+/// Enabled features: the manifest's `default` feature (unless `--no-default-features`) plus
+/// every `--feature`, expanded through the `[features]` table like Cargo does. `dep:` and
+/// `crate/feature` entries name dependencies and are not followed.
+fn enabled_features(options: &Options, fs: &dyn SourceFs) -> Result<BTreeSet<String>, String> {
+    let manifest = match &options.input {
+        Input::Manifest(manifest_path) => Some(Manifest::read(fs, manifest_path)?),
+        Input::File(_) => None,
+    };
+    let Some(manifest) = manifest else {
+        return Ok(options.features.iter().cloned().collect());
+    };
+    let mut pending = options.features.clone();
+    if !options.no_default_features && manifest.get("features.default").is_some() {
+        pending.push("default".to_string());
+    }
+    Ok(expand_features(&manifest, pending))
+}
+
+/// `features` plus every feature they enable through the manifest's `[features]` table.
+fn expand_features(manifest: &Manifest, mut pending: Vec<String>) -> BTreeSet<String> {
+    let mut enabled = BTreeSet::new();
+    while let Some(feature) = pending.pop() {
+        if !enabled.insert(feature.clone()) {
+            continue;
+        }
+        let Some(TomlValue::Array(implied)) = manifest.get(&format!("features.{feature}")) else {
+            continue;
+        };
+        for entry in implied {
+            if let TomlValue::String(name) = entry {
+                if !name.starts_with("dep:") && !name.contains('/') {
+                    pending.push(name.clone());
+                }
+            }
+        }
+    }
+    enabled
+}
+
+/// The `[features]` table and the features `default` enables, for `CrateMeta`.
+fn declared_features(manifest: &Manifest) -> (BTreeMap<String, Vec<String>>, Vec<String>) {
+    let features = manifest
+        .entries
+        .iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix("features.").filter(|name| !name.contains('.'))?;
+            let TomlValue::Array(entries) = value else {
+                return None;
+            };
+            let entries = entries
+                .iter()
+                .filter_map(|entry| match entry {
+                    TomlValue::String(entry) => Some(entry.clone()),
+                    _ => None,
+                })
+                .collect();
+            Some((name.to_string(), entries))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let default_features = if features.contains_key("default") {
+        let mut enabled = expand_features(manifest, vec!["default".to_string()]);
+        enabled.remove("default");
+        enabled.into_iter().collect()
+    } else {
+        Vec::new()
+    };
+    (features, default_features)
+}
+
+fn proc_macro_derive_names(attrs: &[syn::Attribute]) -> Vec<String> {
+    let mut out = Vec::new();
+    for attr in attrs {
+        if !attr.path().is_ident("proc_macro_derive") {
+            continue;
+        }
+        let text = normalize_ws(attr.meta.to_token_stream().to_string());
+        let open = text.find('(');
+        let close = text.rfind(')');
+        let Some(open) = open else {
+            continue;
+        };
+        let Some(close) = close else {
+            continue;
+        };
+        if close <= open + 1 {
+            continue;
+        }
+        let inner = &text[(open + 1)..close];
+        if let Some(first) = inner
+            .split(',')
+            .map(|part| part.trim())
+            .find(|part| !part.is_empty())
+        {
+            out.push(first.to_string());
+        }
+    }
+    out
+}
+
+fn resolve_child_module_file(fs: &dyn SourceFs, base_dir: &Path, module_name: &str) -> Result<PathBuf, String> {
+    let direct = base_dir.join(format!("{module_name}.rs"));
+    if fs.exists(&direct) {
+        return Ok(direct);
+    }
+    let nested = base_dir.join(module_name).join("mod.rs");
+    if fs.exists(&nested) {
+        return Ok(nested);
+    }
+    Err(format!(
+        "Could not resolve pub mod '{module_name}' from base directory {}.",
+        base_dir.display()
+    ))
+}
+
+/// Both `foo.rs` and `foo/mod.rs` existing for `mod foo;` is a rustc error (E0761): the two
+/// candidate files, so callers can report it instead of silently preferring one.
+fn ambiguous_child_module_files(fs: &dyn SourceFs, base_dir: &Path, module_name: &str) -> Option<[PathBuf; 2]> {
+    let direct = base_dir.join(format!("{module_name}.rs"));
+    let nested = base_dir.join(module_name).join("mod.rs");
+    (fs.exists(&direct) && fs.exists(&nested)).then_some([direct, nested])
+}
+
+/// Directory that `mod child;` declarations in `file_path` resolve against. Only the crate root
+/// and `mod.rs` files own their parent directory; any other file (including a nested module that
+/// happens to be named `lib.rs` or `main.rs`) owns the directory named after its stem.
+fn module_base_dir_for_file(file_path: &Path, is_crate_root: bool) -> PathBuf {
+    let parent = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let name = file_path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    if is_crate_root || name == "mod.rs" {
+        return parent.to_path_buf();
+    }
+    let stem = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("module");
+    parent.join(stem)
+}
+
+fn empty_module(file_label: &str, parts: &[String]) -> ExtractModule {
+    ExtractModule {
+        file: file_label.to_string(),
+        parts: parts.to_vec(),
+        consts: Vec::new(),
+        enums: Vec::new(),
+        structs: Vec::new(),
+        traits: Vec::new(),
+        functions: Vec::new(),
+        reexports: Vec::new(),
+        pending_methods: Vec::new(),
+        issues: Vec::new(),
+        flattened: Vec::new(),
+        dependency_items: Vec::new(),
+        prelude: false,
+        source_order: 0,
+    }
+}
+
+/// Markers are only honored inside comments on the first few lines, so a marker string that
+/// appears in code or deep inside a file never hides a module.
+const SKIP_MARKER_SCAN_LINES: usize = 5;
+
+fn find_skip_marker<'a>(source: &str, markers: &'a [String]) -> Option<&'a str> {
+    source
+        .lines()
+        .take(SKIP_MARKER_SCAN_LINES)
+        .map(str::trim_start)
+        .filter(|line| line.starts_with("//") || line.starts_with("/*") || line.starts_with('*'))
+        .find_map(|line| markers.iter().find(|marker| line.contains(marker.as_str())))
+        .map(String::as_str)
+}
+
+/// Walks the module tree from a root file, accumulating one `ExtractModule` per module.
+struct Collector<'a> {
+    options: &'a Options,
+    fs: &'a dyn SourceFs,
+    crate_root: &'a Path,
+    out: Vec<ExtractModule>,
+    seen_files: HashSet<PathBuf>,
+    /// Target names of hand-written `impl Default for` blocks, applied to `hasDefault` once the
+    /// whole crate is collected (the impl may live in a different module than the type).
+    default_impls: HashSet<String>,
+    /// Per-module name scopes for `--resolve-paths` and `--with-deps`, keyed by module `parts`.
+    scopes: HashMap<Vec<String>, ModuleScope>,
+    cfg: CfgSet,
+    file_stats: Vec<FileStats>,
+    /// Each module's parts and the label of the parsed file it came from, in resolution order
+    /// (inline modules share their parent's file).
+    module_files: Vec<(Vec<String>, String)>,
+    /// Modules started so far; the next module's `source_order`.
+    modules_started: usize,
+    /// `--since`: module files the previous run collected, replayed when unchanged, and the
+    /// records of this run.
+    cache: Option<ParseCache>,
+    progress: &'a mut dyn FnMut(Progress<'_>),
+}
+
+/// `--since`: what collecting one module file produced, so a later run can replay it instead of
+/// parsing the file again. `hash` is the SHA-256 of the file's source.
+#[derive(Serialize, Deserialize, Clone)]
+struct FileRecord {
+    hash: String,
+    module: ModuleRecord,
+}
+
+/// One collected module as it was before the crate-wide passes, with what it added to the
+/// collector and its child modules in walk order.
+#[derive(Serialize, Deserialize, Clone)]
+struct ModuleRecord {
+    module: ExtractModule,
+    skipped: SkippedFields,
+    scope: Option<ModuleScope>,
+    default_impls: Vec<String>,
+    children: Vec<ChildRecord>,
+}
+
+/// A child of a recorded module: an inline `mod name { .. }`, recorded with its parent, or a
+/// `mod name;`, whose file is resolved and collected again on replay since it may have changed.
+#[derive(Serialize, Deserialize, Clone)]
+enum ChildRecord {
+    Inline(String, Box<ModuleRecord>),
+    File(String),
+}
+
+/// The `#[serde(skip)]` fields of a module's structs and impl blocks, in item order, which the
+/// recorded `ExtractModule` does not carry.
+#[derive(Serialize, Deserialize, Clone)]
+struct SkippedFields {
+    structs: Vec<(Vec<String>, Vec<String>)>,
+    impls: Vec<(Vec<String>, Option<String>, usize)>,
+}
+
+impl SkippedFields {
+    fn of(module: &ExtractModule) -> SkippedFields {
+        SkippedFields {
+            structs: module
+                .structs
+                .iter()
+                .map(|s| (s.private_field_names.clone(), s.hidden_field_types.clone()))
+                .collect(),
+            impls: module
+                .pending_methods
+                .iter()
+                .map(|pending| (pending.target_path.clone(), pending.trait_name.clone(), pending.source_start))
+                .collect(),
+        }
+    }
+
+    fn restore(self, module: &mut ExtractModule) {
+        for (s, (private_field_names, hidden_field_types)) in module.structs.iter_mut().zip(self.structs) {
+            s.private_field_names = private_field_names;
+            s.hidden_field_types = hidden_field_types;
+        }
+        for (pending, (target_path, trait_name, source_start)) in module.pending_methods.iter_mut().zip(self.impls) {
+            pending.target_path = target_path;
+            pending.trait_name = trait_name;
+            pending.source_start = source_start;
+        }
+    }
+}
+
+/// `--since`: the module files recorded by the previous run (`previous`, keyed by label) and by
+/// this one (`current`), which the `<out>.since` stamp keeps for the next run.
+#[derive(Default)]
+struct ParseCache {
+    previous: HashMap<String, FileRecord>,
+    current: BTreeMap<String, FileRecord>,
+    /// Files replayed from `previous` instead of parsed.
+    reused: usize,
+}
+
+fn module_path_label(parts: &[String]) -> String {
+    if parts.is_empty() {
+        "crate".to_string()
+    } else {
+        parts.join("::")
+    }
+}
+
+impl<'a> Collector<'a> {
+    fn new(
+        options: &'a Options,
+        fs: &'a dyn SourceFs,
+        crate_root: &'a Path,
+        progress: &'a mut dyn FnMut(Progress<'_>),
+    ) -> Result<Collector<'a>, String> {
+        Ok(Collector {
+            options,
+            fs,
+            crate_root,
+            out: Vec::new(),
+            seen_files: HashSet::new(),
+            default_impls: HashSet::new(),
+            scopes: HashMap::new(),
+            cfg: CfgSet {
+                features: enabled_features(options, fs)?,
+                cfgs: options.cfgs.clone(),
+            },
+            file_stats: Vec::new(),
+            module_files: Vec::new(),
+            modules_started: 0,
+            cache: None,
+            progress,
+        })
+    }
+
+    /// Adds a finished module to the output.
+    fn push_module(&mut self, module: ExtractModule) {
+        (self.progress)(Progress::ModuleCollected {
+            module: &module.parts,
+            file: &module.file,
+        });
+        self.out.push(module);
+    }
+
+    /// An empty module numbered in walk order. Modules are pushed to `out` once their children
+    /// are done, so the number is taken here, before any child is visited.
+    fn start_module(&mut self, file_label: &str, parts: &[String]) -> ExtractModule {
+        let mut module = empty_module(file_label, parts);
+        module.source_order = self.modules_started;
+        self.modules_started += 1;
+        module
+    }
+
+    /// Collects the module tree below `root_file`, recording `read`/`parse`/`collect` timings.
+    /// With `--expand`, the macro-expanded crate is collected instead when `cargo expand`
+    /// succeeds, timed as `expand`.
+    fn collect_crate(&mut self, root_file: &Path, stats: &mut Stats) -> Result<(), String> {
+        let expanded = match (&self.options.input, self.options.expand) {
+            (Input::Manifest(manifest_path), true) => {
+                let expanded = stats.time("expand", || cargo_expand(self.options, manifest_path));
+                expanded
+                    .map_err(|reason| {
+                        eprintln!(
+                            "[tsubabindgen-extractor] warning: --expand failed ({reason}); extracting the unexpanded sources instead."
+                        );
+                    })
+                    .ok()
+            }
+            _ => None,
+        };
+        let collect_start = Instant::now();
+        match expanded {
+            Some(source) => self.collect_expanded(root_file, &source)?,
+            None => self.collect_module_file(root_file, &[])?,
+        }
+        let collected = collect_start.elapsed();
+        let read = self.file_stats.iter().map(|file| file.read).sum();
+        let parse = self.file_stats.iter().map(|file| file.parse).sum();
+        stats.phase("read", read);
+        stats.phase("parse", parse);
+        stats.phase("collect", collected.saturating_sub(read + parse));
+        stats.files = std::mem::take(&mut self.file_stats);
+        Ok(())
+    }
+
+    /// `--verbose` diagnostics. Always stderr: stdout carries only the JSON payload.
+    fn log(&self, message: String) {
+        if self.options.verbose {
+            eprintln!("[tsubabindgen-extractor] {message}");
+        }
+    }
+
+    fn collect_module_items(
+        &mut self,
+        file_label: &str,
+        parts: &[String],
+        base_dir: &Path,
+        items: &[Item],
+    ) -> Result<Option<ModuleRecord>, String> {
+        let mut module = self.start_module(file_label, parts);
+        let scope = module_scope(items);
+        let recording = self.cache.is_some();
+        let mut children = Vec::new();
+        let mut default_impls = Vec::new();
+
+        for item in items {
+            // `--list-files` only follows the module tree.
+            if self.options.output_mode == OutputMode::ListFiles && !matches!(item, Item::Mod(_)) {
+                continue;
+            }
+            if let Some((owner_kind, name, attrs)) = cfg_gated_item(item, self.options.include_private) {
+                match self.cfg.eval_attrs(attrs) {
+                    Some((Some(false), predicate)) => {
+                        module.issues.push(SkipIssue::coded(
+                            file_label,
+                            "skip",
+                            "disabled_by_cfg",
+                            name.clone(),
+                            format!("{owner_kind} '{name}' is disabled by {predicate} for the selected features and cfgs and was not extracted."),
+                        ));
+                        self.log(format!("skipped {owner_kind} '{name}' in {file_label} ({predicate} is false)"));
+                        continue;
+                    }
+                    Some((None, predicate)) => {
+                        module.issues.push(SkipIssue::coded(
+                            file_label,
+                            "cfg",
+                            "unevaluated_cfg",
+                            name.clone(),
+                            format!("{owner_kind} '{name}' is gated by {predicate}, which depends on cfgs that were not specified; it was extracted as enabled."),
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            if let Some((owner_kind, name, attrs)) = skippable_item(item) {
+                let namespace = &self.options.attr_namespace;
+                if tool_attrs(attrs, namespace).skip {
+                    module.issues.push(SkipIssue::coded(
+                        file_label,
+                        "skip",
+                        "skipped_by_attribute",
+                        name.clone(),
+                        format!("{owner_kind} '{name}' is marked #[{namespace}(skip)] and was not extracted."),
+                    ));
+                    self.log(format!("skipped {owner_kind} '{name}' in {file_label} (#[{namespace}(skip)])"));
+                    continue;
+                }
+            }
+            match item {
+                Item::Mod(ItemMod { vis, ident, content, .. }) if is_public(vis) => {
+                    let mut child_parts = parts.to_vec();
+                    child_parts.push(ident.to_string());
+                    if let Some((_, inline_items)) = content {
+                        let inline_base = base_dir.join(ident.to_string());
+                        self.log(format!(
+                            "inline mod {} in {file_label}; child files resolve under {}",
+                            module_path_label(&child_parts),
+                            inline_base.display()
+                        ));
+                        self.module_files.push((child_parts.clone(), file_label.to_string()));
+                        if let Some(record) =
+                            self.collect_module_items(file_label, &child_parts, &inline_base, inline_items)?
+                        {
+                            children.push(ChildRecord::Inline(ident.to_string(), Box::new(record)));
+                        }
+                        continue;
+                    }
+                    self.collect_child_file(file_label, &child_parts, base_dir)?;
+                    if recording {
+                        children.push(ChildRecord::File(ident.to_string()));
+                    }
+                }
+                Item::Const(c) if is_public(&c.vis) => {
+                    module.consts.push(parse_const(c, self.options, file_label));
+                }
+                Item::Fn(f) if is_public(&f.vis) => {
+                    let derive_names = proc_macro_derive_names(&f.attrs);
+                    for derive_name in &derive_names {
+                        module.functions.push(ExtractFunction {
+                            kind: "derive".to_string(),
+                            name: derive_name.to_string(),
+                            return_type: "()".to_string(),
+                            return_type_repr: Some(TypeRepr::unit()),
+                            span: source_span(file_label, f),
+                            ..Default::default()
+                        });
+                    }
+                    if has_attr(&f.attrs, "proc_macro_attribute") {
+                        module.functions.push(parse_signature(
+                            &f.sig,
+                            &f.attrs,
+                            self.options,
+                            file_label,
+                            &mut module.issues,
+                            "attr_macro",
+                            source_span(file_label, f),
+                        ));
+                    } else if has_attr(&f.attrs, "proc_macro") {
+                        module.functions.push(parse_signature(
+                            &f.sig,
+                            &f.attrs,
+                            self.options,
+                            file_label,
+                            &mut module.issues,
+                            "macro",
+                            source_span(file_label, f),
+                        ));
+                    } else if derive_names.is_empty() {
+                        module.functions.push(parse_signature(
+                            &f.sig,
+                            &f.attrs,
+                            self.options,
+                            file_label,
+                            &mut module.issues,
+                            "fn",
+                            source_span(file_label, f),
+                        ));
+                    }
+                }
+                Item::Struct(s) if is_public(&s.vis) => {
+                    module
+                        .structs
+                        .push(parse_struct(s, self.options, file_label, &mut module.issues));
+                }
+                Item::Enum(e) if is_public(&e.vis) => {
+                    module.enums.push(parse_enum(e, self.options, file_label, &mut module.issues));
+                }
+                Item::Trait(t) if is_public(&t.vis) => {
+                    module.traits.push(parse_trait(t, self.options, file_label, &mut module.issues));
+                }
+                Item::Use(u) if is_extracted_use(u, self.options.include_private) => {
+                    module
+                        .reexports
+                        .extend(parse_reexports(u, file_label, &mut module.issues));
+                }
+                Item::Impl(i) => {
+                    if implements_default(i) {
+                        if let Some(target) = impl_target_name(i.self_ty.as_ref()) {
+                            default_impls.push(target.clone());
+                            self.default_impls.insert(target);
+                        }
+                    }
+                    if let Some(pending) = parse_impl(i, self.options, file_label, &mut module.issues) {
+                        module.pending_methods.push(pending);
+                    }
+                }
+                Item::Macro(m) if has_macro_export(&m.attrs) => {
+                    if let Some(name) = &m.ident {
+                        module.functions.push(macro_stub(name.to_string(), source_span(file_label, m)));
+                    } else {
+                        module.issues.push(SkipIssue {
+                            file: file_label.to_string(),
+                            kind: "macro".to_string(),
+                            code: None,
+                            snippet: m.to_token_stream().to_string(),
+                            reason: "Encountered #[macro_export] macro_rules! without a stable name.".to_string(),
+                        });
+                    }
+                }
+                Item::Macro(m) if self.options.include_private && is_macro_rules(m) => {
+                    let name = m.ident.as_ref().map(ToString::to_string).unwrap_or_default();
+                    let mut stub = macro_stub(name.clone(), source_span(file_label, m));
+                    stub.visibility = Some(macro_visibility(items, &name, parts));
+                    module.functions.push(stub);
+                }
+                _ => {}
+            }
+        }
+
+        module.pending_methods.sort_by(pending_order);
+        if !module.reexports.is_empty() {
+            let foreign = module
+                .reexports
+                .iter()
+                .map(|reexport| is_foreign_source(&reexport.source, &scope, &module))
+                .collect::<Vec<_>>();
+            for (reexport, foreign) in module.reexports.iter_mut().zip(foreign) {
+                reexport.foreign |= foreign;
+            }
+            if !self.options.preserve_source_order {
+                module
+                    .reexports
+                    .sort_by(|a, b| a.name.cmp(&b.name).then(a.source.cmp(&b.source)));
+            }
+        }
+        let scope = (self.options.resolve_paths || self.options.with_deps).then_some(scope);
+        let record = recording.then(|| ModuleRecord {
+            module: module.clone(),
+            skipped: SkippedFields::of(&module),
+            scope: scope.clone(),
+            default_impls,
+            children,
+        });
+        if let Some(scope) = scope {
+            self.scopes.insert(parts.to_vec(), scope);
+        }
+        self.log(format!(
+            "collected module {} from {file_label}: {} consts, {} enums, {} structs, {} traits, {} functions, {} re-exports, {} impls, {} issues",
+            module_path_label(parts),
+            module.consts.len(),
+            module.enums.len(),
+            module.structs.len(),
+            module.traits.len(),
+            module.functions.len(),
+            module.reexports.len(),
+            module.pending_methods.len(),
+            module.issues.len()
+        ));
+        self.push_module(module);
+        Ok(record)
+    }
+
+    /// Resolves the file of `mod name;` (the last of `child_parts`) under `base_dir` and collects
+    /// it, or records the ambiguous or (with `--require-all-modules`) missing module.
+    fn collect_child_file(
+        &mut self,
+        file_label: &str,
+        child_parts: &[String],
+        base_dir: &Path,
+    ) -> Result<(), String> {
+        let name = child_parts.last().map(String::as_str).unwrap_or_default();
+        if let Some(candidates) = ambiguous_child_module_files(self.fs, base_dir, name) {
+            let labels = candidates
+                .iter()
+                .map(|path| {
+                    let canonical = self.fs.canonicalize(path).unwrap_or_else(|_| path.clone());
+                    relative_module_label(self.crate_root, &canonical)
+                })
+                .collect::<Vec<_>>();
+            let module_path = module_path_label(child_parts);
+            let mut child = self.start_module(file_label, child_parts);
+            child.issues.push(SkipIssue::coded(
+                file_label,
+                "module",
+                "ambiguous_module_path",
+                labels.join(", "),
+                format!(
+                    "Module '{module_path}' resolves to both {} and {}, which rustc rejects; declarations were not extracted.",
+                    labels[0], labels[1]
+                ),
+            ));
+            self.log(format!("ambiguous mod {module_path}: {}", labels.join(" and ")));
+            self.push_module(child);
+            return Ok(());
+        }
+        let child_file = match resolve_child_module_file(self.fs, base_dir, name) {
+            Ok(child_file) => child_file,
+            Err(_) if self.options.require_all_modules => {
+                let module_path = module_path_label(child_parts);
+                let expected = [format!("{name}.rs"), format!("{name}/mod.rs")]
+                    .map(|file| relative_module_label(self.crate_root, &base_dir.join(file)));
+                let mut child = self.start_module(file_label, child_parts);
+                child.issues.push(SkipIssue::coded(
+                    file_label,
+                    "module",
+                    "unresolved_module",
+                    format!("mod {name}"),
+                    format!(
+                        "Module '{module_path}' has no file ({} or {}); declarations were not extracted.",
+                        expected[0], expected[1]
+                    ),
+                ));
+                self.log(format!("unresolved mod {module_path}"));
+                self.push_module(child);
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+        self.log(format!(
+            "resolved mod {} -> {}",
+            module_path_label(child_parts),
+            child_file.display()
+        ));
+        self.collect_module_file(&child_file, child_parts)
+    }
+
+    /// `--since`: the previous run's record of `file_label`, if neither the file's source
+    /// (`hash`) nor the module it is collected as changed since. The record is kept for the
+    /// next run.
+    fn cached_record(&mut self, file_label: &str, parts: &[String], hash: &str) -> Option<ModuleRecord> {
+        let cache = self.cache.as_mut()?;
+        let record = cache
+            .previous
+            .remove(file_label)
+            .filter(|record| record.hash == hash && record.module.module.parts == parts)?;
+        cache.reused += 1;
+        let module = record.module.clone();
+        cache.current.insert(file_label.to_string(), record);
+        Some(module)
+    }
+
+    /// `--since`: collects a module from its record instead of its items. Child files are
+    /// resolved and collected as usual, since they are recorded on their own.
+    fn replay_module(
+        &mut self,
+        file_label: &str,
+        parts: &[String],
+        base_dir: &Path,
+        record: ModuleRecord,
+    ) -> Result<(), String> {
+        let ModuleRecord {
+            mut module,
+            skipped,
+            scope,
+            default_impls,
+            children,
+        } = record;
+        module.source_order = self.start_module(file_label, parts).source_order;
+        skipped.restore(&mut module);
+        for child in children {
+            let (name, inline) = match child {
+                ChildRecord::Inline(name, record) => (name, Some(record)),
+                ChildRecord::File(name) => (name, None),
+            };
+            let mut child_parts = parts.to_vec();
+            child_parts.push(name.clone());
+            match inline {
+                Some(record) => {
+                    self.module_files.push((child_parts.clone(), file_label.to_string()));
+                    self.replay_module(file_label, &child_parts, &base_dir.join(&name), *record)?;
+                }
+                None => self.collect_child_file(file_label, &child_parts, base_dir)?,
+            }
+        }
+        if let Some(scope) = scope {
+            self.scopes.insert(parts.to_vec(), scope);
+        }
+        self.default_impls.extend(default_impls);
+        self.log(format!("replayed module {} from the cached {file_label}", module_path_label(parts)));
+        self.push_module(module);
+        Ok(())
+    }
+
+    fn collect_module_file(&mut self, file_path: &Path, parts: &[String]) -> Result<(), String> {
+        let canonical = self.fs.canonicalize(file_path).map_err(|e| {
+            format!(
+                "Failed to canonicalize module path {}: {e}",
+                file_path.display()
+            )
+        })?;
+        if !self.seen_files.insert(canonical.clone()) {
+            self.log(format!("already visited {}; not collecting it again", canonical.display()));
+            return Ok(());
+        }
+
+        let file_label = relative_module_label(self.crate_root, &canonical);
+        // Checked before reading so a pathological file is never loaded or handed to syn.
+        let size = self
+            .fs
+            .size(&canonical)
+            .map_err(|e| format!("Failed to read module file {}: {e}", canonical.display()))?;
+        if self.skip_too_large(&file_label, parts, size) {
+            return Ok(());
+        }
+        let read_start = Instant::now();
+        let source = self
+            .fs
+            .read(&canonical)
+            .map_err(|e| format!("Failed to read module file {}: {e}", canonical.display()))?;
+        self.file_stats.push(FileStats {
+            file: file_label.clone(),
+            bytes: size,
+            read: read_start.elapsed(),
+            parse: Duration::ZERO,
+        });
+        if let Some(marker) = find_skip_marker(&source, &self.options.skip_markers) {
+            let mut module = self.start_module(&file_label, parts);
+            module.issues.push(SkipIssue::coded(
+                &file_label,
+                "skip",
+                "skipped_by_marker",
+                file_label.clone(),
+                format!("Module {file_label} contains the skip marker '{marker}'; declarations were not extracted."),
+            ));
+            self.log(format!("skipped {file_label} (skip marker '{marker}')"));
+            self.push_module(module);
+            return Ok(());
+        }
+        let base_dir = module_base_dir_for_file(&canonical, parts.is_empty());
+        let hash = self.cache.as_ref().map(|_| sha256_hex(source.as_bytes()));
+        if let Some(record) = hash.as_deref().and_then(|hash| self.cached_record(&file_label, parts, hash)) {
+            self.module_files.push((parts.to_vec(), file_label.clone()));
+            return self.replay_module(&file_label, parts, &base_dir, record);
+        }
+        self.collect_source(&source, &file_label, parts, &base_dir, hash)
+    }
+
+    /// Records an empty module with a `file_too_large` issue when `size` is over
+    /// `--max-file-bytes`.
+    fn skip_too_large(&mut self, file_label: &str, parts: &[String], size: u64) -> bool {
+        let limit = self.options.max_file_bytes;
+        if size <= limit {
+            return false;
+        }
+        let mut module = self.start_module(file_label, parts);
+        module.issues.push(SkipIssue::coded(
+            file_label,
+            "parse",
+            "file_too_large",
+            file_label.to_string(),
+            format!("Module {file_label} is {size} bytes, over the --max-file-bytes limit of {limit}; declarations were not extracted."),
+        ));
+        self.log(format!("skipped {file_label} ({size} bytes > --max-file-bytes {limit})"));
+        self.push_module(module);
+        true
+    }
+
+    /// `--expand`: collects the whole crate from `cargo expand` output. Every module is inline
+    /// there, so the output is labeled as the crate root file and spans point into it.
+    fn collect_expanded(&mut self, root_file: &Path, source: &str) -> Result<(), String> {
+        let canonical = self
+            .fs
+            .canonicalize(root_file)
+            .map_err(|e| format!("Failed to canonicalize module path {}: {e}", root_file.display()))?;
+        self.seen_files.insert(canonical.clone());
+        let file_label = relative_module_label(self.crate_root, &canonical);
+        if self.skip_too_large(&file_label, &[], source.len() as u64) {
+            return Ok(());
+        }
+        self.file_stats.push(FileStats {
+            file: file_label.clone(),
+            bytes: source.len() as u64,
+            read: Duration::ZERO,
+            parse: Duration::ZERO,
+        });
+        let base_dir = module_base_dir_for_file(&canonical, true);
+        self.collect_source(source, &file_label, &[], &base_dir, None)
+    }
+
+    /// Parses one module file's source and collects its items, or records a `parse` issue. With
+    /// `--since`, the collected file is recorded under its source `hash`.
+    fn collect_source(
+        &mut self,
+        source: &str,
+        file_label: &str,
+        parts: &[String],
+        base_dir: &Path,
+        hash: Option<String>,
+    ) -> Result<(), String> {
+        self.log(format!("parsing {file_label} as module {}", module_path_label(parts)));
+        (self.progress)(Progress::ParsingFile {
+            file: file_label,
+            module: parts,
+        });
+        self.module_files.push((parts.to_vec(), file_label.to_string()));
+        let parse_start = Instant::now();
+        let parsed = syn::parse_file(source);
+        if let Some(stats) = self.file_stats.last_mut() {
+            stats.parse = parse_start.elapsed();
+        }
+        let file = match parsed {
+            Ok(file) => file,
+            Err(e) => {
+                self.log(format!("failed to parse {file_label}: {e}"));
+                let mut module = self.start_module(file_label, parts);
+                module.issues.push(SkipIssue {
+                    file: file_label.to_string(),
+                    kind: "parse".to_string(),
+                    code: None,
+                    snippet: file_label.to_string(),
+                    reason: format!(
+                        "Failed to parse Rust module {}; declarations were skipped: {e}",
+                        file_label
+                    ),
+                });
+                self.push_module(module);
+                return Ok(());
+            }
+        };
+        let record = self.collect_module_items(file_label, parts, base_dir, &file.items)?;
+        if let (Some(cache), Some(hash), Some(module)) = (&mut self.cache, hash, record) {
+            cache.current.insert(file_label.to_string(), FileRecord { hash, module });
+        }
+        Ok(())
+    }
+}
+
+/// `--expand`: runs `cargo expand` on the library target with the selected features and returns
+/// the expanded source, or a one-line reason it is unavailable.
+fn cargo_expand(options: &Options, manifest_path: &Path) -> Result<String, String> {
+    let mut command = Command::new("cargo");
+    command
+        .args(["expand", "--lib", "--color", "never", "--manifest-path"])
+        .arg(manifest_path)
+        .stdin(Stdio::null());
+    if options.no_default_features {
+        command.arg("--no-default-features");
+    }
+    if !options.features.is_empty() {
+        command.arg("--features").arg(options.features.join(","));
+    }
+    let output = command.output().map_err(|e| format!("could not run cargo: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("no error output");
+        return Err(format!("cargo expand {}: {detail}", output.status));
+    }
+    String::from_utf8(output.stdout).map_err(|_| "cargo expand printed non-UTF-8 output".to_string())
+}
+
+/// Names visible inside one module, used by `--resolve-paths`: types declared there (public or
+/// not), child module names, `use` imports as alias -> written path, and type aliases.
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct ModuleScope {
+    declared: HashSet<String>,
+    child_modules: HashSet<String>,
+    imports: HashMap<String, Vec<String>>,
+    /// Cached by `--since` as token text, which is parsed again on the way back.
+    #[serde(serialize_with = "serialize_aliases", deserialize_with = "deserialize_aliases")]
+    aliases: HashMap<String, syn::ItemType>,
+}
+
+fn serialize_aliases<S: serde::Serializer>(
+    aliases: &HashMap<String, syn::ItemType>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(aliases.iter().map(|(name, alias)| (name, alias.to_token_stream().to_string())))
+}
+
+fn deserialize_aliases<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, syn::ItemType>, D::Error> {
+    HashMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, text)| Ok((name, syn::parse_str(&text).map_err(serde::de::Error::custom)?)))
+        .collect()
+}
+
+fn module_scope(items: &[Item]) -> ModuleScope {
+    let mut scope = ModuleScope::default();
+    for item in items {
+        match item {
+            Item::Struct(i) => {
+                scope.declared.insert(i.ident.to_string());
+            }
+            Item::Enum(i) => {
+                scope.declared.insert(i.ident.to_string());
+            }
+            Item::Trait(i) => {
+                scope.declared.insert(i.ident.to_string());
+            }
+            Item::Type(i) => {
+                scope.declared.insert(i.ident.to_string());
+                scope.aliases.insert(i.ident.to_string(), i.clone());
+            }
+            Item::Union(i) => {
+                scope.declared.insert(i.ident.to_string());
+            }
+            Item::Mod(i) => {
+                scope.child_modules.insert(i.ident.to_string());
+            }
+            Item::Use(u) => collect_use_imports(&u.tree, &[], &mut scope.imports),
+            _ => {}
+        }
+    }
+    scope
+}
+
+fn collect_use_imports(tree: &UseTree, prefix: &[String], out: &mut HashMap<String, Vec<String>>) {
+    let imported = |ident: &syn::Ident| {
+        let mut path = prefix.to_vec();
+        if ident != "self" {
+            path.push(ident.to_string());
+        }
+        path
+    };
+    match tree {
+        UseTree::Path(path) => {
+            let mut next = prefix.to_vec();
+            next.push(path.ident.to_string());
+            collect_use_imports(path.tree.as_ref(), &next, out);
+        }
+        UseTree::Name(name) => {
+            let path = imported(&name.ident);
+            if let Some(alias) = path.last() {
+                out.insert(alias.clone(), path.clone());
+            }
+        }
+        UseTree::Rename(rename) => {
+            out.insert(rename.rename.to_string(), imported(&rename.ident));
+        }
+        UseTree::Group(group) => {
+            for item in &group.items {
+                collect_use_imports(item, prefix, out);
+            }
+        }
+        UseTree::Glob(_) => {}
+    }
+}
+
+/// Bounds import chasing so re-export cycles (`use a::X` / `use b::X`) cannot loop.
+const MAX_RESOLVE_DEPTH: usize = 16;
+
+/// Resolves a written module path (`crate::a`, `super::b`, `child`, or an imported module alias)
+/// seen from `module` to a collected module's `parts`.
+fn resolve_module_path(
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    written: &[String],
+    depth: usize,
+) -> Option<Vec<String>> {
+    if depth > MAX_RESOLVE_DEPTH {
+        return None;
+    }
+    let scope = scopes.get(module)?;
+    let first = written.first()?;
+    let (mut current, rest) = match first.as_str() {
+        "crate" => (Vec::new(), &written[1..]),
+        "self" => (module.to_vec(), &written[1..]),
+        "super" => {
+            let supers = written.iter().take_while(|segment| *segment == "super").count();
+            if supers > module.len() {
+                return None;
+            }
+            (module[..module.len() - supers].to_vec(), &written[supers..])
+        }
+        name if scope.child_modules.contains(name) => (module.to_vec(), written),
+        name => {
+            let import = scope.imports.get(name)?;
+            (resolve_module_path(scopes, module, import, depth + 1)?, &written[1..])
+        }
+    };
+    current.extend(rest.iter().cloned());
+    scopes.contains_key(&current).then_some(current)
+}
+
+/// Resolves a written type path seen from `module` to the crate-absolute path of its definition,
+/// following `use` imports and re-exports. `None` for foreign or unresolvable paths.
+fn resolve_item_path(
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    written: &[String],
+    depth: usize,
+) -> Option<Vec<String>> {
+    if depth > MAX_RESOLVE_DEPTH {
+        return None;
+    }
+    let (name, prefix) = written.split_last()?;
+    let target = if prefix.is_empty() {
+        module.to_vec()
+    } else {
+        resolve_module_path(scopes, module, prefix, depth + 1)?
+    };
+    let scope = scopes.get(&target)?;
+    if scope.declared.contains(name) {
+        let mut out = target;
+        out.push(name.clone());
+        return Some(out);
+    }
+    let import = scope.imports.get(name)?;
+    resolve_item_path(scopes, &target, import, depth + 1)
+}
+
+/// Rewrites local type and trait paths inside one type to `crate::...` form.
+struct PathRewriter<'a> {
+    scopes: &'a HashMap<Vec<String>, ModuleScope>,
+    module: &'a [String],
+    /// Generic parameters in scope; a bare `T` is never a local type.
+    generics: &'a [String],
+    changed: bool,
+}
+
+impl PathRewriter<'_> {
+    fn rewrite(&mut self, path: &mut syn::Path) {
+        if path.leading_colon.is_some() {
+            return;
+        }
+        let written = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        if written.first().is_some_and(|first| first == "Self") {
+            return;
+        }
+        if written.len() == 1 && self.generics.contains(&written[0]) {
+            return;
+        }
+        let Some(resolved) = resolve_item_path(self.scopes, self.module, &written, 0) else {
+            return;
+        };
+        if written.first().is_some_and(|first| first == "crate") && written[1..] == resolved[..] {
+            return;
+        }
+        let arguments = path.segments.last().map(|segment| segment.arguments.clone()).unwrap_or_default();
+        let mut segments = Punctuated::<syn::PathSegment, Token![::]>::new();
+        segments.push(syn::PathSegment::from(syn::Ident::new("crate", proc_macro2::Span::call_site())));
+        for name in &resolved {
+            segments.push(syn::PathSegment::from(syn::Ident::new(name, proc_macro2::Span::call_site())));
+        }
+        if let Some(last) = segments.last_mut() {
+            last.arguments = arguments;
+        }
+        path.segments = segments;
+        self.changed = true;
+    }
+}
+
+impl VisitMut for PathRewriter<'_> {
+    fn visit_type_path_mut(&mut self, node: &mut syn::TypePath) {
+        visit_mut::visit_type_path_mut(self, node);
+        if node.qself.is_none() {
+            self.rewrite(&mut node.path);
+        }
+    }
+
+    fn visit_trait_bound_mut(&mut self, node: &mut syn::TraitBound) {
+        visit_mut::visit_trait_bound_mut(self, node);
+        self.rewrite(&mut node.path);
+    }
+}
+
+/// Re-resolves one extracted type text; `None` when nothing in it changed (or it is not a type,
+/// like the `self` receiver marker).
+fn resolve_type_text(
+    text: &str,
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    generics: &[String],
+) -> Option<Type> {
+    let mut ty = syn::parse_str::<Type>(text).ok()?;
+    let mut rewriter = PathRewriter {
+        scopes,
+        module,
+        generics,
+        changed: false,
+    };
+    rewriter.visit_type_mut(&mut ty);
+    rewriter.changed.then_some(ty)
+}
+
+fn resolve_field_type(
+    field: &mut ExtractField,
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    generics: &[String],
+) {
+    if let Some(ty) = resolve_type_text(&field.type_text, scopes, module, generics) {
+        let resolved = ExtractField::of_type(String::new(), &ty);
+        field.type_text = resolved.type_text;
+        field.generic_base = resolved.generic_base;
+        field.generic_args = resolved.generic_args;
+        field.type_repr = resolved.type_repr;
+    }
+}
+
+fn resolve_function_types(
+    function: &mut ExtractFunction,
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    outer_generics: &[String],
+) {
+    let mut generics = outer_generics.to_vec();
+    generics.extend(function.type_params.iter().cloned());
+    for param in &mut function.params {
+        resolve_field_type(param, scopes, module, &generics);
+    }
+    for bound in &mut function.generic_bounds {
+        resolve_bound_paths(&bound.bounds, &mut bound.paths, scopes, module, &generics);
+    }
+    if let Some(ty) = resolve_type_text(&function.return_type, scopes, module, &generics) {
+        function.return_type = type_to_string(&ty);
+        function.return_type_repr = Some(type_repr(&ty));
+        (function.return_generic_base, function.return_generic_args) = match split_generic_path(&ty) {
+            Some((base, args)) => (Some(base), args),
+            None => (None, Vec::new()),
+        };
+        if function.future_output.is_some() {
+            function.set_future_output(&ty);
+        }
+    }
+    function.link_const_params();
+}
+
+/// Re-derives the path nodes of written bound texts (`bounds`, `superTraits`) with local paths
+/// made crate-absolute; the texts stay as written. Leaves `paths` alone when nothing resolved.
+fn resolve_bound_paths(
+    bounds: &[String],
+    paths: &mut Vec<PathRepr>,
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    generics: &[String],
+) {
+    let mut rewriter = PathRewriter {
+        scopes,
+        module,
+        generics,
+        changed: false,
+    };
+    let mut parsed = Vec::new();
+    for text in bounds {
+        let Ok(mut bound) = syn::parse_str::<syn::TypeParamBound>(text) else {
+            return;
+        };
+        rewriter.visit_type_param_bound_mut(&mut bound);
+        parsed.push(bound);
+    }
+    if rewriter.changed {
+        *paths = trait_bound_paths(&parsed);
+    }
+}
+
+/// The type alias a written type path resolves to, if it names one declared in the crate.
+fn local_alias<'a>(
+    scopes: &'a HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    written: &[String],
+) -> Option<(Vec<String>, &'a syn::ItemType)> {
+    let resolved = resolve_item_path(scopes, module, written, 0)?;
+    let (name, alias_module) = resolved.split_last()?;
+    let alias = scopes.get(alias_module)?.aliases.get(name)?;
+    Some((alias_module.to_vec(), alias))
+}
+
+/// Follows a type-alias impl target (`type Id = Wrapper<i32>; impl Id`) through any chain of
+/// aliases to the nominal type it names, returning that type's crate-absolute path and its
+/// generic arguments (resolved in the alias's module). `None` when the chain does not end at a
+/// local nominal path type: generic aliases, foreign types, tuples, and so on.
+fn resolve_alias_target(
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    module: &[String],
+    written: &[String],
+) -> Option<(Vec<String>, Vec<String>)> {
+    let (mut alias_module, mut alias) = local_alias(scopes, module, written)?;
+    for _ in 0..MAX_RESOLVE_DEPTH {
+        let Type::Path(path) = alias.ty.as_ref() else {
+            return None;
+        };
+        if !alias.generics.params.is_empty() || path.qself.is_some() {
+            return None;
+        }
+        let segments = path.path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+        if let Some(next) = local_alias(scopes, &alias_module, &segments) {
+            (alias_module, alias) = next;
+            continue;
+        }
+        let target = resolve_item_path(scopes, &alias_module, &segments, 0)?;
+        let args = split_generic_path(alias.ty.as_ref())
+            .map(|(_, args)| args)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|arg| match resolve_type_text(&arg, scopes, &alias_module, &[]) {
+                Some(ty) => type_to_string(&ty),
+                None => arg,
+            })
+            .collect();
+        return Some((target, args));
+    }
+    None
+}
+
+/// `--resolve-paths`: rewrites local type references in every extracted type text to
+/// crate-absolute `crate::...` paths, so the same type reads the same from every module.
+/// Foreign and unresolvable paths stay as written.
+fn resolve_type_paths(modules: &mut [ExtractModule], scopes: &HashMap<Vec<String>, ModuleScope>) {
+    for module in modules {
+        let parts = module.parts.clone();
+        for c in &mut module.consts {
+            resolve_field_type(c, scopes, &parts, &[]);
+        }
+        for s in &mut module.structs {
+            for field in &mut s.fields {
+                resolve_field_type(field, scopes, &parts, &s.type_params);
+            }
+            for text in &mut s.hidden_field_types {
+                if let Some(ty) = resolve_type_text(text, scopes, &parts, &s.type_params) {
+                    *text = type_to_string(&ty);
+                }
+            }
+        }
+        for e in &mut module.enums {
+            for variant in &mut e.variants {
+                for field in &mut variant.fields {
+                    resolve_field_type(field, scopes, &parts, &e.type_params);
+                }
+            }
+        }
+        for t in &mut module.traits {
+            resolve_bound_paths(&t.super_traits, &mut t.super_trait_paths, scopes, &parts, &t.type_params);
+            for method in &mut t.methods {
+                resolve_function_types(method, scopes, &parts, &t.type_params);
+            }
+        }
+        for f in &mut module.functions {
+            resolve_function_types(f, scopes, &parts, &[]);
+        }
+        let mut alias_issues = Vec::new();
+        for pending in &mut module.pending_methods {
+            let generics = pending.type_params.clone();
+            if local_alias(scopes, &parts, &pending.target_path).is_some() {
+                match resolve_alias_target(scopes, &parts, &pending.target_path) {
+                    Some((target, args)) => {
+                        pending.target = target.last().cloned().unwrap_or_default();
+                        pending.target_generic_args = args;
+                        pending.target_path = target;
+                    }
+                    _ => alias_issues.push(SkipIssue::coded(
+                        &module.file,
+                        "impl",
+                        "unresolved_alias_target",
+                        pending.target_path.join("::"),
+                        format!(
+                            "Impl target '{}' is a type alias that does not resolve to a nominal type in this crate; its members stay attached to '{}'.",
+                            pending.target_path.join("::"),
+                            pending.target
+                        ),
+                    )),
+                }
+            }
+            for arg in &mut pending.target_generic_args {
+                if let Some(ty) = resolve_type_text(arg, scopes, &parts, &generics) {
+                    *arg = type_to_string(&ty);
+                }
+            }
+            let resolved_trait = pending
+                .trait_path
+                .as_deref()
+                .and_then(|text| resolve_type_text(text, scopes, &parts, &generics));
+            if let Some(ty) = resolved_trait {
+                pending.set_trait(&ty);
+            }
+            for bound in &mut pending.generic_bounds {
+                resolve_bound_paths(&bound.bounds, &mut bound.paths, scopes, &parts, &generics);
+            }
+            for method in &mut pending.methods {
+                resolve_function_types(method, scopes, &parts, &generics);
+            }
+            for field in pending.consts.iter_mut().chain(&mut pending.types) {
+                resolve_field_type(field, scopes, &parts, &generics);
+            }
+        }
+        // Alias targets may have been renamed; restore the canonical impl block order.
+        module.pending_methods.sort_by(pending_order);
+        module.issues.extend(alias_issues);
+    }
+}
+
+/// The local definitions a type text refers to: crate-absolute paths (as `--resolve-paths` writes
+/// them) with the leading `crate` dropped, and `Self` as `self_path`.
+fn referenced_paths(text: &str, self_path: &[String]) -> Vec<Vec<String>> {
+    struct Paths<'a> {
+        self_path: &'a [String],
+        out: Vec<Vec<String>>,
+    }
+    impl VisitMut for Paths<'_> {
+        fn visit_type_path_mut(&mut self, node: &mut syn::TypePath) {
+            visit_mut::visit_type_path_mut(self, node);
+            let segments = node.path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+            match segments.split_first() {
+                Some((first, rest)) if first == "crate" && node.qself.is_none() => self.out.push(rest.to_vec()),
+                Some((first, [])) if first == "Self" => self.out.push(self.self_path.to_vec()),
+                _ => {}
+            }
+        }
+    }
+    let Ok(mut ty) = syn::parse_str::<Type>(text) else {
+        return Vec::new();
+    };
+    let mut paths = Paths {
+        self_path,
+        out: Vec::new(),
+    };
+    paths.visit_type_mut(&mut ty);
+    paths.out
+}
+
+/// `--resolve-paths`: sets `recursive` on structs and enums that can reach themselves through
+/// the types of their fields, so a generator walking definitions knows where it has to stop.
+/// Fields that are not extracted count too (`hidden_field_types`), so
+/// `pub struct Node { next: Option<Box<Node>> }` is recursive. Type aliases are followed to
+/// their definitions, so `type Link = Option<Box<Node>>` links `Node` to itself. Each search
+/// keeps a visited set, so cycles (including mutually recursive aliases, which rustc rejects)
+/// end the walk instead of looping.
+fn mark_recursive_types(modules: &mut [ExtractModule], scopes: &HashMap<Vec<String>, ModuleScope>) {
+    let mut edges = HashMap::<Vec<String>, Vec<Vec<String>>>::new();
+    for module in modules.iter() {
+        let definitions = module
+            .structs
+            .iter()
+            .map(|s| {
+                let types = s.fields.iter().map(|field| &field.type_text).chain(&s.hidden_field_types);
+                (&s.name, types.collect::<Vec<_>>())
+            })
+            .chain(module.enums.iter().map(|e| {
+                let types = e.variants.iter().flat_map(|variant| &variant.fields).map(|field| &field.type_text);
+                (&e.name, types.collect::<Vec<_>>())
+            }));
+        for (name, types) in definitions {
+            let path = [module.parts.as_slice(), std::slice::from_ref(name)].concat();
+            let targets = types
+                .iter()
+                .flat_map(|text| referenced_paths(text, &path))
+                .collect::<Vec<_>>();
+            edges.entry(path).or_default().extend(targets);
+        }
+    }
+    for (parts, scope) in scopes {
+        for (name, alias) in &scope.aliases {
+            let generics = alias
+                .generics
+                .type_params()
+                .map(|param| param.ident.to_string())
+                .collect::<Vec<_>>();
+            let text = type_to_string(&alias.ty);
+            let text = resolve_type_text(&text, scopes, parts, &generics).map_or(text, |ty| type_to_string(&ty));
+            let path = [parts.as_slice(), std::slice::from_ref(name)].concat();
+            edges.entry(path).or_default().extend(referenced_paths(&text, &[]));
+        }
+    }
+    let reaches_itself = |start: &Vec<String>| {
+        let mut visited = HashSet::new();
+        let mut stack = edges.get(start).map(|targets| targets.iter().collect::<Vec<_>>()).unwrap_or_default();
+        while let Some(path) = stack.pop() {
+            if path == start {
+                return true;
+            }
+            if visited.insert(path) {
+                stack.extend(edges.get(path).into_iter().flatten());
+            }
+        }
+        false
+    };
+    for module in modules.iter_mut() {
+        for s in &mut module.structs {
+            s.recursive = reaches_itself(&[module.parts.as_slice(), std::slice::from_ref(&s.name)].concat());
+        }
+        for e in &mut module.enums {
+            e.recursive = reaches_itself(&[module.parts.as_slice(), std::slice::from_ref(&e.name)].concat());
+        }
+    }
+}
+
+/// A value from a TOML document. Only the shapes the extractor reads are modeled; anything else
+/// (floats, dates) is kept as its TOML text.
+#[derive(Clone, Debug, PartialEq)]
+enum TomlValue {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    Array(Vec<TomlValue>),
+    /// A table; its entries are stored under their own dotted keys.
+    Table,
+    Other(String),
+}
+
+/// A TOML document (`Cargo.toml` or a `--config` file) with its entries flattened to dotted keys
+/// (`package.name`, `features.default`); tables inside arrays, including arrays of tables, are
+/// indexed (`bin.0.path`).
+#[derive(Default)]
+struct Manifest {
+    entries: BTreeMap<String, TomlValue>,
+}
+
+impl Manifest {
+    fn read(fs: &dyn SourceFs, path: &Path) -> Result<Manifest, String> {
+        let text = fs
+            .read(path)
+            .map_err(|e| format!("Failed to read manifest {}: {e}", path.display()))?;
+        Manifest::parse(&text).map_err(|e| format!("Failed to parse manifest {}: {e}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Manifest, String> {
+        let table = text.parse::<toml::Table>().map_err(|e| e.to_string().trim_end().to_string())?;
+        let mut manifest = Manifest::default();
+        manifest.flatten_table("", &table);
+        Ok(manifest)
+    }
+
+    fn flatten_table(&mut self, prefix: &str, table: &toml::Table) {
+        for (key, value) in table {
+            let key = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+            let value = self.flatten(&key, value);
+            self.entries.insert(key, value);
+        }
+    }
+
+    /// Records the entries of a table value under `key` and returns the value to store at `key`.
+    fn flatten(&mut self, key: &str, value: &toml::Value) -> TomlValue {
+        match value {
+            toml::Value::String(text) => TomlValue::String(text.clone()),
+            toml::Value::Integer(integer) => TomlValue::Integer(*integer),
+            toml::Value::Boolean(flag) => TomlValue::Bool(*flag),
+            toml::Value::Array(items) => TomlValue::Array(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| match item {
+                        toml::Value::Table(_) => {
+                            let indexed = format!("{key}.{index}");
+                            let value = self.flatten(&indexed, item);
+                            self.entries.insert(indexed, value.clone());
+                            value
+                        }
+                        _ => self.flatten(key, item),
+                    })
+                    .collect(),
+            ),
+            toml::Value::Table(table) => {
+                self.flatten_table(key, table);
+                TomlValue::Table
+            }
+            toml::Value::Float(_) | toml::Value::Datetime(_) => TomlValue::Other(value.to_string()),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&TomlValue> {
+        self.entries.get(key)
+    }
+
+    fn string(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(TomlValue::String(text)) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Rust crate identity recorded as top-level `crate` metadata, like `crate.name` /
+/// `crate.package` in `tsuba.bindings.json`.
+#[derive(Serialize, Clone)]
+struct CrateMeta {
+    name: String,
+    /// Cargo package name, when it differs from the Rust crate name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package: Option<String>,
+    /// `parts` of the module marked `prelude`, as emitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prelude: Option<Vec<String>>,
+    /// The manifest's `[features]` table as written: each feature (including `default`) and the
+    /// entries it enables, `dep:` and `crate/feature` entries included.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    features: BTreeMap<String, Vec<String>>,
+    /// Features enabled by `default`, expanded like `enabled_features` does, without `default`
+    /// itself. Independent of `--no-default-features` and `--feature`.
+    #[serde(rename = "defaultFeatures", skip_serializing_if = "Vec::is_empty")]
+    default_features: Vec<String>,
+}
+
+/// The crate's identity, plus the issue to report when a manifest without a name had the crate
+/// named after its directory.
+fn crate_meta(options: &Options, fs: &dyn SourceFs) -> Result<(CrateMeta, Option<SkipIssue>), String> {
+    match &options.input {
+        Input::Manifest(manifest_path) => {
+            let manifest = Manifest::read(fs, manifest_path)?;
+            let (features, default_features) = declared_features(&manifest);
+            let package = manifest.string("package.name").map(str::to_string);
+            let name = manifest
+                .string("lib.name")
+                .map(str::to_string)
+                .or_else(|| package.as_ref().map(|package| package.replace('-', "_")));
+            if let Some(name) = name {
+                let package = package.filter(|package| *package != name);
+                return Ok((
+                    CrateMeta {
+                        name,
+                        package,
+                        prelude: None,
+                        features,
+                        default_features,
+                    },
+                    None,
+                ));
+            }
+            let (crate_root, _) = resolve_root(&options.input, fs)?;
+            let dir = crate_root
+                .file_name()
+                .and_then(|dir| dir.to_str())
+                .ok_or_else(|| format!("Manifest {} has no [package] name or named directory.", manifest_path.display()))?;
+            let name = dir.replace('-', "_");
+            let label = manifest_path.file_name().and_then(|file| file.to_str()).unwrap_or("Cargo.toml");
+            let issue = SkipIssue::coded(
+                label,
+                "crate",
+                "crate_name_from_dir",
+                dir.to_string(),
+                format!("The manifest has no [package] or [lib] name; the crate is named '{name}' after its directory."),
+            );
+            Ok((
+                CrateMeta {
+                    name,
+                    package: None,
+                    prelude: None,
+                    features,
+                    default_features,
+                },
+                Some(issue),
+            ))
+        }
+        Input::File(file_path) => {
+            let stem = file_path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("crate");
+            let meta = CrateMeta {
+                name: stem.replace('-', "_"),
+                package: None,
+                prelude: None,
+                features: BTreeMap::new(),
+                default_features: Vec::new(),
+            };
+            Ok((meta, None))
+        }
+    }
+}
+
+/// Returns `(crate_root, root_file)`: the directory module labels are relative to, and the file
+/// extraction starts from.
+fn resolve_root(input: &Input, fs: &dyn SourceFs) -> Result<(PathBuf, PathBuf), String> {
+    match input {
+        Input::Manifest(manifest_path) => {
+            let crate_root = manifest_path.parent().ok_or_else(|| {
+                format!(
+                    "Manifest path has no parent directory: {}",
+                    manifest_path.display()
+                )
+            })?;
+            // Canonical, like module file paths, so labels stay crate-relative for manifest paths
+            // such as `../crate/Cargo.toml`.
+            let crate_root = fs
+                .canonicalize(if crate_root.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    crate_root
+                })
+                .map_err(|e| format!("Failed to read crate directory of {}: {e}", manifest_path.display()))?;
+            // `[lib] path` and `[[bin]] path` are relative to the manifest directory, like Cargo's.
+            // Without a library target, the first `[[bin]]` with a path is the root.
+            let manifest = Manifest::read(fs, manifest_path)?;
+            let default_root = crate_root.join("src").join("lib.rs");
+            let (root_file, expected) = match (manifest.string("lib.path"), manifest.string("bin.0.path")) {
+                (Some(path), _) => (crate_root.join(path), format!("[lib] path = \"{path}\"")),
+                (None, Some(path)) if !fs.exists(&default_root) => {
+                    (crate_root.join(path), format!("[[bin]] path = \"{path}\""))
+                }
+                (None, _) => (default_root, "src/lib.rs".to_string()),
+            };
+            if !fs.exists(&root_file) {
+                return Err(format!(
+                    "Missing crate root {} (expected {expected}).",
+                    root_file.display()
+                ));
+            }
+            let root_file = fs
+                .canonicalize(&root_file)
+                .map_err(|e| format!("Failed to read crate root {}: {e}", root_file.display()))?;
+            Ok((crate_root, root_file))
+        }
+        Input::File(file_path) => {
+            let root_file = fs
+                .canonicalize(file_path)
+                .map_err(|e| format!("Failed to read source file {}: {e}", file_path.display()))?;
+            let crate_root = root_file
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("."));
+            Ok((crate_root, root_file))
+        }
+    }
+}
+
+/// Collects the crate and runs the crate-wide passes. With a `cache` (`--since`), unchanged
+/// module files are replayed from it, and it is left holding this run's records.
+fn extract_modules(
+    options: &Options,
+    fs: &dyn SourceFs,
+    stats: &mut Stats,
+    progress: &mut dyn FnMut(Progress<'_>),
+    mut cache: Option<&mut ParseCache>,
+) -> Result<Vec<ExtractModule>, String> {
+    let (crate_root, root_file) = resolve_root(&options.input, fs)?;
+    let mut collector = Collector::new(options, fs, &crate_root, progress)?;
+    collector.cache = cache.as_deref_mut().map(std::mem::take);
+    collector.collect_crate(&root_file, stats)?;
+    if let (Some(cache), Some(collected)) = (cache, collector.cache.take()) {
+        *cache = collected;
+    }
+    let post_start = Instant::now();
+    let mut modules = std::mem::take(&mut collector.out);
+    for module in &mut modules {
+        for s in &mut module.structs {
+            s.has_default |= collector.default_impls.contains(&s.name);
+        }
+        for e in &mut module.enums {
+            e.has_default |= collector.default_impls.contains(&e.name);
+        }
+    }
+    check_trait_impl_methods(&mut modules);
+    check_method_name_collisions(&mut modules);
+    resolve_reexport_sources(&mut modules);
+    check_reserved_names(&mut modules, &options.attr_namespace);
+    if options.resolve_paths {
+        resolve_type_paths(&mut modules, &collector.scopes);
+        mark_recursive_types(&mut modules, &collector.scopes);
+    }
+    collect_implemented_traits(&mut modules);
+    if options.infer_properties {
+        infer_properties(&mut modules);
+    }
+    if options.with_deps {
+        inline_dependency_reexports(&mut modules, &collector.scopes, options, fs)?;
+    }
+    set_call_paths(&mut modules);
+    if options.preserve_source_order {
+        // Walk order puts each module right after its parent's `mod` declaration; impl blocks
+        // go back to file order, undoing the by-target sort.
+        modules.sort_by_key(|module| module.source_order);
+        for module in &mut modules {
+            module.pending_methods.sort_by_key(|pending| pending.source_start);
+        }
+    } else {
+        modules.sort_by(|a, b| {
+            let left = if a.parts.is_empty() {
+                String::new()
+            } else {
+                a.parts.join("::")
+            };
+            let right = if b.parts.is_empty() {
+                String::new()
+            } else {
+                b.parts.join("::")
+            };
+            left.cmp(&right).then(a.file.cmp(&b.file))
+        });
+    }
+    stats.phase("post-process", post_start.elapsed());
+    Ok(modules)
+}
+
+/// `--stats` timings, printed to stderr once the output is written. `read` and `parse` are summed
+/// over the files in `files`; `collect` is the rest of the module walk.
+#[derive(Default)]
+struct Stats {
+    phases: Vec<(&'static str, Duration)>,
+    files: Vec<FileStats>,
+}
+
+/// Read and `syn` parse time of one module file, in collection order.
+struct FileStats {
+    file: String,
+    bytes: u64,
+    read: Duration,
+    parse: Duration,
+}
+
+impl Stats {
+    fn phase(&mut self, name: &'static str, duration: Duration) {
+        self.phases.push((name, duration));
+    }
+
+    /// Times `f` as phase `name`.
+    fn time<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.phase(name, start.elapsed());
+        value
+    }
+
+    fn print(&self, total: Duration) {
+        let phases = self
+            .phases
+            .iter()
+            .map(|(name, duration)| format!("{name} {}", format_duration(*duration)))
+            .collect::<Vec<_>>();
+        eprintln!(
+            "[tsubabindgen-extractor] stats: {}, total {}",
+            phases.join(", "),
+            format_duration(total)
+        );
+        for file in &self.files {
+            eprintln!(
+                "[tsubabindgen-extractor] stats: {} ({} bytes): read {}, parse {}",
+                file.file,
+                file.bytes,
+                format_duration(file.read),
+                format_duration(file.parse)
+            );
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Splits a `use` path into the crate-absolute module it names and the final item segment.
+/// `crate::` starts at the root, each leading `super::` steps out of `from`, and `self::` or a
+/// bare path starts at `from`. Returns `None` for paths that climb above the crate root.
+fn split_use_source(from: &[String], source: &str) -> Option<(Vec<String>, String)> {
+    let mut segments = source.split("::").filter(|s| !s.is_empty()).peekable();
+    let mut base = from.to_vec();
+    match segments.peek().copied() {
+        Some("crate") => {
+            base.clear();
+            segments.next();
+        }
+        Some("self") => {
+            segments.next();
+        }
+        _ => {
+            while segments.next_if_eq(&"super").is_some() {
+                base.pop()?;
+            }
+        }
+    }
+    let mut rest: Vec<String> = segments.map(str::to_string).collect();
+    let symbol = rest.pop()?;
+    base.extend(rest);
+    Some((base, symbol))
+}
+
+fn declares_item(module: &ExtractModule, name: &str) -> bool {
+    module.consts.iter().any(|c| c.name == name)
+        || module.enums.iter().any(|e| e.name == name)
+        || module.structs.iter().any(|s| s.name == name)
+        || module.traits.iter().any(|t| t.name == name)
+        || module.functions.iter().any(|f| f.name == name)
+}
+
+/// Points each re-export at the module that defines its item. A source that names another
+/// re-export is followed to that re-export's own source; sources outside the crate, or naming
+/// nothing the crate declares, are left unresolved for the generator to report.
+fn resolve_reexport_sources(modules: &mut [ExtractModule]) {
+    let by_parts: HashMap<Vec<String>, usize> = modules
+        .iter()
+        .enumerate()
+        .map(|(index, module)| (module.parts.clone(), index))
+        .collect();
+    let mut resolved = Vec::new();
+    for (index, module) in modules.iter().enumerate() {
+        for (reexport_index, reexport) in module.reexports.iter().enumerate() {
+            let mut from = module.parts.clone();
+            let mut source = reexport.source.clone();
+            let mut visited = HashSet::new();
+            while let Some((parts, symbol)) = split_use_source(&from, &source) {
+                let Some(&target) = by_parts.get(&parts) else {
+                    break;
+                };
+                let target_module = &modules[target];
+                if declares_item(target_module, &symbol) {
+                    resolved.push((index, reexport_index, parts, symbol));
+                    break;
+                }
+                let Some(next) = target_module.reexports.iter().find(|r| r.name == symbol) else {
+                    break;
+                };
+                if !visited.insert((target, symbol)) {
+                    break;
+                }
+                from = parts;
+                source = next.source.clone();
+            }
+        }
+    }
+    for (index, reexport_index, parts, symbol) in resolved {
+        let reexport = &mut modules[index].reexports[reexport_index];
+        reexport.source_module = Some(parts);
+        reexport.source_name = Some(symbol);
+    }
+}
+
+/// `--with-deps`: the `[dependencies]` of the manifest that have a `path`, keyed by the name code
+/// uses for them (the dependency key with `-` as `_`), mapped to their manifest.
+fn path_dependencies(manifest_path: &Path, fs: &dyn SourceFs) -> Result<BTreeMap<String, PathBuf>, String> {
+    let manifest = Manifest::read(fs, manifest_path)?;
+    let dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let dependencies = manifest.entries.iter().filter_map(|(key, value)| {
+        let name = key.strip_prefix("dependencies.")?.strip_suffix(".path")?;
+        match value {
+            TomlValue::String(path) if !name.contains('.') => {
+                Some((name.replace('-', "_"), dir.join(path).join("Cargo.toml")))
+            }
+            _ => None,
+        }
+    });
+    Ok(dependencies.collect())
+}
+
+/// The segments of a re-export `source`, with a leading name the module imports replaced by its
+/// `use` path (`use core_crate::shapes; pub use shapes::Circle;` gives `core_crate::shapes::Circle`).
+fn expanded_source(source: &str, scope: Option<&ModuleScope>) -> Vec<String> {
+    let mut segments = source
+        .split("::")
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    for _ in 0..MAX_RESOLVE_DEPTH {
+        let Some(first) = segments.first() else {
+            break;
+        };
+        match scope.and_then(|scope| scope.imports.get(first)) {
+            Some(path) if path.first() != Some(first) => {
+                segments.splice(..1, path.clone());
+            }
+            _ => break,
+        }
+    }
+    segments
+}
+
+/// Where a dependency defines the item at `path` (module parts, then the item name): the index of
+/// the declaring module and the item's name there, following the dependency's own re-exports.
+fn dependency_item(modules: &[ExtractModule], path: &[String]) -> Option<(usize, String)> {
+    let (symbol, parts) = path.split_last()?;
+    let index = modules.iter().position(|module| module.parts == parts)?;
+    if declares_item(&modules[index], symbol) {
+        return Some((index, symbol.clone()));
+    }
+    let reexport = modules[index].reexports.iter().find(|reexport| &reexport.name == symbol)?;
+    let (Some(source_module), Some(source_name)) = (&reexport.source_module, &reexport.source_name) else {
+        return None;
+    };
+    let index = modules.iter().position(|module| &module.parts == source_module)?;
+    Some((index, source_name.clone()))
+}
+
+/// Clones the items of `items` named `rust_name`, renamed to `name`.
+fn copy_named<T: Clone>(items: &[T], rust_name: &str, name: &str, name_of: fn(&mut T) -> &mut String) -> Vec<T> {
+    let mut copies = Vec::new();
+    for item in items {
+        let mut copy = item.clone();
+        let copy_name = name_of(&mut copy);
+        if copy_name == rust_name {
+            *copy_name = name.to_string();
+            copies.push(copy);
+        }
+    }
+    copies
+}
+
+/// `--with-deps`: replaces each re-export from a path dependency (`pub use core_crate::Circle;`)
+/// with a copy of the item the dependency defines, under the re-exported name, plus the
+/// dependency's impl blocks on it (matched by name, like pending methods), and records it in
+/// `dependencyItems`. A dependency is extracted on first use, with the same options but its own
+/// default features; its re-exports from further dependencies are not followed. Re-exports of a
+/// dependency module, or of a name it does not define, stay `foreign`.
+fn inline_dependency_reexports(
+    modules: &mut [ExtractModule],
+    scopes: &HashMap<Vec<String>, ModuleScope>,
+    options: &Options,
+    fs: &dyn SourceFs,
+) -> Result<(), String> {
+    let Input::Manifest(manifest_path) = &options.input else {
+        return Ok(());
+    };
+    let dependencies = path_dependencies(manifest_path, fs)?;
+    let mut extracted = HashMap::<String, Vec<ExtractModule>>::new();
+    for module in modules.iter_mut() {
+        let mut kept = Vec::new();
+        for reexport in std::mem::take(&mut module.reexports) {
+            let segments = expanded_source(&reexport.source, scopes.get(&module.parts));
+            let dependency = match segments.split_first() {
+                Some((first, path)) if reexport.foreign => dependencies.get(first).map(|manifest| (first, manifest, path)),
+                _ => None,
+            };
+            let Some((crate_name, manifest, path)) = dependency else {
+                kept.push(reexport);
+                continue;
+            };
+            if !extracted.contains_key(crate_name) {
+                let dependency_options = Options {
+                    input: Input::Manifest(manifest.clone()),
+                    features: Vec::new(),
+                    no_default_features: false,
+                    with_deps: false,
+                    ..options.clone()
+                };
+                let mut ignore_progress = |_: Progress<'_>| {};
+                let dependency_modules =
+                    extract_modules(&dependency_options, fs, &mut Stats::default(), &mut ignore_progress, None)
+                        .map_err(|e| format!("Failed to extract dependency '{crate_name}': {e}"))?;
+                extracted.insert(crate_name.clone(), dependency_modules);
+            }
+            let from = &extracted[crate_name];
+            let Some((index, rust_name)) = dependency_item(from, path) else {
+                kept.push(reexport);
+                continue;
+            };
+            let source = &from[index];
+            let name = reexport.name;
+            module.consts.extend(copy_named(&source.consts, &rust_name, &name, |c| &mut c.name));
+            module.enums.extend(copy_named(&source.enums, &rust_name, &name, |e| &mut e.name));
+            module.structs.extend(copy_named(&source.structs, &rust_name, &name, |s| &mut s.name));
+            module.traits.extend(copy_named(&source.traits, &rust_name, &name, |t| &mut t.name));
+            module.functions.extend(copy_named(&source.functions, &rust_name, &name, |f| &mut f.name));
+            for from_module in from {
+                let pending = copy_named(&from_module.pending_methods, &rust_name, &name, |p| &mut p.target);
+                module.pending_methods.extend(pending);
+            }
+            module.dependency_items.push(DependencyItem {
+                name,
+                crate_name: crate_name.clone(),
+                module: source.parts.clone(),
+                rust_name,
+            });
+        }
+        module.reexports = kept;
+        module.pending_methods.sort_by(pending_order);
+    }
+    Ok(())
+}
+
+/// Flags trait-impl methods that the implemented trait does not declare. Only paths that name a
+/// crate trait are checked: a bare name, matched like pending methods against the union of
+/// same-named traits, or a `crate::`/`self::`/`super::` path to the defining module.
+fn check_trait_impl_methods(modules: &mut [ExtractModule]) {
+    let mut by_name = HashMap::<String, HashSet<String>>::new();
+    let mut by_path = HashMap::<(Vec<String>, String), HashSet<String>>::new();
+    for module in modules.iter() {
+        for t in &module.traits {
+            let names = t.methods.iter().map(|method| method.name.clone());
+            by_name.entry(t.name.clone()).or_default().extend(names.clone());
+            by_path.entry((module.parts.clone(), t.name.clone())).or_default().extend(names);
+        }
+    }
+    for module in modules.iter_mut() {
+        for pending in &module.pending_methods {
+            for method in &pending.methods {
+                let Some(implemented) = &method.implements else {
+                    continue;
+                };
+                let path = &implemented.trait_name;
+                let declared = if !path.contains("::") {
+                    by_name.get(path)
+                } else if ["crate::", "self::", "super::"].iter().any(|prefix| path.starts_with(prefix)) {
+                    split_use_source(&module.parts, path).and_then(|key| by_path.get(&key))
+                } else {
+                    None
+                };
+                let Some(declared) = declared else {
+                    continue;
+                };
+                if !declared.contains(&method.name) {
+                    module.issues.push(SkipIssue::coded(
+                        &method.span.source_file,
+                        "impl",
+                        "method_not_in_trait",
+                        format!("{}::{}", pending.target, method.name),
+                        format!(
+                            "Method '{}' in impl {} for '{}' is not declared by the trait; the impl may be mis-parsed.",
+                            method.name, implemented.trait_name, pending.target
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Sets `callPath` on receiver-less inherent methods, from the final target name (after
+/// `--resolve-paths` and `--with-deps` renames, before `--flatten`). Trait-impl functions get none:
+/// calling them needs the trait in scope or a qualified path (see `implements`).
+fn set_call_paths(modules: &mut [ExtractModule]) {
+    for pending in modules.iter_mut().flat_map(|module| &mut module.pending_methods) {
+        if pending.trait_path.is_some() {
+            continue;
+        }
+        for method in &mut pending.methods {
+            if method.params.first().is_none_or(|param| param.type_text != "self") {
+                method.call_path = Some(format!("{}::{}", pending.target, method.name));
+            }
+        }
+    }
+}
+
+/// Sets `qualifiedId` on trait-impl methods that share a name with an inherent method of the same
+/// target, and reports each with a `method_name_collision` issue. Targets are matched by name
+/// across the crate, like pending methods; impls on `&T` are not compared with `T`'s.
+fn check_method_name_collisions(modules: &mut [ExtractModule]) {
+    let mut inherent = HashMap::<String, HashSet<String>>::new();
+    for pending in modules.iter().flat_map(|module| &module.pending_methods) {
+        if pending.trait_path.is_none() && pending.target_reference.is_none() {
+            let names = inherent.entry(pending.target.clone()).or_default();
+            names.extend(pending.methods.iter().map(|method| method.name.clone()));
+        }
+    }
+    for module in modules.iter_mut() {
+        let mut issues = Vec::new();
+        for pending in &mut module.pending_methods {
+            if pending.target_reference.is_some() {
+                continue;
+            }
+            let Some(names) = inherent.get(&pending.target) else {
+                continue;
+            };
+            for method in &mut pending.methods {
+                let Some(implemented) = &method.implements else {
+                    continue;
+                };
+                if !names.contains(&method.name) {
+                    continue;
+                }
+                let qualified = format!("<{} as {}>::{}", pending.target, implemented.trait_name, method.name);
+                issues.push(SkipIssue::coded(
+                    &method.span.source_file,
+                    "impl",
+                    "method_name_collision",
+                    format!("{}::{}", pending.target, method.name),
+                    format!(
+                        "Method '{}' in impl {} for '{}' has the same name as an inherent method; it is recorded as '{qualified}'.",
+                        method.name, implemented.trait_name, pending.target
+                    ),
+                ));
+                method.qualified_id = Some(qualified);
+            }
+        }
+        module.issues.extend(issues);
+    }
+}
+
+/// Names a generated facade cannot declare as a top-level export: ECMAScript reserved words
+/// (including strict-mode and module-code ones), names TS reserves for built-in types, and the
+/// globals facades refer to. Keep sorted.
+const TS_RESERVED_NAMES: &[&str] = &[
+    "Array",
+    "Infinity",
+    "NaN",
+    "Object",
+    "Promise",
+    "Symbol",
+    "any",
+    "arguments",
+    "await",
+    "bigint",
+    "boolean",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "globalThis",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "namespace",
+    "never",
+    "new",
+    "null",
+    "number",
+    "object",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "string",
+    "super",
+    "switch",
+    "symbol",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "unknown",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// The `TS_RESERVED_NAMES` entries the generator still declares, renamed with a trailing `_`
+/// (`JS_KEYWORDS` in `src/pipeline/common.ts`; keep the two lists in sync). Keep sorted.
+const FACADE_RENAMED_NAMES: &[&str] =
+    &["class", "const", "default", "delete", "let", "namespace", "new", "return", "static", "super", "var"];
+
+/// Flags public items whose facade name (the `rename` override if any, else the Rust name
+/// without an `r#` prefix) is in `TS_RESERVED_NAMES`, so authors can rename them before
+/// generation fails or, for `FACADE_RENAMED_NAMES`, before the generator picks a `name_` export.
+/// A re-export without `as` takes the facade name of the item it resolves to, as the generator
+/// declares it under that item's `exportName`. Members are not checked: TS allows reserved words
+/// as property names.
+fn check_reserved_names(modules: &mut [ExtractModule], namespace: &str) {
+    let mut export_names = HashMap::<(&[String], &str), &str>::new();
+    for module in modules.iter() {
+        let items = (module.consts.iter().map(|c| (&c.name, &c.export_name)))
+            .chain(module.enums.iter().map(|e| (&e.name, &e.export_name)))
+            .chain(module.structs.iter().map(|st| (&st.name, &st.export_name)))
+            .chain(module.traits.iter().map(|t| (&t.name, &t.export_name)))
+            .chain(module.functions.iter().map(|f| (&f.name, &f.export_name)));
+        for (name, export_name) in items {
+            if let Some(export_name) = export_name {
+                export_names.insert((&module.parts, name), export_name);
+            }
+        }
+    }
+    let mut issues_by_module = Vec::new();
+    for module in modules.iter() {
+        let mut named = Vec::new();
+        for c in &module.consts {
+            let file = c.span.as_ref().map_or(&module.file, |span| &span.source_file);
+            named.push(("Const", &c.name, c.export_name.as_deref(), file));
+        }
+        for e in &module.enums {
+            named.push(("Enum", &e.name, e.export_name.as_deref(), &e.span.source_file));
+        }
+        for st in &module.structs {
+            named.push(("Struct", &st.name, st.export_name.as_deref(), &st.span.source_file));
+        }
+        for t in &module.traits {
+            named.push(("Trait", &t.name, t.export_name.as_deref(), &t.span.source_file));
+        }
+        for f in &module.functions {
+            let kind = if f.kind == "fn" { "Function" } else { "Macro" };
+            named.push((kind, &f.name, f.export_name.as_deref(), &f.span.source_file));
+        }
+        for r in &module.reexports {
+            let export_name = match (&r.source_module, &r.source_name) {
+                (Some(parts), Some(source_name)) if *source_name == r.name => {
+                    export_names.get(&(parts.as_slice(), source_name.as_str())).copied()
+                }
+                _ => None,
+            };
+            named.push(("Re-export", &r.name, export_name, &module.file));
+        }
+        let mut issues = Vec::new();
+        for (kind, name, export_name, file) in named {
+            let facade_name = export_name.unwrap_or(name.as_str());
+            let facade_name = facade_name.strip_prefix("r#").unwrap_or(facade_name);
+            if TS_RESERVED_NAMES.binary_search(&facade_name).is_ok() {
+                issues.push(SkipIssue::coded(
+                    file,
+                    "name",
+                    "ts_reserved_name",
+                    name.clone(),
+                    if FACADE_RENAMED_NAMES.binary_search(&facade_name).is_ok() {
+                        format!(
+                            "{kind} name '{facade_name}' is a TS reserved word; generated facades declare it as '{facade_name}_'. Rename it with #[{namespace}(rename = \"...\")] to choose the name."
+                        )
+                    } else {
+                        format!(
+                            "{kind} name '{facade_name}' is a TS reserved word or global and cannot be declared by generated facades; rename it with #[{namespace}(rename = \"...\")]."
+                        )
+                    },
+                ));
+            }
+        }
+        issues_by_module.push(issues);
+    }
+    for (module, issues) in modules.iter_mut().zip(issues_by_module) {
+        module.issues.extend(issues);
+    }
+}
+
+/// Fills `implementedTraits` on structs and enums from the trait impls of every module. Impls are
+/// matched to types by target name, as for `hasDefault`; impls on `&T` do not count for `T`. Each
+/// trait is listed once, without generic arguments, sorted.
+fn collect_implemented_traits(modules: &mut [ExtractModule]) {
+    let mut by_target = HashMap::<String, BTreeSet<String>>::new();
+    for pending in modules.iter().flat_map(|module| &module.pending_methods) {
+        if let (Some(trait_name), None) = (&pending.trait_name, &pending.target_reference) {
+            by_target.entry(pending.target.clone()).or_default().insert(trait_name.clone());
+        }
+    }
+    let traits_of = |name: &str| by_target.get(name).map(|traits| traits.iter().cloned().collect()).unwrap_or_default();
+    for module in modules {
+        for s in &mut module.structs {
+            s.implemented_traits = traits_of(&s.name);
+        }
+        for e in &mut module.enums {
+            e.implemented_traits = traits_of(&e.name);
+        }
+    }
+}
+
+/// The property a method reads, if it is a getter: a `&self` method with no other parameters,
+/// no type parameters, not `async`, and a non-`()` return type, named `get_<name>` or after one of
+/// the struct's private fields.
+fn getter_property(method: &ExtractFunction, private_fields: &[String]) -> Option<String> {
+    let is_getter = method.kind == "fn"
+        && method.params.len() == 1
+        && method.params[0].name == "&self"
+        && method.type_params.is_empty()
+        && !method.is_async
+        && method.return_type != "()";
+    if !is_getter {
+        return None;
+    }
+    match method.name.strip_prefix("get_") {
+        Some(name) if !name.is_empty() => Some(name.to_string()),
+        _ => private_fields.contains(&method.name).then(|| method.name.clone()),
+    }
+}
+
+/// `--infer-properties`: attaches `derivedProperties` to structs from the inherent-impl getters
+/// found anywhere in the crate (matched by type name, like pending methods). Properties that
+/// shadow a `pub` field are left out, and the first getter for a name wins. Opaque structs get none.
+fn infer_properties(modules: &mut [ExtractModule]) {
+    let mut getters = HashMap::<String, Vec<ExtractFunction>>::new();
+    for module in modules.iter() {
+        for pending in &module.pending_methods {
+            if pending.trait_path.is_none() && pending.target_reference.is_none() {
+                let methods = getters.entry(pending.target.clone()).or_default();
+                methods.extend(pending.methods.iter().cloned());
+            }
+        }
+    }
+    for s in modules.iter_mut().flat_map(|module| module.structs.iter_mut()).filter(|s| !s.opaque) {
+        let Some(methods) = getters.get(&s.name) else {
+            continue;
+        };
+        let mut properties = BTreeMap::<String, DerivedProperty>::new();
+        for method in methods {
+            let Some(name) = getter_property(method, &s.private_field_names) else {
+                continue;
+            };
+            if s.fields.iter().any(|field| field.name == name) {
+                continue;
+            }
+            properties.entry(name.clone()).or_insert_with(|| DerivedProperty {
+                name,
+                getter: method.name.clone(),
+                type_text: method.return_type.clone(),
+                type_repr: method.return_type_repr.clone(),
+            });
+        }
+        s.derived_properties = properties.into_values().collect();
+    }
+}
+
+const USAGE: &str = "Usage: tsubabindgen-extractor [options] <manifest-path>
+       tsubabindgen-extractor [options] --file <source.rs>";
+
+/// How the result is written to stdout. Modes are mutually exclusive.
+#[derive(Clone, Copy, PartialEq)]
+enum OutputMode {
+    /// One JSON document with the full module tree (the default).
+    Modules,
+    /// `--json-lines-per-item`: one `ItemRecord` JSON object per line.
+    ItemLines,
+    /// `--list-files`: the crate-relative label of every module file that would be parsed, one
+    /// per line.
+    ListFiles,
+    /// `--diff <old-manifest>`: one `ApiDiff` JSON document comparing the old crate's public
+    /// items with the extracted crate's.
+    Diff,
+}
+
+/// What to extract: a crate (via its `Cargo.toml`, rooted at `[lib] path` or `src/lib.rs`) or a
+/// single source file treated as a crate root, with child modules resolved next to it.
+#[derive(Clone)]
+enum Input {
+    Manifest(PathBuf),
+    File(PathBuf),
+}
+
+/// Where the extractor reads the manifest and module files from. [`RealFs`] is the disk;
+/// [`MemoryFs`] serves sources held in memory.
+pub trait SourceFs {
+    /// The whole file as UTF-8 text.
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Whether `path` names a file that [`read`](SourceFs::read) can open.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// The file's size in bytes, checked against `--max-file-bytes` before the file is read.
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|text| text.len() as u64)
+    }
+
+    /// The form of `path` used to label module files and visit each file once. The default
+    /// resolves `.` and `..` lexically, which suits file systems without symlinks.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(normalize_path(path))
+    }
+}
+
+/// A step of an extraction, reported to the callback of [`extract_with_progress`]. `file` is the
+/// crate-relative label of a module file (`src/inner.rs`) and `module` the module's `parts` as
+/// collected, before `--subtree`, `--root-module-name`, and `--flatten` relabel them.
+#[derive(Clone, Copy, Debug)]
+pub enum Progress<'a> {
+    /// A module file is about to be parsed.
+    ParsingFile { file: &'a str, module: &'a [String] },
+    /// A module is complete, with its items, or empty because it was skipped or did not parse.
+    /// Modules finish after their child modules.
+    ModuleCollected { module: &'a [String], file: &'a str },
+}
+
+/// The real file system, used by [`parse_options`], [`extract`], and the binary.
+pub struct RealFs;
+
+impl SourceFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn size(&self, path: &Path) -> io::Result<u64> {
+        fs::metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+}
+
+/// Sources held in memory, keyed by path: a `Cargo.toml` plus `src/lib.rs` and its module
+/// files under one crate directory, for tests without temp dirs or editor buffers that are not
+/// saved yet. Paths are compared after resolving `.` and `..`.
+#[derive(Default)]
+pub struct MemoryFs {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl MemoryFs {
+    pub fn new() -> MemoryFs {
+        MemoryFs::default()
+    }
+
+    /// Adds or replaces the file at `path`.
+    pub fn insert(&mut self, path: impl AsRef<Path>, source: impl Into<String>) -> &mut MemoryFs {
+        self.files.insert(normalize_path(path.as_ref()), source.into());
+        self
+    }
+}
+
+impl SourceFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<String> {
+        self.files.get(&normalize_path(path)).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "no such file in the in-memory sources")
+        })
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.contains_key(&normalize_path(path))
+    }
+}
+
+/// `path` with `.` segments dropped and `..` applied to the preceding segment, without touching
+/// the file system.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir
+                if matches!(out.components().next_back(), Some(std::path::Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+#[derive(Clone)]
+pub struct Options {
+    input: Input,
+    root_module_name: Option<String>,
+    subtree: Option<Vec<String>>,
+    skip_markers: Vec<String>,
+    attr_namespace: String,
+    verbose: bool,
+    resolve_paths: bool,
+    output_mode: OutputMode,
+    features: Vec<String>,
+    no_default_features: bool,
+    cfgs: Vec<CfgOption>,
+    max_file_bytes: u64,
+    infer_properties: bool,
+    transform: Option<String>,
+    print_hash: bool,
+    stats: bool,
+    emit_index: bool,
+    expand: bool,
+    emit_attributes: bool,
+    extract_examples: bool,
+    recognize_futures: bool,
+    preserve_source_order: bool,
+    preserve_impl_groups: bool,
+    /// `--flatten`: the separator joining module path and item name.
+    flatten: Option<String>,
+    /// `--out`: where the modules document is written instead of stdout.
+    out: Option<PathBuf>,
+    watch: bool,
+    /// `--diff`: the manifest of the old crate version to compare against.
+    diff: Option<PathBuf>,
+    /// `[package.metadata.tsuba].prelude`: the module to mark with `prelude`.
+    prelude: Option<Vec<String>>,
+    /// `--with-deps`: copy items re-exported from path dependencies into the re-exporting module.
+    with_deps: bool,
+    serde_aware: bool,
+    /// `--require-all-modules`: record unresolvable `mod` declarations as issues instead of
+    /// failing on the first, then exit non-zero after writing the output.
+    require_all_modules: bool,
+    /// `--include-private`: also extract items reachable only inside the crate: `macro_rules!`
+    /// macros that are not `#[macro_export]`ed and restricted (`pub(crate) use`) re-exports.
+    include_private: bool,
+    /// `--max-issues <n>`: keep at most `n` issues in the output (see `cap_issues`).
+    max_issues: Option<usize>,
+    /// `--since <git-ref>`: reuse the `--out` document when no module file changed since the ref,
+    /// and otherwise parse only the changed module files (see `since_check`).
+    since: Option<String>,
+    /// Hash of the flags that shape the document (see `options_fingerprint`), stamped next to
+    /// `--out` for `--since`.
+    fingerprint: String,
+}
+
+const DEFAULT_SKIP_MARKER: &str = "tsuba:skip";
+const DEFAULT_FLATTEN_SEPARATOR: &str = "_";
+const DEFAULT_ATTR_NAMESPACE: &str = "tsuba";
+/// Far above any hand-written module; generated bindings files reach a few MiB.
+const DEFAULT_MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    match args.next() {
+        Some(value) if !value.is_empty() && !value.starts_with("--") => Ok(value),
+        _ => Err(format!("Option {flag} requires a value.\n{USAGE}")),
+    }
+}
+
+/// Parses command-line arguments (without the program name).
+pub fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    parse_options_with(args, &RealFs)
+}
+
+/// Like [`parse_options`], reading `[package.metadata.tsuba]` defaults through `fs`.
+pub fn parse_options_with(args: impl Iterator<Item = String>, fs: &dyn SourceFs) -> Result<Options, String> {
+    // `--config` values come first, so flags on the command line override them.
+    let mut cli_args = args.collect::<Vec<_>>();
+    let config = match cli_args.iter().position(|arg| arg == "--config") {
+        Some(at) => {
+            let path = cli_args
+                .get(at + 1)
+                .filter(|value| !value.is_empty() && !value.starts_with("--"))
+                .ok_or_else(|| format!("Option --config requires a value.\n{USAGE}"))?;
+            let config = ConfigFile::read(fs, Path::new(path))?;
+            cli_args.drain(at..at + 2);
+            if cli_args.iter().any(|arg| arg == "--config") {
+                return Err(format!("Option --config can only be given once.\n{USAGE}"));
+            }
+            config
+        }
+        None => ConfigFile::default(),
+    };
+    let all_args = config.args.into_iter().chain(cli_args).collect::<Vec<_>>();
+    let fingerprint = options_fingerprint(&all_args);
+    let mut args = all_args.into_iter();
+    let mut manifest_path = None;
+    let mut file_path = None;
+    let mut root_module_name = None;
+    let mut subtree = None;
+    let mut skip_markers = vec![DEFAULT_SKIP_MARKER.to_string()];
+    let mut attr_namespace = None;
+    let mut verbose = false;
+    let mut resolve_paths = None;
+    let mut features = Vec::new();
+    let mut no_default_features = false;
+    let mut cfgs = Vec::new();
+    let mut max_file_bytes = None;
+    let mut max_issues = None;
+    let mut since = None;
+    let mut infer_properties = None;
+    let mut transform = None;
+    let mut print_hash = false;
+    let mut stats = false;
+    let mut emit_index = false;
+    let mut expand = false;
+    let mut emit_attributes = false;
+    let mut extract_examples = false;
+    let mut recognize_futures = false;
+    let mut preserve_source_order = false;
+    let mut preserve_impl_groups = false;
+    let mut flatten = false;
+    let mut flatten_separator = None;
+    let mut out = None;
+    let mut watch = false;
+    let mut diff = None;
+    let mut with_deps = false;
+    let mut serde_aware = false;
+    let mut require_all_modules = false;
+    let mut include_private = false;
+    let mut output_mode = OutputMode::Modules;
+    let mut set_output_mode = |mode: OutputMode, flag: &str| {
+        if output_mode != OutputMode::Modules && output_mode != mode {
+            return Err(format!("Option {flag} cannot be combined with another output mode.\n{USAGE}"));
+        }
+        output_mode = mode;
+        Ok(())
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root-module-name" => {
+                root_module_name = Some(flag_value(&mut args, "--root-module-name")?);
+            }
+            "--subtree" => {
+                let value = flag_value(&mut args, "--subtree")?;
+                subtree = Some(parse_module_path(&value)?);
+            }
+            "--file" => file_path = Some(PathBuf::from(flag_value(&mut args, "--file")?)),
+            "--skip-marker" => skip_markers.push(flag_value(&mut args, "--skip-marker")?),
+            "--attr-namespace" => attr_namespace = Some(flag_value(&mut args, "--attr-namespace")?),
+            "--verbose" => verbose = true,
+            "--resolve-paths" => resolve_paths = Some(true),
+            "--no-resolve-paths" => resolve_paths = Some(false),
+            "--infer-properties" => infer_properties = Some(true),
+            "--no-infer-properties" => infer_properties = Some(false),
+            "--transform" => transform = Some(flag_value(&mut args, "--transform")?),
+            "--print-hash" => print_hash = true,
+            "--stats" => stats = true,
+            "--emit-index" => emit_index = true,
+            "--expand" => expand = true,
+            "--emit-attributes" => emit_attributes = true,
+            "--extract-examples" => extract_examples = true,
+            "--recognize-futures" => recognize_futures = true,
+            "--preserve-source-order" => preserve_source_order = true,
+            "--preserve-impl-groups" => preserve_impl_groups = true,
+            "--flatten" => flatten = true,
+            "--flatten-separator" => flatten_separator = Some(flag_value(&mut args, "--flatten-separator")?),
+            "--out" => out = Some(PathBuf::from(flag_value(&mut args, "--out")?)),
+            "--watch" => watch = true,
+            "--with-deps" => with_deps = true,
+            "--serde-aware" => serde_aware = true,
+            "--require-all-modules" => require_all_modules = true,
+            "--include-private" => include_private = true,
+            "--feature" => features.push(flag_value(&mut args, "--feature")?),
+            "--no-default-features" => no_default_features = true,
+            "--cfg" => cfgs.push(CfgOption::parse(&flag_value(&mut args, "--cfg")?)?),
+            "--max-file-bytes" => {
+                let value = flag_value(&mut args, "--max-file-bytes")?;
+                max_file_bytes = Some(
+                    value
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|bytes| *bytes > 0)
+                        .ok_or_else(|| format!("Invalid --max-file-bytes '{value}' (expected a positive byte count)."))?,
+                );
+            }
+            "--max-issues" => {
+                let value = flag_value(&mut args, "--max-issues")?;
+                max_issues = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid --max-issues '{value}' (expected a non-negative issue count)."))?,
+                );
+            }
+            "--since" => {
+                let value = flag_value(&mut args, "--since")?;
+                if value.starts_with('-') {
+                    return Err(format!("Invalid --since '{value}' (expected a git revision)."));
+                }
+                since = Some(value);
+            }
+            "--json-lines-per-item" => set_output_mode(OutputMode::ItemLines, "--json-lines-per-item")?,
+            "--list-files" => set_output_mode(OutputMode::ListFiles, "--list-files")?,
+            "--diff" => {
+                diff = Some(PathBuf::from(flag_value(&mut args, "--diff")?));
+                set_output_mode(OutputMode::Diff, "--diff")?;
+            }
+            flag if flag.starts_with("--") => {
+                return Err(format!("Unknown option {flag}.\n{USAGE}"));
+            }
+            _ => {
+                if manifest_path.is_some() {
+                    return Err(USAGE.to_string());
+                }
+                manifest_path = Some(PathBuf::from(arg));
+            }
+        }
+    }
+    let input = match (manifest_path, file_path, config.input) {
+        (Some(manifest_path), None, _) => Input::Manifest(manifest_path),
+        (None, Some(file_path), _) => Input::File(file_path),
+        (Some(_), Some(_), _) => {
+            return Err(format!("A manifest path cannot be combined with --file.\n{USAGE}"));
+        }
+        // The config's input is used only when the command line names none.
+        (None, None, Some(input)) => input,
+        (None, None, None) => return Err(USAGE.to_string()),
+    };
+    if transform.is_some() && output_mode != OutputMode::Modules {
+        return Err(format!("Option --transform only applies to the modules document output.\n{USAGE}"));
+    }
+    if emit_index && output_mode != OutputMode::Modules {
+        return Err(format!("Option --emit-index only applies to the modules document output.\n{USAGE}"));
+    }
+    if expand && matches!(input, Input::File(_)) {
+        return Err(format!("Option --expand requires a manifest path, not --file.\n{USAGE}"));
+    }
+    if with_deps && matches!(input, Input::File(_)) {
+        return Err(format!("Option --with-deps requires a manifest path, not --file.\n{USAGE}"));
+    }
+    if out.is_some() && output_mode != OutputMode::Modules {
+        return Err(format!("Option --out only applies to the modules document output.\n{USAGE}"));
+    }
+    if watch && out.is_none() {
+        return Err(format!("Option --watch requires --out.\n{USAGE}"));
+    }
+    if since.is_some() {
+        if matches!(input, Input::File(_)) {
+            return Err(format!("Option --since requires a manifest path, not --file.\n{USAGE}"));
+        }
+        if out.is_none() {
+            return Err(format!("Option --since requires --out.\n{USAGE}"));
+        }
+        let conflicts = [
+            (watch, "--watch"),
+            (with_deps, "--with-deps"),
+            (expand, "--expand"),
+            (transform.is_some(), "--transform"),
+        ];
+        for (set, flag) in conflicts {
+            if set {
+                return Err(format!("Option --since cannot be combined with {flag}.\n{USAGE}"));
+            }
+        }
+    }
+    if flatten_separator.is_some() && !flatten {
+        return Err(format!("Option --flatten-separator requires --flatten.\n{USAGE}"));
+    }
+    if expand && output_mode == OutputMode::ListFiles {
+        return Err(format!("Option --expand cannot be combined with --list-files.\n{USAGE}"));
+    }
+    let defaults = match &input {
+        Input::Manifest(manifest_path) => ManifestDefaults::read(fs, manifest_path)?,
+        Input::File(_) => ManifestDefaults::default(),
+    };
+    skip_markers.extend(defaults.skip_markers);
+    Ok(Options {
+        input,
+        root_module_name: root_module_name.or(defaults.root_module_name),
+        subtree,
+        skip_markers,
+        attr_namespace: attr_namespace
+            .or(defaults.attr_namespace)
+            .unwrap_or_else(|| DEFAULT_ATTR_NAMESPACE.to_string()),
+        verbose,
+        resolve_paths: resolve_paths.or(defaults.resolve_paths).unwrap_or(false),
+        output_mode,
+        features,
+        no_default_features,
+        cfgs,
+        max_file_bytes: max_file_bytes.or(defaults.max_file_bytes).unwrap_or(DEFAULT_MAX_FILE_BYTES),
+        infer_properties: infer_properties.or(defaults.infer_properties).unwrap_or(false),
+        transform,
+        print_hash,
+        stats,
+        emit_index,
+        expand,
+        emit_attributes,
+        extract_examples,
+        recognize_futures,
+        preserve_source_order,
+        preserve_impl_groups,
+        flatten: flatten.then(|| flatten_separator.unwrap_or_else(|| DEFAULT_FLATTEN_SEPARATOR.to_string())),
+        out,
+        watch,
+        diff,
+        prelude: defaults.prelude,
+        with_deps,
+        serde_aware,
+        require_all_modules,
+        include_private,
+        max_issues,
+        since,
+        fingerprint,
+    })
+}
+
+/// `--config <path>`: options from a TOML file, or JSON when the path ends in `.json`. Keys are
+/// the flag names without `--` (`root-module-name = "api"`, `emit-attributes = true`); repeatable
+/// flags take arrays under plural keys (`features`, `cfgs`, `skip-markers`), and `manifest` or
+/// `file` names the input. The file becomes flag arguments parsed before the command line, so
+/// command-line flags override it. Paths (`manifest`, `file`, `out`, `diff`) are relative to the
+/// config file.
+#[derive(Default)]
+struct ConfigFile {
+    args: Vec<String>,
+    input: Option<Input>,
+}
+
+const CONFIG_VALUE_KEYS: &[&str] = &[
+    "root-module-name",
+    "subtree",
+    "attr-namespace",
+    "transform",
+    "flatten-separator",
+    "out",
+    "diff",
+];
+const CONFIG_LIST_KEYS: &[(&str, &str)] = &[("features", "--feature"), ("cfgs", "--cfg"), ("skip-markers", "--skip-marker")];
+/// Switches; `false` leaves them off unless the command line turns them on.
+const CONFIG_SWITCH_KEYS: &[&str] = &[
+    "verbose",
+    "print-hash",
+    "stats",
+    "emit-index",
+    "expand",
+    "emit-attributes",
+    "extract-examples",
+    "recognize-futures",
+    "preserve-source-order",
+    "preserve-impl-groups",
+    "flatten",
+    "watch",
+    "no-default-features",
+    "json-lines-per-item",
+    "list-files",
+    "with-deps",
+    "serde-aware",
+    "require-all-modules",
+    "include-private",
+];
+/// Switches with a `--no-` form, so `false` is passed on as `--no-<key>`.
+const CONFIG_NEGATABLE_KEYS: &[&str] = &["resolve-paths", "infer-properties"];
+
+impl ConfigFile {
+    /// Reads the file. A recognized key with the wrong type is an error; an unknown key is
+    /// ignored with a warning on stderr. Values are checked again as flags.
+    fn read(fs: &dyn SourceFs, path: &Path) -> Result<ConfigFile, String> {
+        let text = fs
+            .read(path)
+            .map_err(|e| format!("Failed to read config {}: {e}", path.display()))?;
+        let parse_error = |e: String| format!("Failed to parse config {}: {e}", path.display());
+        let entries = if path.extension().is_some_and(|extension| extension == "json") {
+            let value = serde_json::from_str::<serde_json::Value>(&text).map_err(|e| parse_error(e.to_string()))?;
+            let serde_json::Value::Object(map) = value else {
+                return Err(parse_error("expected a JSON object".to_string()));
+            };
+            map.into_iter().map(|(key, value)| (key, json_to_toml(value))).collect::<Vec<_>>()
+        } else {
+            Manifest::parse(&text).map_err(parse_error)?.entries.into_iter().collect()
+        };
+        let base = path.parent().unwrap_or(Path::new(""));
+        let relative = |value: &str| base.join(value).display().to_string();
+        let mut config = ConfigFile::default();
+        let mut unknown = BTreeSet::new();
+        for (name, value) in &entries {
+            let name = name.as_str();
+            let invalid = |expected: &str| format!("Invalid {name} in config {} (expected {expected}).", path.display());
+            match value {
+                TomlValue::String(text) if CONFIG_VALUE_KEYS.contains(&name) && !text.is_empty() => {
+                    let text = if matches!(name, "out" | "diff") { relative(text) } else { text.clone() };
+                    config.args.extend([format!("--{name}"), text]);
+                }
+                TomlValue::String(text) if matches!(name, "manifest" | "file") && !text.is_empty() => {
+                    if config.input.is_some() {
+                        return Err(format!("Config {} sets both manifest and file.", path.display()));
+                    }
+                    let input = PathBuf::from(relative(text));
+                    config.input = Some(if name == "manifest" { Input::Manifest(input) } else { Input::File(input) });
+                }
+                _ if CONFIG_VALUE_KEYS.contains(&name) || matches!(name, "manifest" | "file") => {
+                    return Err(invalid("a non-empty string"));
+                }
+                TomlValue::Bool(flag) if CONFIG_SWITCH_KEYS.contains(&name) => {
+                    if *flag {
+                        config.args.push(format!("--{name}"));
+                    }
+                }
+                TomlValue::Bool(flag) if CONFIG_NEGATABLE_KEYS.contains(&name) => {
+                    config.args.push(if *flag { format!("--{name}") } else { format!("--no-{name}") });
+                }
+                _ if CONFIG_SWITCH_KEYS.contains(&name) || CONFIG_NEGATABLE_KEYS.contains(&name) => {
+                    return Err(invalid("a boolean"));
+                }
+                TomlValue::Integer(bytes) if name == "max-file-bytes" && *bytes > 0 => {
+                    config.args.extend(["--max-file-bytes".to_string(), bytes.to_string()]);
+                }
+                _ if name == "max-file-bytes" => return Err(invalid("a positive byte count")),
+                TomlValue::Integer(count) if name == "max-issues" && *count >= 0 => {
+                    config.args.extend(["--max-issues".to_string(), count.to_string()]);
+                }
+                _ if name == "max-issues" => return Err(invalid("a non-negative issue count")),
+                _ => match CONFIG_LIST_KEYS.iter().find(|(key, _)| *key == name) {
+                    Some((_, flag)) => {
+                        let TomlValue::Array(values) = value else {
+                            return Err(invalid("an array of non-empty strings"));
+                        };
+                        for entry in values {
+                            match entry {
+                                TomlValue::String(text) if !text.is_empty() => {
+                                    config.args.extend([flag.to_string(), text.clone()]);
+                                }
+                                _ => return Err(invalid("an array of non-empty strings")),
+                            }
+                        }
+                    }
+                    // Nested tables flatten to dotted keys; warn once per top-level key.
+                    None => {
+                        unknown.insert(name.split('.').next().unwrap_or(name).to_string());
+                    }
+                },
+            }
+        }
+        for name in unknown {
+            eprintln!("[tsubabindgen-extractor] warning: ignoring unknown key {name} in config {}", path.display());
+        }
+        Ok(config)
+    }
+}
+
+/// A JSON config value in the shapes the TOML reader produces.
+fn json_to_toml(value: serde_json::Value) -> TomlValue {
+    match value {
+        serde_json::Value::String(text) => TomlValue::String(text),
+        serde_json::Value::Bool(flag) => TomlValue::Bool(flag),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(integer) => TomlValue::Integer(integer),
+            None => TomlValue::Other(number.to_string()),
+        },
+        serde_json::Value::Array(values) => TomlValue::Array(values.into_iter().map(json_to_toml).collect()),
+        serde_json::Value::Object(_) => TomlValue::Table,
+        serde_json::Value::Null => TomlValue::Other("null".to_string()),
+    }
+}
+
+const METADATA_TABLE: &str = "package.metadata.tsuba";
+
+/// Per-crate defaults from the manifest's `[package.metadata.tsuba]` table. Keys are named after
+/// the CLI flags they stand in for; flags given on the command line take precedence.
+#[derive(Default)]
+struct ManifestDefaults {
+    root_module_name: Option<String>,
+    attr_namespace: Option<String>,
+    skip_markers: Vec<String>,
+    resolve_paths: Option<bool>,
+    infer_properties: Option<bool>,
+    max_file_bytes: Option<u64>,
+    /// `prelude = "prelude"`: the module to mark as the crate's primary API. Manifest only; it
+    /// describes the crate rather than a run.
+    prelude: Option<Vec<String>>,
+}
+
+impl ManifestDefaults {
+    /// Reads the table. A recognized key with the wrong type is an error; an unknown key is
+    /// ignored with a warning on stderr.
+    fn read(fs: &dyn SourceFs, manifest_path: &Path) -> Result<ManifestDefaults, String> {
+        let manifest = Manifest::read(fs, manifest_path)?;
+        let mut defaults = ManifestDefaults::default();
+        let prefix = format!("{METADATA_TABLE}.");
+        let mut unknown = BTreeSet::new();
+        for (key, value) in &manifest.entries {
+            let Some(name) = key.strip_prefix(&prefix) else {
+                continue;
+            };
+            let invalid = |expected: &str| {
+                format!(
+                    "Invalid {METADATA_TABLE}.{name} in manifest {} (expected {expected}).",
+                    manifest_path.display()
+                )
+            };
+            match (name, value) {
+                ("schema", TomlValue::Integer(1)) => {}
+                ("schema", _) => return Err(invalid("1, the only supported output schema")),
+                ("root-module-name", TomlValue::String(text)) if !text.is_empty() => {
+                    defaults.root_module_name = Some(text.clone());
+                }
+                ("attr-namespace", TomlValue::String(text)) if !text.is_empty() => {
+                    defaults.attr_namespace = Some(text.clone());
+                }
+                ("skip-markers", TomlValue::Array(values)) => {
+                    for entry in values {
+                        match entry {
+                            TomlValue::String(marker) if !marker.is_empty() => {
+                                defaults.skip_markers.push(marker.clone());
+                            }
+                            _ => return Err(invalid("an array of non-empty strings")),
+                        }
+                    }
+                }
+                ("resolve-paths", TomlValue::Bool(flag)) => defaults.resolve_paths = Some(*flag),
+                ("infer-properties", TomlValue::Bool(flag)) => defaults.infer_properties = Some(*flag),
+                ("max-file-bytes", TomlValue::Integer(bytes)) if *bytes > 0 => {
+                    defaults.max_file_bytes = Some(*bytes as u64);
+                }
+                ("prelude", TomlValue::String(text)) => {
+                    let parts = parse_module_path(text).map_err(|_| invalid("a module path like \"prelude\""))?;
+                    defaults.prelude = Some(parts);
+                }
+                ("root-module-name" | "attr-namespace", _) => return Err(invalid("a non-empty string")),
+                ("skip-markers", _) => return Err(invalid("an array of non-empty strings")),
+                ("resolve-paths" | "infer-properties", _) => return Err(invalid("a boolean")),
+                ("max-file-bytes", _) => return Err(invalid("a positive byte count")),
+                ("prelude", _) => return Err(invalid("a module path like \"prelude\"")),
+                _ => {
+                    // Nested tables flatten to dotted keys; warn once per top-level key.
+                    unknown.insert(name.split('.').next().unwrap_or(name).to_string());
+                }
+            }
+        }
+        for name in unknown {
+            eprintln!(
+                "[tsubabindgen-extractor] warning: ignoring unknown key {METADATA_TABLE}.{name} in manifest {}",
+                manifest_path.display()
+            );
+        }
+        Ok(defaults)
+    }
+}
+
+fn parse_module_path(text: &str) -> Result<Vec<String>, String> {
+    let parts = text
+        .split("::")
+        .map(|part| part.trim().to_string())
+        .collect::<Vec<_>>();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(format!("Invalid module path '{text}' (expected segments like 'api::v1')."));
+    }
+    Ok(parts)
+}
+
+fn apply_subtree(modules: Vec<ExtractModule>, subtree: &[String]) -> Result<Vec<ExtractModule>, String> {
+    let rebased = modules
+        .into_iter()
+        .filter(|module| module.parts.starts_with(subtree))
+        .map(|mut module| {
+            module.parts.drain(..subtree.len());
+            module
+        })
+        .collect::<Vec<_>>();
+    if rebased.is_empty() {
+        return Err(format!(
+            "Subtree module '{}' was not found in the extracted module tree.",
+            subtree.join("::")
+        ));
+    }
+    Ok(rebased)
+}
+
+fn apply_root_module_name(modules: &mut [ExtractModule], name: &str) {
+    for module in modules {
+        module.parts.insert(0, name.to_string());
+    }
+}
+
+/// `--flatten`: merges every module into the root module (the first one), renaming each moved
+/// item to its module path relative to the root and its name, joined with `separator`
+/// (`inner::deep::Thing` becomes `inner_deep_Thing`). Impl blocks on a moved type follow it, and
+/// re-exports of a moved item point at its new name. A name already taken in the root keeps its
+/// first owner; the later item is dropped with a `module` issue.
+fn apply_flatten(modules: Vec<ExtractModule>, separator: &str) -> Vec<ExtractModule> {
+    let mut modules = modules.into_iter();
+    let Some(mut root) = modules.next() else {
+        return Vec::new();
+    };
+    let mut taken = BTreeSet::new();
+    taken.extend(root.consts.iter().map(|c| c.name.clone()));
+    taken.extend(root.enums.iter().map(|e| e.name.clone()));
+    taken.extend(root.structs.iter().map(|s| s.name.clone()));
+    taken.extend(root.traits.iter().map(|t| t.name.clone()));
+    taken.extend(root.functions.iter().map(|f| f.name.clone()));
+    taken.extend(root.reexports.iter().map(|r| r.name.clone()));
+    for module in modules {
+        let relative = module.parts.strip_prefix(root.parts.as_slice()).unwrap_or(&module.parts);
+        let mut flattener = Flattener {
+            prefix: relative.iter().map(|part| format!("{part}{separator}")).collect(),
+            module: &module.parts,
+            file: &module.file,
+            taken: &mut taken,
+            renamed: BTreeMap::new(),
+            names: &mut root.flattened,
+            issues: &mut root.issues,
+        };
+        let consts = flattener.rename(module.consts, "const", |c| &mut c.name);
+        let enums = flattener.rename(module.enums, "enum", |e| &mut e.name);
+        let structs = flattener.rename(module.structs, "struct", |s| &mut s.name);
+        let traits = flattener.rename(module.traits, "trait", |t| &mut t.name);
+        let functions = flattener.rename(module.functions, "function", |f| &mut f.name);
+        let reexports = flattener.rename(module.reexports, "re-export", |r| &mut r.name);
+        let mut pending_methods = module.pending_methods;
+        // A block on a dropped type would otherwise attach to the root item that kept the name.
+        pending_methods.retain_mut(|pending| match flattener.renamed.get(&pending.target) {
+            Some(Some(name)) => {
+                pending.target = name.clone();
+                true
+            }
+            Some(None) => false,
+            None => true,
+        });
+        let mut dependency_items = module.dependency_items;
+        dependency_items.retain_mut(|item| match flattener.renamed.get(&item.name) {
+            Some(Some(name)) => {
+                item.name = name.clone();
+                true
+            }
+            _ => false,
+        });
+        root.consts.extend(consts);
+        root.enums.extend(enums);
+        root.structs.extend(structs);
+        root.traits.extend(traits);
+        root.functions.extend(functions);
+        root.reexports.extend(reexports);
+        root.pending_methods.extend(pending_methods);
+        root.dependency_items.extend(dependency_items);
+        root.issues.extend(module.issues);
+    }
+    for reexport in &mut root.reexports {
+        let (Some(module), Some(source_name)) = (&reexport.source_module, &reexport.source_name) else {
+            continue;
+        };
+        if let Some(moved) = root
+            .flattened
+            .iter()
+            .find(|moved| &moved.module == module && &moved.rust_name == source_name)
+        {
+            reexport.source_name = Some(moved.name.clone());
+            reexport.source_module = Some(root.parts.clone());
+        }
+    }
+    root.pending_methods.sort_by(pending_order);
+    vec![root]
+}
+
+/// Renames the items of one module for `apply_flatten`.
+struct Flattener<'a> {
+    prefix: String,
+    module: &'a [String],
+    file: &'a str,
+    taken: &'a mut BTreeSet<String>,
+    /// Rust name to flattened name, or `None` when the item was dropped as a collision.
+    renamed: BTreeMap<String, Option<String>>,
+    names: &'a mut Vec<FlattenedName>,
+    issues: &'a mut Vec<SkipIssue>,
+}
+
+impl Flattener<'_> {
+    fn rename<T>(&mut self, items: Vec<T>, kind: &str, name_of: fn(&mut T) -> &mut String) -> Vec<T> {
+        let mut kept = Vec::new();
+        for mut item in items {
+            let rust_name = name_of(&mut item).clone();
+            let name = format!("{}{rust_name}", self.prefix);
+            if !self.taken.insert(name.clone()) {
+                let path = format!("{}::{rust_name}", self.module.join("::"));
+                self.issues.push(SkipIssue::coded(
+                    self.file,
+                    "module",
+                    "flatten_collision",
+                    path.clone(),
+                    format!("Flattened name '{name}' of {kind} '{path}' is already taken in the root module; the {kind} was skipped."),
+                ));
+                self.renamed.entry(rust_name).or_insert(None);
+                continue;
+            }
+            *name_of(&mut item) = name.clone();
+            self.renamed.insert(rust_name.clone(), Some(name.clone()));
+            self.names.push(FlattenedName {
+                name,
+                module: self.module.to_vec(),
+                rust_name,
+            });
+            kept.push(item);
+        }
+        kept
+    }
+}
+
+/// The module tree as emitted, after `--subtree`, `--root-module-name`, and `--flatten`, with its
+/// crate metadata.
+fn collect_output(
+    options: &Options,
+    fs: &dyn SourceFs,
+    stats: &mut Stats,
+    progress: &mut dyn FnMut(Progress<'_>),
+    cache: Option<&mut ParseCache>,
+) -> Result<(Vec<ExtractModule>, CrateMeta), String> {
+    let mut modules = extract_modules(options, fs, stats, progress, cache)?;
+    if let Some(prelude) = &options.prelude {
+        let module = modules.iter_mut().find(|module| &module.parts == prelude).ok_or_else(|| {
+            format!(
+                "Prelude module '{}' ({METADATA_TABLE}.prelude) was not found in the extracted module tree.",
+                prelude.join("::")
+            )
+        })?;
+        module.prelude = true;
+    }
+    if let Some(subtree) = &options.subtree {
+        modules = apply_subtree(modules, subtree)?;
+    }
+    if let Some(name) = &options.root_module_name {
+        apply_root_module_name(&mut modules, name);
+    }
+    if let Some(separator) = &options.flatten {
+        modules = apply_flatten(modules, separator);
+    }
+    let (mut crate_meta, name_issue) = crate_meta(options, fs)?;
+    // Marked before relabeling, so this is the module's final `parts`; `--subtree` or
+    // `--flatten` may have dropped it.
+    crate_meta.prelude = modules.iter().find(|module| module.prelude).map(|module| module.parts.clone());
+    if let (Some(issue), Some(first)) = (name_issue, modules.first_mut()) {
+        first.issues.push(issue);
+    }
+    if let Some(limit) = options.max_issues {
+        cap_issues(&mut modules, limit);
+    }
+    Ok((modules, crate_meta))
+}
+
+/// `--max-issues`: keeps the first `limit` issues in module order and replaces the rest with one
+/// `issues_suppressed` issue where the first dropped one was. `unresolved_module` issues are always
+/// kept and not counted, so `--require-all-modules` still lists every one.
+fn cap_issues(modules: &mut [ExtractModule], limit: usize) {
+    let mut kept = 0;
+    let mut suppressed = 0;
+    let mut marker = None;
+    for (index, module) in modules.iter_mut().enumerate() {
+        let mut issues = Vec::new();
+        for issue in std::mem::take(&mut module.issues) {
+            let exempt = issue.code.as_deref() == Some("unresolved_module");
+            if exempt || kept < limit {
+                if !exempt {
+                    kept += 1;
+                }
+                issues.push(issue);
+                continue;
+            }
+            suppressed += 1;
+            marker.get_or_insert((index, issues.len(), issue.file));
+        }
+        module.issues = issues;
+    }
+    if let Some((index, position, file)) = marker {
+        modules[index].issues.insert(
+            position,
+            SkipIssue::coded(
+                &file,
+                "issues",
+                "issues_suppressed",
+                format!("--max-issues {limit}"),
+                format!("{suppressed} more issue(s) suppressed by --max-issues {limit}."),
+            ),
+        );
+    }
+}
+
+/// `--list-files`: the labels of the files extraction would parse, in resolution order. Only
+/// `mod` items are followed, so `#[cfg]`-disabled modules, skip markers, and `--max-file-bytes`
+/// apply as usual. With `--subtree`, only files holding a module of that subtree are listed. Also
+/// returns the `--require-all-modules` error (see `unresolved_modules_error`).
+fn list_files(options: &Options, stats: &mut Stats) -> Result<(Vec<String>, Option<String>), String> {
+    let (crate_root, root_file) = resolve_root(&options.input, &RealFs)?;
+    let mut ignore_progress = |_: Progress<'_>| {};
+    let mut collector = Collector::new(options, &RealFs, &crate_root, &mut ignore_progress)?;
+    collector.collect_crate(&root_file, stats)?;
+    let subtree = options.subtree.as_deref().unwrap_or_default();
+    let mut files = Vec::<String>::new();
+    for (parts, file) in collector.module_files {
+        if parts.starts_with(subtree) && !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    if files.is_empty() && !subtree.is_empty() {
+        return Err(format!(
+            "Subtree module '{}' was not found in the extracted module tree.",
+            subtree.join("::")
+        ));
+    }
+    let unresolved = unresolved_modules_error(collector.out.iter().filter(|module| module.parts.starts_with(subtree)));
+    Ok((files, unresolved))
+}
+
+/// `--emit-index`: maps each const, enum, struct, trait, function, and macro name to its
+/// definitions, in module order and then that order. A name defined in several modules (or as
+/// both a type and a function) lists every location.
+fn build_index(modules: &[ExtractModule]) -> BTreeMap<String, Vec<IndexLocation>> {
+    let mut index = BTreeMap::<String, Vec<IndexLocation>>::new();
+    for module in modules {
+        let mut add = |name: &str, kind: &'static str, span: &SourceSpan| {
+            index.entry(name.to_string()).or_default().push(IndexLocation {
+                module: module.parts.clone(),
+                kind,
+                span: span.clone(),
+            });
+        };
+        for c in &module.consts {
+            if let Some(span) = &c.span {
+                add(&c.name, "const", span);
+            }
+        }
+        for e in &module.enums {
+            add(&e.name, "enum", &e.span);
+        }
+        for s in &module.structs {
+            add(&s.name, "struct", &s.span);
+        }
+        for t in &module.traits {
+            add(&t.name, "trait", &t.span);
+        }
+        for f in &module.functions {
+            add(&f.name, if f.kind == "macro" { "macro" } else { "function" }, &f.span);
+        }
+    }
+    index
+}
+
+/// Extracts the modules document for `options`, stamped with its `contentHash`. This is what the
+/// binary prints without `--transform`; output-mode and stderr-only options are ignored.
+pub fn extract(options: &Options) -> Result<ExtractOutput, String> {
+    extract_with(options, &RealFs)
+}
+
+/// Like [`extract`], reading the manifest and module files through `fs`, so sources that are
+/// not on disk (an editor's unsaved buffers, test fixtures in a [`MemoryFs`]) can be extracted.
+/// `--expand` still runs `cargo expand` against the real crate directory.
+pub fn extract_with(options: &Options, fs: &dyn SourceFs) -> Result<ExtractOutput, String> {
+    extract_with_progress(options, fs, &mut |_| {})
+}
+
+/// Like [`extract_with`], calling `progress` as each module file is parsed and each module is
+/// collected, so embedders can show progress on a large crate without reading `--verbose`
+/// output from stderr.
+pub fn extract_with_progress(
+    options: &Options,
+    fs: &dyn SourceFs,
+    progress: &mut dyn FnMut(Progress<'_>),
+) -> Result<ExtractOutput, String> {
+    stamp_payload(modules_document(options, fs, &mut Stats::default(), progress, None)?)
+}
+
+/// The modules document for `options`, before `contentHash` is set.
+fn modules_document(
+    options: &Options,
+    fs: &dyn SourceFs,
+    stats: &mut Stats,
+    progress: &mut dyn FnMut(Progress<'_>),
+    cache: Option<&mut ParseCache>,
+) -> Result<ExtractOutput, String> {
+    let (modules, crate_meta) = collect_output(options, fs, stats, progress, cache)?;
+    let index = options.emit_index.then(|| stats.time("index", || build_index(&modules)));
+    Ok(ExtractOutput {
+        schema: 1,
+        content_hash: None,
+        crate_meta,
+        modules,
+        index,
+    })
+}
+
+/// Sets `contentHash`, which covers the document as emitted, minus `contentHash` itself.
+fn stamp_payload(mut payload: ExtractOutput) -> Result<ExtractOutput, String> {
+    payload.content_hash = None;
+    payload.content_hash = Some(sha256_hex(to_json(&payload)?.as_bytes()));
+    Ok(payload)
+}
+
+/// Runs the extractor CLI with `args` (without the program name), writing to stdout (or the
+/// `--out` file).
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let started = Instant::now();
+    let args = args.collect::<Vec<_>>();
+    let options = parse_options(args.iter().cloned())?;
+    let mut stats = Stats::default();
+    // `--require-all-modules`: reported once the output is written.
+    let unresolved = match options.output_mode {
+        OutputMode::Modules if options.watch => return watch(&args, options),
+        OutputMode::Modules => {
+            let check = match (&options.since, &options.out) {
+                (Some(git_ref), Some(out)) => Some(stats.time("since", || since_check(&options, git_ref, out))?),
+                _ => None,
+            };
+            if let Some(SinceCheck::Reuse(document)) = &check {
+                cached_unresolved_error(document)
+            } else {
+                let mut stale = match check {
+                    Some(SinceCheck::Stale(reason, cache)) => Some((reason, cache)),
+                    _ => None,
+                };
+                let cache = stale.as_mut().map(|(_, cache)| cache);
+                let (_, json, missing) = render_modules(&options, &mut stats, cache)?;
+                stats.time("write", || write_document(&options, &json))?;
+                if let (Some((reason, cache)), Some(out)) = (stale, &options.out) {
+                    let (reused, total) = (cache.reused, cache.current.len());
+                    write_since_stamp(out, &options.fingerprint, cache.current)?;
+                    eprintln!(
+                        "[tsubabindgen-extractor] since: re-extracted ({reason}); parsed {} of {total} module files",
+                        total - reused
+                    );
+                }
+                missing
+            }
+        }
+        OutputMode::ItemLines => {
+            let (modules, crate_meta) = collect_output(&options, &RealFs, &mut stats, &mut |_| {}, None)?;
+            // Records are serialized as they are streamed, so `write` includes serialization here.
+            stats.time("write", || write_item_lines(&crate_meta.name, &modules))?;
+            unresolved_modules_error(&modules)
+        }
+        OutputMode::Diff => {
+            let old_manifest = options.diff.clone().ok_or_else(|| USAGE.to_string())?;
+            // The prelude describes a crate version, so the old one is read from its own manifest.
+            let old_options = Options {
+                prelude: ManifestDefaults::read(&RealFs, &old_manifest)?.prelude,
+                input: Input::Manifest(old_manifest),
+                ..options.clone()
+            };
+            let (old_modules, old_meta) = collect_output(&old_options, &RealFs, &mut stats, &mut |_| {}, None)?;
+            let (new_modules, new_meta) = collect_output(&options, &RealFs, &mut stats, &mut |_| {}, None)?;
+            let missing = unresolved_modules_error(&new_modules);
+            let diff = stats.time("diff", || api_diff((old_meta, &old_modules), (new_meta, &new_modules)))?;
+            stats.time("write", || {
+                println!("{}", to_json(&diff)?);
+                Ok::<_, String>(())
+            })?;
+            missing
+        }
+        OutputMode::ListFiles => {
+            let (files, missing) = list_files(&options, &mut stats)?;
+            stats.time("write", || {
+                let mut stdout = io::stdout().lock();
+                files
+                    .iter()
+                    .try_for_each(|file| writeln!(stdout, "{file}"))
+                    .map_err(|e| format!("Failed to write file list: {e}"))
+            })?;
+            missing
+        }
+    };
+    if options.stats {
+        stats.print(started.elapsed());
+    }
+    unresolved.map_or(Ok(()), Err)
+}
+
+/// `--require-all-modules`: the error listing every `unresolved_module` issue in `modules`, if
+/// there is one.
+fn unresolved_modules_error<'a>(modules: impl IntoIterator<Item = &'a ExtractModule>) -> Option<String> {
+    let unresolved = modules
+        .into_iter()
+        .flat_map(|module| &module.issues)
+        .filter(|issue| issue.code.as_deref() == Some("unresolved_module"))
+        .map(|issue| format!("  {}: {}", issue.file, issue.reason))
+        .collect::<Vec<_>>();
+    unresolved_list_error(unresolved)
+}
+
+/// `unresolved_modules_error` for a document reused by `--since`.
+fn cached_unresolved_error(document: &serde_json::Value) -> Option<String> {
+    let text = |value: &serde_json::Value| value.as_str().unwrap_or_default().to_string();
+    let unresolved = document["modules"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|module| module["issues"].as_array().into_iter().flatten())
+        .filter(|issue| issue["code"] == "unresolved_module")
+        .map(|issue| format!("  {}: {}", text(&issue["file"]), text(&issue["reason"])))
+        .collect::<Vec<_>>();
+    unresolved_list_error(unresolved)
+}
+
+fn unresolved_list_error(unresolved: Vec<String>) -> Option<String> {
+    (!unresolved.is_empty()).then(|| {
+        format!(
+            "{} module declaration(s) could not be resolved (--require-all-modules):\n{}",
+            unresolved.len(),
+            unresolved.join("\n")
+        )
+    })
+}
+
+/// Extracts the modules document (through a `--since` cache, see `extract_modules`), through
+/// `--transform` if given, and returns its `contentHash` and JSON text, plus the
+/// `--require-all-modules` error (see `unresolved_modules_error`).
+fn render_modules(
+    options: &Options,
+    stats: &mut Stats,
+    cache: Option<&mut ParseCache>,
+) -> Result<(String, String, Option<String>), String> {
+    let payload = modules_document(options, &RealFs, stats, &mut |_| {}, cache)?;
+    let unresolved = unresolved_modules_error(&payload.modules);
+    let serialize_start = Instant::now();
+    let mut transform_time = Duration::ZERO;
+    let (hash, json) = match &options.transform {
+        None => {
+            let payload = stamp_payload(payload)?;
+            (payload.content_hash.clone().unwrap_or_default(), to_json(&payload)?)
+        }
+        Some(program) => {
+            let transform_start = Instant::now();
+            let mut transformed = apply_transform(program, to_json(&payload)?)?;
+            transform_time = transform_start.elapsed();
+            let hash = sha256_hex(to_json(&transformed)?.as_bytes());
+            transformed.insert("contentHash".to_string(), serde_json::Value::from(hash.clone()));
+            (hash, to_json(&transformed)?)
+        }
+    };
+    stats.phase("serialize", serialize_start.elapsed().saturating_sub(transform_time));
+    if options.transform.is_some() {
+        stats.phase("transform", transform_time);
+    }
+    if options.print_hash {
+        eprintln!("[tsubabindgen-extractor] contentHash {hash}");
+    }
+    Ok((hash, json, unresolved))
+}
+
+/// Writes the modules document to the `--out` file, or to stdout. Outside `--since`, a `--since`
+/// stamp left next to the file is removed, since it describes the document being replaced.
+fn write_document(options: &Options, json: &str) -> Result<(), String> {
+    match &options.out {
+        Some(path) => {
+            fs::write(path, format!("{json}\n")).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            if options.since.is_some() {
+                return Ok(());
+            }
+            let stamp = since_stamp_path(path);
+            match fs::remove_file(&stamp) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                    Err(format!("Failed to remove {}: {e}", stamp.display()))
+                }
+                _ => Ok(()),
+            }
+        }
+        None => {
+            println!("{json}");
+            Ok(())
+        }
+    }
+}
+
+/// Flags that do not change the document, left out of `options_fingerprint`.
+const FINGERPRINT_IGNORED_FLAGS: &[&str] = &["--stats", "--verbose", "--print-hash"];
+
+/// Hash of the extractor version and every argument (config file entries first) except `--since`
+/// and `FINGERPRINT_IGNORED_FLAGS`, so `--since` only reuses a document written with the same
+/// flags. Reordered flags hash differently and only cost a full extraction.
+fn options_fingerprint(args: &[String]) -> String {
+    let mut kept = vec![env!("CARGO_PKG_VERSION")];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--since" => {
+                args.next();
+            }
+            flag if FINGERPRINT_IGNORED_FLAGS.contains(&flag) => {}
+            other => kept.push(other),
+        }
+    }
+    sha256_hex(kept.join("\n").as_bytes())
+}
+
+/// `--since`: the `--out` file's companion, `<out>.since` (see `SinceStamp`).
+fn since_stamp_path(out: &Path) -> PathBuf {
+    let mut path = out.as_os_str().to_owned();
+    path.push(".since");
+    PathBuf::from(path)
+}
+
+/// `--since`: the fingerprint of the run that wrote the `--out` document, and the module files it
+/// collected, keyed by label (see `FileRecord`).
+#[derive(Serialize, Deserialize)]
+struct SinceStamp {
+    fingerprint: String,
+    files: BTreeMap<String, FileRecord>,
+}
+
+fn write_since_stamp(out: &Path, fingerprint: &str, files: BTreeMap<String, FileRecord>) -> Result<(), String> {
+    let stamp = SinceStamp {
+        fingerprint: fingerprint.to_string(),
+        files,
+    };
+    let path = since_stamp_path(out);
+    fs::write(&path, format!("{}\n", to_json(&stamp)?))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+enum SinceCheck {
+    /// The cached document, still current.
+    Reuse(serde_json::Value),
+    /// Why the crate is extracted again, and the module files of the previous run that can be
+    /// replayed (none when the options or `Cargo.toml` changed).
+    Stale(String, ParseCache),
+}
+
+/// `--since`: reuses the `--out` document when nothing it depends on changed since `git_ref`,
+/// else says why and which recorded module files can be replayed.
+fn since_check(options: &Options, git_ref: &str, out: &Path) -> Result<SinceCheck, String> {
+    let stamp = fs::read_to_string(since_stamp_path(out))
+        .ok()
+        .and_then(|text| serde_json::from_str::<SinceStamp>(&text).ok())
+        .filter(|stamp| stamp.fingerprint == options.fingerprint);
+    let Some(stamp) = stamp else {
+        return Ok(SinceCheck::Stale("no cached output for these options".to_string(), ParseCache::default()));
+    };
+    let cache = ParseCache {
+        previous: stamp.files.into_iter().collect(),
+        ..ParseCache::default()
+    };
+    let cached = fs::read_to_string(out).ok();
+    let Some(document) = cached.and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok()) else {
+        return Ok(SinceCheck::Stale("cached output could not be read".to_string(), cache));
+    };
+    if document["schema"] != 1 {
+        return Ok(SinceCheck::Stale("cached output has another schema".to_string(), cache));
+    }
+    let (crate_root, root_file) = resolve_root(&options.input, &RealFs)?;
+    let changed = git_changed_files(&crate_root, git_ref)?;
+    if changed.contains("Cargo.toml") {
+        return Ok(SinceCheck::Stale(format!("Cargo.toml changed since {git_ref}"), ParseCache::default()));
+    }
+    let mut labels = BTreeSet::new();
+    collect_file_labels(&document, &mut labels);
+    let source_dir = relative_module_label(&crate_root, root_file.parent().unwrap_or(&crate_root));
+    let source_prefix = if source_dir == "." { String::new() } else { format!("{source_dir}/") };
+    for file in changed {
+        let source = file.ends_with(".rs") && file.starts_with(&source_prefix);
+        if labels.contains(&file) || source {
+            return Ok(SinceCheck::Stale(format!("{file} changed since {git_ref}"), cache));
+        }
+    }
+    // Module files outside the crate directory (symlink targets) have absolute labels and are
+    // invisible to `git diff` there, so their contents are checked against the stamp.
+    let edited_outside = cache
+        .previous
+        .iter()
+        .filter(|(label, record)| {
+            let path = Path::new(label);
+            path.is_absolute() && fs::read(path).map_or(true, |source| sha256_hex(&source) != record.hash)
+        })
+        .map(|(label, _)| label.clone())
+        .min();
+    if let Some(label) = edited_outside {
+        return Ok(SinceCheck::Stale(format!("{label} changed since the cached run"), cache));
+    }
+    eprintln!("[tsubabindgen-extractor] since: reused {} (no module file changed since {git_ref})", out.display());
+    if options.print_hash {
+        eprintln!("[tsubabindgen-extractor] contentHash {}", document["contentHash"].as_str().unwrap_or_default());
+    }
+    Ok(SinceCheck::Reuse(document))
+}
+
+/// Every `file` and `sourceFile` label in a modules document.
+fn collect_file_labels(value: &serde_json::Value, out: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("file" | "sourceFile", serde_json::Value::String(label)) => {
+                        out.insert(label.clone());
+                    }
+                    _ => collect_file_labels(value, out),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter().for_each(|value| collect_file_labels(value, out)),
+        _ => {}
+    }
+}
+
+/// `--since`: crate-relative paths that differ between `git_ref` and the working tree, and
+/// untracked files that are not ignored.
+fn git_changed_files(crate_root: &Path, git_ref: &str) -> Result<BTreeSet<String>, String> {
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(["-c", "core.quotePath=false"])
+            .args(args)
+            .current_dir(crate_root)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("--since: could not run git: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let detail = stderr.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or("no error output");
+            return Err(format!("--since: git {}: {detail}", args.join(" ")));
+        }
+        String::from_utf8(output.stdout).map_err(|_| "--since: git printed non-UTF-8 output".to_string())
+    };
+    let mut files = BTreeSet::new();
+    for listing in [
+        git(&["diff", "--name-only", "--relative", git_ref, "--"])?,
+        git(&["ls-files", "--others", "--exclude-standard"])?,
+    ] {
+        files.extend(listing.lines().filter(|line| !line.is_empty()).map(str::to_string));
+    }
+    Ok(files)
+}
+
+/// How often `--watch` checks the crate's files for changes.
+const WATCH_POLL: Duration = Duration::from_millis(100);
+/// How long the files must stay unchanged after a change before `--watch` re-extracts, so an
+/// editor saving several files (or writing one in steps) triggers a single run.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// `--watch`: writes the `--out` document, then re-extracts on every change to a watched file
+/// until the process is killed.
+fn watch(args: &[String], mut options: Options) -> Result<(), String> {
+    let (crate_root, _) = resolve_root(&options.input, &RealFs)?;
+    let config = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|at| args.get(at + 1))
+        .map(PathBuf::from);
+    let manifest = crate_root.join("Cargo.toml");
+    let mut last_hash = None;
+    let mut cache = ParseCache::default();
+    // The config file and every module file read, including ones that resolve outside the crate
+    // directory (a symlinked `src/shared.rs`).
+    let mut extra = config.iter().cloned().collect::<Vec<_>>();
+    let mut seen = watched_files(&crate_root, &extra);
+    loop {
+        let started = Instant::now();
+        let mut stats = Stats::default();
+        let result = render_modules(&options, &mut stats, Some(&mut cache)).and_then(|(hash, json, unresolved)| {
+            if let Some(error) = unresolved {
+                eprintln!("[tsubabindgen-extractor] watch: {error}");
+            }
+            if last_hash.as_ref() == Some(&hash) {
+                return Ok(None);
+            }
+            stats.time("write", || write_document(&options, &json))?;
+            Ok(Some(hash))
+        });
+        let elapsed = format_duration(started.elapsed());
+        let total = cache.current.len();
+        let parsed = format!("parsed {} of {total} module files", total - cache.reused);
+        match result {
+            Ok(Some(hash)) => {
+                eprintln!("[tsubabindgen-extractor] watch: extracted in {elapsed} ({parsed}), contentHash {hash}");
+                last_hash = Some(hash);
+            }
+            Ok(None) => eprintln!("[tsubabindgen-extractor] watch: extracted in {elapsed} ({parsed}), unchanged"),
+            Err(error) => eprintln!("[tsubabindgen-extractor] watch: extraction failed: {error}"),
+        }
+        if options.stats {
+            stats.print(started.elapsed());
+        }
+        // A file first seen in this run is stamped only now, so an edit made since the collector
+        // read it would count as seen; compare its contents with what was parsed instead.
+        let mut edited = false;
+        if !stats.files.is_empty() {
+            extra = config.iter().cloned().collect();
+            for file in &stats.files {
+                let path = crate_root.join(&file.file);
+                if let (false, Some(stamp)) = (seen.contains_key(&path), watch_stamp(&path)) {
+                    seen.insert(path.clone(), stamp);
+                    edited |= cache.current.get(&file.file).is_some_and(|record| {
+                        fs::read(&path).map_or(true, |source| sha256_hex(&source) != record.hash)
+                    });
+                }
+                extra.push(path);
+            }
+        }
+        // The next run replays what this one recorded.
+        cache = ParseCache {
+            previous: std::mem::take(&mut cache.current).into_iter().collect(),
+            ..ParseCache::default()
+        };
+        // Wait for a change, then for the files to settle.
+        let before = seen.clone();
+        while !edited {
+            std::thread::sleep(WATCH_POLL);
+            let current = watched_files(&crate_root, &extra);
+            edited = current != seen;
+            seen = current;
+        }
+        let mut settled = Instant::now();
+        while settled.elapsed() < WATCH_DEBOUNCE {
+            std::thread::sleep(WATCH_POLL);
+            let current = watched_files(&crate_root, &extra);
+            if current != seen {
+                seen = current;
+                settled = Instant::now();
+            }
+        }
+        let reread = std::iter::once(&manifest)
+            .chain(&config)
+            .any(|path| before.get(path) != seen.get(path));
+        if reread {
+            cache = ParseCache::default();
+            match parse_options(args.iter().cloned()) {
+                Ok(reparsed) => options = reparsed,
+                Err(error) => eprintln!("[tsubabindgen-extractor] watch: extraction failed: {error}"),
+            }
+        }
+    }
+}
+
+/// Modification time and size of every `.rs` file under `dir`, of `dir/Cargo.toml`, and of each
+/// `extra` file, for `--watch`. `target` and hidden directories are skipped; unreadable entries
+/// are ignored.
+fn watched_files(dir: &Path, extra: &[PathBuf]) -> BTreeMap<PathBuf, (Option<std::time::SystemTime>, u64)> {
+    fn walk(dir: &Path, top: bool, out: &mut BTreeMap<PathBuf, (Option<std::time::SystemTime>, u64)>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let skipped = name.starts_with('.') || (top && name == "target");
+            if metadata.is_dir() {
+                if !skipped {
+                    walk(&path, false, out);
+                }
+            } else if name.ends_with(".rs") || (top && name == "Cargo.toml") {
+                out.insert(path, (metadata.modified().ok(), metadata.len()));
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    walk(dir, true, &mut out);
+    for path in extra {
+        if let Some(stamp) = watch_stamp(path) {
+            out.insert(path.clone(), stamp);
+        }
+    }
+    out
+}
+
+/// Modification time and size of one `--watch` file, `None` when it cannot be read.
+fn watch_stamp(path: &Path) -> Option<(Option<std::time::SystemTime>, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/// `--transform`: pipes the output document through `sh -c <program>` and returns the program's
+/// stdout document once it passes `validate_output`.
+fn apply_transform(program: &str, json: String) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("Failed to start transform program '{program}': {e}"))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| format!("Failed to open stdin of transform program '{program}'."))?;
+    // Written from a thread so a program that streams its output cannot deadlock on a full pipe.
+    let writer = std::thread::spawn(move || stdin.write_all(json.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run transform program '{program}': {e}"))?;
+    let written = writer
+        .join()
+        .map_err(|_| format!("Failed to write to transform program '{program}'."))?;
+    if !output.status.success() {
+        return Err(format!("Transform program '{program}' failed ({}).", output.status));
+    }
+    // A program may succeed without reading all of its input; only its output matters then.
+    match written {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            return Err(format!("Failed to write to transform program '{program}': {e}"));
+        }
+        _ => {}
+    }
+    let value = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        .map_err(|e| format!("Transform program '{program}' did not print valid JSON: {e}"))?;
+    validate_output(&value)
+        .map_err(|e| format!("Transform program '{program}' output does not match the extractor schema: {e}"))?;
+    let serde_json::Value::Object(mut document) = value else {
+        return Err(format!("Transform program '{program}' output is not a JSON object."));
+    };
+    // A stale hash copied through from the input no longer describes the document.
+    document.remove("contentHash");
+    Ok(document)
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("Failed to serialize extractor output: {e}"))
+}
+
+/// SHA-256 of `data` as lowercase hex.
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Structural check of a modules document: the envelope, every module's lists, and the fields
+/// consumers key on (`name`, `target`, issue texts). Other fields are not constrained.
+fn validate_output(value: &serde_json::Value) -> Result<(), String> {
+    use serde_json::Value;
+    fn field<'v>(value: &'v Value, at: &str, key: &str) -> Result<&'v Value, String> {
+        value.get(key).ok_or_else(|| format!("{at}.{key} is missing."))
+    }
+    fn string(value: &Value, at: &str, key: &str) -> Result<(), String> {
+        match field(value, at, key)? {
+            Value::String(_) => Ok(()),
+            _ => Err(format!("{at}.{key} must be a string.")),
+        }
+    }
+    fn array<'v>(value: &'v Value, at: &str, key: &str) -> Result<&'v [Value], String> {
+        match field(value, at, key)? {
+            Value::Array(items) => Ok(items),
+            _ => Err(format!("{at}.{key} must be an array.")),
+        }
+    }
+    if value.get("schema") != Some(&Value::from(1)) {
+        return Err("schema must be 1.".to_string());
+    }
+    string(field(value, "", "crate")?, "crate", "name")?;
+    for (index, module) in array(value, "", "modules")?.iter().enumerate() {
+        let at = format!("modules[{index}]");
+        string(module, &at, "file")?;
+        if !array(module, &at, "parts")?.iter().all(Value::is_string) {
+            return Err(format!("{at}.parts must be an array of strings."));
+        }
+        for key in ["consts", "enums", "structs", "traits", "functions"] {
+            for (item_index, item) in array(module, &at, key)?.iter().enumerate() {
+                string(item, &format!("{at}.{key}[{item_index}]"), "name")?;
+            }
+        }
+        for (item_index, item) in array(module, &at, "reexports")?.iter().enumerate() {
+            let item_at = format!("{at}.reexports[{item_index}]");
+            string(item, &item_at, "name")?;
+            string(item, &item_at, "source")?;
+        }
+        for (item_index, item) in array(module, &at, "pendingMethods")?.iter().enumerate() {
+            let item_at = format!("{at}.pendingMethods[{item_index}]");
+            string(item, &item_at, "target")?;
+            for (method_index, method) in array(item, &item_at, "methods")?.iter().enumerate() {
+                string(method, &format!("{item_at}.methods[{method_index}]"), "name")?;
+            }
+        }
+        for (item_index, item) in array(module, &at, "issues")?.iter().enumerate() {
+            let item_at = format!("{at}.issues[{item_index}]");
+            for key in ["file", "kind", "snippet", "reason"] {
+                string(item, &item_at, key)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn item_path(crate_name: &str, module: &[String], names: &[&str]) -> String {
+    let mut path = vec![crate_name];
+    path.extend(module.iter().map(String::as_str));
+    path.extend(names);
+    path.join("::")
+}
+
+/// A module's items as `(kind, path, target, item)`, in the `--json-lines-per-item` order:
+/// consts, enums, structs, traits, functions, re-exports, impl members (methods, then
+/// associated consts and types), and issues (which have no path).
+fn module_items<'a>(
+    crate_name: &str,
+    module: &'a ExtractModule,
+) -> Vec<(&'static str, Option<String>, Option<&'a str>, RecordItem<'a>)> {
+    let parts = &module.parts;
+    let mut items = Vec::<(&'static str, Option<String>, Option<&str>, RecordItem)>::new();
+    for c in &module.consts {
+        items.push(("const", Some(item_path(crate_name, parts, &[&c.name])), None, RecordItem::Const(c)));
+    }
+    for e in &module.enums {
+        items.push(("enum", Some(item_path(crate_name, parts, &[&e.name])), None, RecordItem::Enum(e)));
+    }
+    for s in &module.structs {
+        items.push(("struct", Some(item_path(crate_name, parts, &[&s.name])), None, RecordItem::Struct(s)));
+    }
+    for t in &module.traits {
+        items.push(("trait", Some(item_path(crate_name, parts, &[&t.name])), None, RecordItem::Trait(t)));
+    }
+    for f in &module.functions {
+        items.push(("function", Some(item_path(crate_name, parts, &[&f.name])), None, RecordItem::Function(f)));
+    }
+    for r in &module.reexports {
+        items.push(("reexport", Some(item_path(crate_name, parts, &[&r.name])), None, RecordItem::Reexport(r)));
+    }
+    for pending in &module.pending_methods {
+        for m in &pending.methods {
+            let path = item_path(crate_name, parts, &[&pending.target, &m.name]);
+            items.push(("method", Some(path), Some(&pending.target), RecordItem::Function(m)));
+        }
+        for (kind, fields) in [("assoc_const", &pending.consts), ("assoc_type", &pending.types)] {
+            for field in fields {
+                let path = item_path(crate_name, parts, &[&pending.target, &field.name]);
+                items.push((kind, Some(path), Some(&pending.target), RecordItem::Const(field)));
+            }
+        }
+    }
+    for issue in &module.issues {
+        items.push(("issue", None, None, RecordItem::Issue(issue)));
+    }
+    items
+}
+
+/// `--json-lines-per-item`: flattens the module tree into one record per item, in module order
+/// and then `module_items` order.
+fn write_item_lines(crate_name: &str, modules: &[ExtractModule]) -> Result<(), String> {
+    let mut stdout = io::stdout().lock();
+    for module in modules {
+        let parts = &module.parts;
+        for (kind, path, target, item) in module_items(crate_name, module) {
+            let record = ItemRecord {
+                schema: 1,
+                crate_name,
+                module: parts,
+                file: &module.file,
+                kind,
+                path,
+                target,
+                item,
+            };
+            let json = serde_json::to_string(&record)
+                .map_err(|e| format!("Failed to serialize extractor output: {e}"))?;
+            writeln!(stdout, "{json}").map_err(|e| format!("Failed to write extractor output: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// `--diff`: the public items added, removed, and changed between the old crate (`--diff
+/// <old-manifest>`) and the extracted one.
+#[derive(Serialize)]
+struct ApiDiff {
+    schema: u32,
+    old: CrateMeta,
+    new: CrateMeta,
+    added: Vec<DiffEntry>,
+    removed: Vec<DiffEntry>,
+    changed: Vec<DiffEntry>,
+}
+
+/// One item in an `ApiDiff`, keyed by `kind` and its crate-relative `path`
+/// (`crate::shapes::Circle`, `crate::shapes::Circle::area` for methods), so the two versions line
+/// up even when the crate is renamed.
+#[derive(Serialize)]
+struct DiffEntry {
+    kind: &'static str,
+    path: String,
+    /// Changed items: the item keys whose values differ (`params`, `returnType`, `fields`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    changes: Vec<String>,
+}
+
+/// Item keys that do not affect the API surface: source locations move with every edit, and
+/// docs are prose.
+const DIFF_IGNORED_KEYS: &[&str] = &["sourceFile", "byteStart", "byteEnd", "lineStart", "lineEnd", "docs", "docAliases", "examples"];
+
+/// Compares the items of two extractions (see `module_items`; issues are not compared). An item
+/// is changed when its JSON differs once `DIFF_IGNORED_KEYS` are removed at every depth. Each list
+/// is sorted by path, then kind.
+fn api_diff(old: (CrateMeta, &[ExtractModule]), new: (CrateMeta, &[ExtractModule])) -> Result<ApiDiff, String> {
+    type Items = BTreeMap<(String, &'static str), Vec<serde_json::Value>>;
+    fn strip(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.retain(|key, _| !DIFF_IGNORED_KEYS.contains(&key.as_str()));
+                map.values_mut().for_each(strip);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip),
+            _ => {}
+        }
+    }
+    let items = |modules: &[ExtractModule]| -> Result<Items, String> {
+        let mut out = Items::new();
+        for module in modules {
+            for (kind, path, _, item) in module_items("crate", module) {
+                let Some(path) = path else {
+                    continue;
+                };
+                let mut value = serde_json::to_value(&item)
+                    .map_err(|e| format!("Failed to serialize extractor output: {e}"))?;
+                strip(&mut value);
+                // Items sharing a path (`#[cfg]` alternatives left unevaluated) compare as a group.
+                out.entry((path, kind)).or_default().push(value);
+            }
+        }
+        Ok(out)
+    };
+    let (old_items, new_items) = (items(old.1)?, items(new.1)?);
+    let entry = |(path, kind): &(String, &'static str), changes| DiffEntry {
+        kind,
+        path: path.clone(),
+        changes,
+    };
+    let mut diff = ApiDiff {
+        schema: 1,
+        old: old.0,
+        new: new.0,
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    for (key, old_values) in &old_items {
+        let Some(new_values) = new_items.get(key) else {
+            diff.removed.push(entry(key, Vec::new()));
+            continue;
+        };
+        if old_values == new_values {
+            continue;
+        }
+        let keys = |values: &[serde_json::Value]| -> BTreeSet<String> {
+            values.iter().filter_map(|value| value.as_object()).flat_map(|map| map.keys().cloned()).collect()
+        };
+        let changes = keys(old_values)
+            .union(&keys(new_values))
+            .filter(|field| {
+                let values = |values: &[serde_json::Value]| -> Vec<Option<serde_json::Value>> {
+                    values.iter().map(|value| value.get(field.as_str()).cloned()).collect()
+                };
+                values(old_values) != values(new_values)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        diff.changed.push(entry(key, changes));
+    }
+    for key in new_items.keys().filter(|key| !old_items.contains_key(*key)) {
+        diff.added.push(entry(key, Vec::new()));
+    }
+    Ok(diff)
+}
@@ -0,0 +1,76 @@
+//! Manifest reading on a `Cargo.toml` written the way real crates write them: multi-line and
+//! literal strings, escapes, comments, dotted keys, inline tables, and arrays of tables.
+
+use tsubabindgen_extractor::{extract_with, parse_options_with, MemoryFs};
+
+const MANIFEST: &str = r#"
+[package]
+name = "real-world" # the package name
+version = "0.1.0"
+description = """
+A crate with a "multi-line" \
+description."""
+authors = ['Jane Doe <jane@example.com>']
+keywords = [
+    "bindings",
+    "ffi", # trailing comma
+]
+metadata.tsuba = { schema = 1, prelude = "prelude" }
+
+[lib]
+name = "real_world_lib"
+path = 'src/root.rs'
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"], optional = true }
+
+[features]
+default = ["std"]
+std = []
+"with-serde" = ["dep:serde", "std"]
+
+[[bin]]
+name = "tool"
+path = "src/main.rs"
+
+[[bin]]
+name = "other"
+path = "src/other.rs"
+"#;
+
+#[test]
+fn reads_real_world_manifest_syntax() {
+    let mut memory = MemoryFs::new();
+    memory
+        .insert("/virtual/real/Cargo.toml", MANIFEST)
+        .insert("/virtual/real/src/root.rs", "pub mod prelude;\npub fn top() {}\n")
+        .insert("/virtual/real/src/prelude.rs", "pub struct Item;\n");
+    let options = parse_options_with(["/virtual/real/Cargo.toml".to_string()].into_iter(), &memory).unwrap();
+    let output = serde_json::to_value(extract_with(&options, &memory).unwrap()).unwrap();
+
+    let meta = &output["crate"];
+    assert_eq!(meta["name"], "real_world_lib");
+    assert_eq!(meta["package"], "real-world");
+    assert_eq!(meta["prelude"], serde_json::json!(["prelude"]));
+    assert_eq!(
+        meta["features"],
+        serde_json::json!({ "default": ["std"], "std": [], "with-serde": ["dep:serde", "std"] })
+    );
+    assert_eq!(meta["defaultFeatures"], serde_json::json!(["std"]));
+    let modules = output["modules"].as_array().unwrap();
+    assert_eq!(modules[0]["file"], "src/root.rs");
+    assert_eq!(modules[0]["parts"], serde_json::json!([]));
+}
+
+#[test]
+fn reports_manifest_syntax_errors() {
+    let mut memory = MemoryFs::new();
+    memory
+        .insert("/virtual/bad/Cargo.toml", "[package]\nname = \"bad\nversion = \"0.1.0\"\n")
+        .insert("/virtual/bad/src/lib.rs", "pub fn f() {}\n");
+    let error = parse_options_with(["/virtual/bad/Cargo.toml".to_string()].into_iter(), &memory)
+        .and_then(|options| extract_with(&options, &memory).map(|_| ()))
+        .expect_err("unterminated string");
+    assert!(error.contains("Failed to parse manifest /virtual/bad/Cargo.toml"), "{error}");
+    assert!(error.contains("line 2"), "{error}");
+}
@@ -0,0 +1,62 @@
+//! Extraction through `MemoryFs`: sources that only exist in memory must produce the same
+//! document as the same files on disk, since module labels are crate-relative.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use tsubabindgen_extractor::{extract, extract_with, parse_options, parse_options_with, MemoryFs};
+
+fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../../test/fixtures/bindgen/@tsuba")
+        .join(name)
+        .join("crate")
+}
+
+/// Copies every file under `dir` into `memory`, rooted at `root` instead of `dir`.
+fn load(memory: &mut MemoryFs, dir: &Path, root: &Path) {
+    for entry in fs::read_dir(dir).unwrap_or_else(|e| panic!("{}: {e}", dir.display())) {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            load(memory, &path, &root.join(path.file_name().unwrap()));
+        } else {
+            memory.insert(root.join(path.file_name().unwrap()), fs::read_to_string(&path).unwrap());
+        }
+    }
+}
+
+fn to_json(output: &tsubabindgen_extractor::ExtractOutput) -> String {
+    serde_json::to_string_pretty(output).unwrap()
+}
+
+#[test]
+fn matches_disk_extraction() {
+    for name in ["simple", "nested-modules", "reexports"] {
+        let dir = fixture_dir(name);
+        let on_disk = parse_options([dir.join("Cargo.toml").display().to_string()].into_iter()).unwrap();
+        let expected = extract(&on_disk).unwrap_or_else(|e| panic!("{name}: {e}"));
+
+        let mut memory = MemoryFs::new();
+        let root = Path::new("/virtual").join(name);
+        load(&mut memory, &dir, &root);
+        // `.` segments resolve like on disk.
+        let manifest = root.join(".").join("Cargo.toml").display().to_string();
+        let in_memory = parse_options_with([manifest].into_iter(), &memory).unwrap();
+        let actual = extract_with(&in_memory, &memory).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(to_json(&actual), to_json(&expected), "{name}: in-memory extraction differs from disk");
+    }
+}
+
+#[test]
+fn reports_missing_in_memory_files() {
+    let mut memory = MemoryFs::new();
+    memory
+        .insert("/virtual/demo/Cargo.toml", "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n")
+        .insert("/virtual/demo/src/lib.rs", "pub mod missing;\npub fn ok() {}\n");
+    let options = parse_options_with(["/virtual/demo/Cargo.toml".to_string()].into_iter(), &memory).unwrap();
+    let error = extract_with(&options, &memory).err().expect("missing module file");
+    assert!(error.contains("Could not resolve pub mod 'missing'"), "{error}");
+
+    memory.insert("/virtual/demo/src/missing.rs", "pub struct Found;\n");
+    let output = to_json(&extract_with(&options, &memory).unwrap());
+    assert!(output.contains("\"Found\""), "{output}");
+}
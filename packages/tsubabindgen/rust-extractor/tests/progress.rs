@@ -0,0 +1,47 @@
+//! `extract_with_progress`: the callback sees every parsed file and every collected module, in
+//! walk order, and the document is the same as without it.
+
+use tsubabindgen_extractor::{extract_with, extract_with_progress, parse_options_with, MemoryFs, Progress};
+
+#[test]
+fn reports_parsed_files_and_collected_modules() {
+    let mut memory = MemoryFs::new();
+    memory
+        .insert("/virtual/demo/Cargo.toml", "[package]\nname = \"demo\"\nversion = \"0.1.0\"\n")
+        .insert("/virtual/demo/src/lib.rs", "pub mod shapes;\npub mod inline { pub fn f() {} }\n")
+        .insert("/virtual/demo/src/shapes.rs", "pub struct Circle;\n")
+        .insert("/virtual/demo/src/broken.rs", "pub fn (\n");
+    let options = parse_options_with(["/virtual/demo/Cargo.toml".to_string()].into_iter(), &memory).unwrap();
+
+    let mut events = Vec::new();
+    let output = extract_with_progress(&options, &memory, &mut |event| {
+        events.push(match event {
+            Progress::ParsingFile { file, module } => format!("parse {file} {}", module.join("::")),
+            Progress::ModuleCollected { module, file } => format!("module {} {file}", module.join("::")),
+        });
+    })
+    .unwrap();
+    assert_eq!(
+        events,
+        [
+            "parse src/lib.rs ",
+            "parse src/shapes.rs shapes",
+            "module shapes src/shapes.rs",
+            "module inline src/lib.rs",
+            "module  src/lib.rs",
+        ]
+    );
+    let plain = extract_with(&options, &memory).unwrap();
+    assert_eq!(serde_json::to_string(&output).unwrap(), serde_json::to_string(&plain).unwrap());
+
+    // A file that does not parse still completes its module.
+    memory.insert("/virtual/demo/src/lib.rs", "pub mod broken;\n");
+    let mut collected = Vec::new();
+    extract_with_progress(&options, &memory, &mut |event| {
+        if let Progress::ModuleCollected { module, .. } = event {
+            collected.push(module.join("::"));
+        }
+    })
+    .unwrap();
+    assert_eq!(collected, ["broken", ""]);
+}
@@ -0,0 +1,172 @@
+//! Pins the extractor's modules document for every bindgen fixture crate against the committed
+//! JSON under `tests/snapshots/`. After an intended output change, regenerate them with
+//! `UPDATE_SNAPSHOTS=1 cargo test` and review the diff.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn repo_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../..")
+}
+
+fn check(name: &str, manifest: &str) {
+    let manifest = repo_root().join(manifest);
+    let options = tsubabindgen_extractor::parse_options([manifest.display().to_string()].into_iter())
+        .unwrap_or_else(|e| panic!("{name}: {e}"));
+    let output = tsubabindgen_extractor::extract(&options).unwrap_or_else(|e| panic!("{name}: {e}"));
+    let actual = serde_json::to_string_pretty(&output).unwrap_or_else(|e| panic!("{name}: {e}")) + "\n";
+    let snapshot = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.json"));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&snapshot, &actual).unwrap_or_else(|e| panic!("{}: {e}", snapshot.display()));
+        return;
+    }
+    let expected = fs::read_to_string(&snapshot).unwrap_or_else(|e| {
+        panic!("{}: {e} (run with UPDATE_SNAPSHOTS=1 to create it)", snapshot.display())
+    });
+    assert!(
+        actual == expected,
+        "{name}: extractor output differs from {}; rerun with UPDATE_SNAPSHOTS=1 if the change is intended.\n{}",
+        snapshot.display(),
+        first_difference(&expected, &actual)
+    );
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => break,
+            (left, right) if left == right => {}
+            (left, right) => {
+                return format!(
+                    "first difference at line {line}:\n  snapshot: {}\n  actual:   {}",
+                    left.unwrap_or("<end of file>"),
+                    right.unwrap_or("<end of file>")
+                );
+            }
+        }
+    }
+    String::new()
+}
+
+#[test]
+fn simple() {
+    check("simple", "test/fixtures/bindgen/@tsuba/simple/crate/Cargo.toml");
+}
+
+#[test]
+fn advanced() {
+    check("advanced", "test/fixtures/bindgen/@tsuba/advanced/crate/Cargo.toml");
+}
+
+#[test]
+fn traits() {
+    check("traits", "test/fixtures/bindgen/@tsuba/traits/crate/Cargo.toml");
+}
+
+#[test]
+fn edge() {
+    check("edge", "test/fixtures/bindgen/@tsuba/edge/crate/Cargo.toml");
+}
+
+#[test]
+fn reexports() {
+    check("reexports", "test/fixtures/bindgen/@tsuba/reexports/crate/Cargo.toml");
+}
+
+#[test]
+fn parse_failure() {
+    check("parse-failure", "test/fixtures/bindgen/@tsuba/parse-failure/crate/Cargo.toml");
+}
+
+#[test]
+fn nested_modules() {
+    check("nested-modules", "test/fixtures/bindgen/@tsuba/nested-modules/crate/Cargo.toml");
+}
+
+#[test]
+fn layout_2018() {
+    check("layout-2018", "test/fixtures/bindgen/@tsuba/layout-2018/crate/Cargo.toml");
+}
+
+#[test]
+fn async_fns() {
+    check("async", "test/fixtures/bindgen/@tsuba/async/crate/Cargo.toml");
+}
+
+#[test]
+fn where_bounds() {
+    check("where-bounds", "test/fixtures/bindgen/@tsuba/where-bounds/crate/Cargo.toml");
+}
+
+#[test]
+fn impl_trait_args() {
+    check("impl-trait-args", "test/fixtures/bindgen/@tsuba/impl-trait-args/crate/Cargo.toml");
+}
+
+#[test]
+fn builders() {
+    check("builders", "test/fixtures/bindgen/@tsuba/builders/crate/Cargo.toml");
+}
+
+#[test]
+fn const_generics() {
+    check("const-generics", "test/fixtures/bindgen/@tsuba/const-generics/crate/Cargo.toml");
+}
+
+#[test]
+fn trait_paths() {
+    check("trait-paths", "test/fixtures/bindgen/@tsuba/trait-paths/crate/Cargo.toml");
+}
+
+#[test]
+fn cfg_combinators() {
+    check("cfg-combinators", "test/fixtures/bindgen/@tsuba/cfg-combinators/crate/Cargo.toml");
+}
+
+#[test]
+fn nested_generics() {
+    check("nested-generics", "test/fixtures/bindgen/@tsuba/nested-generics/crate/Cargo.toml");
+}
+
+#[test]
+fn recursive_types() {
+    check("recursive-types", "test/fixtures/bindgen/@tsuba/recursive-types/crate/Cargo.toml");
+}
+
+#[test]
+fn gats() {
+    check("gats", "test/fixtures/bindgen/@tsuba/gats/crate/Cargo.toml");
+}
+
+#[test]
+fn variadic() {
+    check("variadic", "test/fixtures/bindgen/@tsuba/variadic/crate/Cargo.toml");
+}
+
+#[test]
+fn facade() {
+    check("facade", "test/fixtures/bindgen/@tsuba/facade/crate/Cargo.toml");
+}
+
+#[test]
+fn assoc_consts() {
+    check("assoc-consts", "test/fixtures/bindgen/@tsuba/assoc-consts/crate/Cargo.toml");
+}
+
+#[test]
+fn factories() {
+    check("factories", "test/fixtures/bindgen/@tsuba/factories/crate/Cargo.toml");
+}
+
+#[test]
+fn proof_analytics() {
+    check(
+        "proof-analytics",
+        "test/proof-codebases/bindgen-heavy/vendor/proof-analytics/Cargo.toml",
+    );
+}
@@ -0,0 +1,142 @@
+//! `--watch` end to end: the binary must rewrite `--out` after a source edit, leave it alone
+//! when the document does not change, keep watching after a failed run, and only parse the
+//! module files that changed.
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+use tsubabindgen_extractor::parse_options;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+fn temp_crate(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tsubabindgen-watch-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\nname = \"watched\"\nversion = \"0.1.0\"\n").unwrap();
+    fs::write(dir.join("src/lib.rs"), "pub fn first() {}\n").unwrap();
+    dir
+}
+
+/// Kills the watcher when the test ends, pass or fail.
+struct Watcher(Child);
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn start(dir: &Path, args: &[&str]) -> (Watcher, Receiver<String>) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tsubabindgen-extractor"))
+        .args(args)
+        .arg("--watch")
+        .arg("--out")
+        .arg(dir.join("out.json"))
+        .arg(dir.join("Cargo.toml"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stderr = child.stderr.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    (Watcher(child), receiver)
+}
+
+/// The next watch status line, skipping anything else on stderr.
+fn next_status(lines: &Receiver<String>) -> String {
+    loop {
+        let line = lines.recv_timeout(TIMEOUT).expect("watch status line");
+        if let Some(status) = line.strip_prefix("[tsubabindgen-extractor] watch: ") {
+            return status.to_string();
+        }
+    }
+}
+
+#[test]
+fn reextracts_on_change() {
+    let dir = temp_crate("change");
+    let (_watcher, lines) = start(&dir, &[]);
+    let out = dir.join("out.json");
+
+    assert!(next_status(&lines).contains("contentHash"));
+    assert!(fs::read_to_string(&out).unwrap().contains("\"first\""));
+
+    fs::write(dir.join("src/lib.rs"), "pub fn first() {}\npub fn second() {}\n").unwrap();
+    assert!(next_status(&lines).contains("contentHash"));
+    assert!(fs::read_to_string(&out).unwrap().contains("\"second\""));
+
+    // A trailing comment changes the file but not the document.
+    fs::write(dir.join("src/lib.rs"), "pub fn first() {}\npub fn second() {}\n// note\n").unwrap();
+    assert!(next_status(&lines).ends_with("unchanged"));
+
+    fs::write(dir.join("src/lib.rs"), "pub mod missing;\n").unwrap();
+    let failed = next_status(&lines);
+    assert!(failed.starts_with("extraction failed: "), "{failed}");
+    assert!(fs::read_to_string(&out).unwrap().contains("\"second\""));
+
+    fs::write(dir.join("src/missing.rs"), "pub struct Found;\n").unwrap();
+    assert!(next_status(&lines).contains("contentHash"));
+    assert!(fs::read_to_string(&out).unwrap().contains("\"Found\""));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn reparses_only_changed_files_and_watches_outside_files() {
+    let dir = temp_crate("cache");
+    let outside = std::env::temp_dir().join(format!("tsubabindgen-watch-outside-{}.rs", std::process::id()));
+    fs::write(&outside, "pub fn away() {}\n").unwrap();
+    std::os::unix::fs::symlink(&outside, dir.join("src/away.rs")).unwrap();
+    fs::write(dir.join("src/lib.rs"), "pub mod inner;\npub mod away;\n").unwrap();
+    fs::write(dir.join("src/inner.rs"), "pub fn inner() {}\n").unwrap();
+    let config = dir.join("tsuba.toml");
+    fs::write(&config, "stats = false\n").unwrap();
+    let (_watcher, lines) = start(&dir, &["--config", config.to_str().unwrap()]);
+    let out = dir.join("out.json");
+
+    let status = next_status(&lines);
+    assert!(status.contains("(parsed 3 of 3 module files), contentHash"), "{status}");
+
+    // A module file that resolves outside the crate directory is watched through its target,
+    // and only it is parsed again. The edit can land before the watcher has stamped the file
+    // after its first run; the file's contents are checked against what was parsed, so the
+    // outcome is the same either way.
+    fs::write(&outside, "pub fn away() {}\npub fn back() {}\n").unwrap();
+    let status = next_status(&lines);
+    assert!(status.contains("(parsed 1 of 3 module files), contentHash"), "{status}");
+    assert!(fs::read_to_string(&out).unwrap().contains("\"back\""));
+
+    // A config change re-reads the options and parses every file again.
+    fs::write(&config, "root-module-name = \"renamed\"\n").unwrap();
+    let status = next_status(&lines);
+    assert!(status.contains("(parsed 3 of 3 module files), contentHash"), "{status}");
+    assert!(fs::read_to_string(&out).unwrap().contains("\"renamed\""));
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_file(&outside);
+}
+
+#[test]
+fn requires_out_for_the_modules_document() {
+    let dir = temp_crate("options");
+    let manifest = dir.join("Cargo.toml").display().to_string();
+    let parse = |args: &[&str]| {
+        let args = args.iter().map(ToString::to_string).chain([manifest.clone()]);
+        parse_options(args).err().unwrap_or_default()
+    };
+    assert!(parse(&["--watch"]).starts_with("Option --watch requires --out."));
+    assert!(parse(&["--out", "x.json", "--list-files"]).starts_with("Option --out only applies to the modules document output."));
+    assert_eq!(parse(&["--watch", "--out", "x.json"]), "");
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,2990 @@
+import { spawnSync } from "node:child_process";
+import { createHash } from "node:crypto";
+import {
+  appendFileSync,
+  existsSync,
+  mkdirSync,
+  mkdtempSync,
+  readdirSync,
+  readFileSync,
+  realpathSync,
+  rmSync,
+  symlinkSync,
+  writeFileSync,
+} from "node:fs";
+import { tmpdir } from "node:os";
+import { dirname, join, resolve } from "node:path";
+import { fileURLToPath } from "node:url";
+
+import { expect } from "chai";
+
+import type {
+  ExtractedField,
+  ExtractedFunction,
+  ExtractedOutput,
+  ExtractedPath,
+  ExtractedSpan,
+} from "./pipeline/common.js";
+
+describe("@tsuba/tsubabindgen rust extractor", () => {
+  function repoRoot(): string {
+    const here = fileURLToPath(import.meta.url);
+    return resolve(dirname(here), "../../../");
+  }
+
+  function fixtureManifest(fixture: string): string {
+    return join(repoRoot(), "test", "fixtures", "bindgen", "@tsuba", fixture, "crate", "Cargo.toml");
+  }
+
+  function writeCrate(files: Readonly<Record<string, string>>): string {
+    const crateDir = mkdtempSync(join(tmpdir(), "tsubabindgen-extractor-"));
+    writeFileSync(
+      join(crateDir, "Cargo.toml"),
+      '[package]\nname = "extractor-case"\nversion = "0.1.0"\nedition = "2021"\n'
+    );
+    for (const [rel, text] of Object.entries(files)) {
+      mkdirSync(dirname(join(crateDir, rel)), { recursive: true });
+      writeFileSync(join(crateDir, rel), text);
+    }
+    return join(crateDir, "Cargo.toml");
+  }
+
+  function runExtractor(
+    args: readonly string[],
+    env?: Readonly<Record<string, string>>
+  ): { readonly status: number | null; readonly stdout: string; readonly stderr: string } {
+    const extractorManifest = join(repoRoot(), "packages", "tsubabindgen", "rust-extractor", "Cargo.toml");
+    const result = spawnSync("cargo", ["run", "--quiet", "--manifest-path", extractorManifest, "--", ...args], {
+      encoding: "utf-8",
+      env: env === undefined ? process.env : { ...process.env, ...env },
+    });
+    return { status: result.status, stdout: result.stdout ?? "", stderr: result.stderr ?? "" };
+  }
+
+  function gitIn(crateDir: string, args: readonly string[]): void {
+    const result = spawnSync("git", ["-c", "user.name=tsuba", "-c", "user.email=tsuba@example.com", ...args], {
+      cwd: crateDir,
+      encoding: "utf-8",
+    });
+    expect(result.status, result.stderr).to.equal(0);
+  }
+
+  function extract(args: readonly string[]): ExtractedOutput {
+    const result = runExtractor(args);
+    expect(result.status, result.stderr).to.equal(0);
+    return JSON.parse(result.stdout) as ExtractedOutput;
+  }
+
+  afterEach(() => {
+    for (const entry of readdirSync(tmpdir(), { withFileTypes: true })) {
+      if (!entry.isDirectory() || !entry.name.startsWith("tsubabindgen-extractor-")) continue;
+      rmSync(join(tmpdir(), entry.name), { recursive: true, force: true });
+    }
+  });
+
+  it("labels the crate-root module when --root-module-name is set", () => {
+    const manifest = fixtureManifest("simple");
+    expect(extract([manifest]).modules.map((m) => m.parts)).to.deep.equal([[], ["math"]]);
+    expect(extract(["--root-module-name", "simple", manifest]).modules.map((m) => m.parts)).to.deep.equal([
+      ["simple"],
+      ["simple", "math"],
+    ]);
+  });
+
+  it("rejects unknown options with usage text", () => {
+    const result = runExtractor(["--bogus", fixtureManifest("simple")]);
+    expect(result.status).to.equal(1);
+    expect(result.stderr).to.include("Unknown option --bogus.");
+    expect(result.stderr).to.include("Usage: tsubabindgen-extractor");
+  });
+
+  it("keeps lifetime-generic impl targets and reports the impl lifetimes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Holder<'a> {",
+        "    pub text: &'a str,",
+        "}",
+        "",
+        "impl<'a> Holder<'a> {",
+        "    pub fn text(&self) -> &'a str {",
+        "        self.text",
+        "    }",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.pendingMethods.map((p) => [p.target, p.methods.map((m) => m.name)])).to.deep.equal([
+      ["Holder", ["text"]],
+    ]);
+    expect(
+      root.issues.some(
+        (issue) => issue.kind === "generic" && issue.snippet === "'a" && issue.reason.startsWith("Impl 'Holder'")
+      )
+    ).to.equal(true);
+  });
+
+  it("records lifetime parameters in lifetimes, separate from typeParams", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Holder<'a, T> { pub value: &'a T }",
+        "pub enum Token<'src> { Word(&'src str) }",
+        "pub trait Lexer<'src> { fn next<'buf>(&self, buf: &'buf str) -> Token<'src>; }",
+        "impl<'a, T> Holder<'a, T> { pub fn get(&self) -> &'a T { self.value } }",
+        "pub fn longest<'x, 'y>(left: &'x str, right: &'y str) -> usize { left.len().max(right.len()) }",
+        "pub fn plain(value: u32) -> u32 { value }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs.map((s) => [s.name, s.typeParams, s.lifetimes])).to.deep.equal([["Holder", ["T"], ["'a"]]]);
+    expect(root.enums.map((e) => [e.name, e.lifetimes])).to.deep.equal([["Token", ["'src"]]]);
+    expect(root.traits.map((t) => [t.name, t.lifetimes, t.methods.map((m) => m.lifetimes)])).to.deep.equal([
+      ["Lexer", ["'src"], [["'buf"]]],
+    ]);
+    expect(root.pendingMethods.map((p) => [p.target, p.typeParams, p.lifetimes])).to.deep.equal([["Holder", ["T"], ["'a"]]]);
+    expect(root.functions.map((f) => [f.name, f.lifetimes])).to.deep.equal([
+      ["longest", ["'x", "'y"]],
+      ["plain", undefined],
+    ]);
+    const lifetimeIssues = root.issues.filter((i) => i.kind === "generic" && i.snippet.startsWith("'"));
+    expect(lifetimeIssues.map((i) => i.reason)).to.include(
+      "Function 'longest' lifetime generic parameters are not representable in TS facades; they are recorded in `lifetimes`."
+    );
+  });
+
+  it("rebases output onto a module subtree with --subtree", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod api;\n\npub fn outside() {}\n",
+      "src/api.rs": "pub mod v1;\n\npub fn top() {}\n",
+      "src/api/v1.rs": "pub fn leaf() {}\n",
+    });
+    const output = extract(["--subtree", "api", manifest]);
+    expect(output.modules.map((m) => [m.parts, m.file])).to.deep.equal([
+      [[], "src/api.rs"],
+      [["v1"], "src/api/v1.rs"],
+    ]);
+    const missing = runExtractor(["--subtree", "nope", manifest]);
+    expect(missing.status).to.equal(1);
+    expect(missing.stderr).to.include("Subtree module 'nope' was not found");
+  });
+
+  it("skips module files carrying a skip marker comment", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod manual;\npub mod proto;\n\npub fn root() {}\n",
+      "src/manual.rs": "// tsuba:skip\npub fn hidden() {}\n",
+      "src/proto.rs": "// @generated by protoc\npub fn generated() {}\n",
+    });
+    const summarize = (output: ExtractedOutput): unknown =>
+      output.modules.map((m) => [m.parts, m.functions.map((f) => f.name), m.issues.map((i) => i.code ?? i.kind)]);
+    expect(summarize(extract([manifest]))).to.deep.equal([
+      [[], ["root"], []],
+      [["manual"], [], ["skipped_by_marker"]],
+      [["proto"], ["generated"], []],
+    ]);
+    expect(summarize(extract(["--skip-marker", "@generated", manifest]))).to.deep.equal([
+      [[], ["root"], []],
+      [["manual"], [], ["skipped_by_marker"]],
+      [["proto"], [], ["skipped_by_marker"]],
+    ]);
+  });
+
+  it("excludes items marked with the skip tool attribute", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "#[tsuba(skip)]",
+        "pub mod internal;",
+        "",
+        "#[tsuba(skip)]",
+        "pub fn hidden() {}",
+        "",
+        "pub fn shown() {}",
+        "",
+        "#[tsuba(skip)]",
+        "pub struct Secret {",
+        "    pub value: i32,",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const output = extract([manifest]);
+    expect(output.modules).to.have.lengthOf(1);
+    const root = output.modules[0]!;
+    expect(root.functions.map((f) => f.name)).to.deep.equal(["shown"]);
+    expect(root.structs).to.deep.equal([]);
+    expect(root.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["skip", "skipped_by_attribute", "internal"],
+      ["skip", "skipped_by_attribute", "hidden"],
+      ["skip", "skipped_by_attribute", "Secret"],
+    ]);
+  });
+
+  it("records rename tool attributes as exportName overrides", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        '#[tsuba(rename = "makeThing")]',
+        "pub fn make_thing() {}",
+        "",
+        '#[tsuba(rename = "Gadget")]',
+        "pub struct Thing {",
+        '    #[tsuba(rename = "deleteFlag")]',
+        "    pub delete: bool,",
+        "    pub plain: i32,",
+        "}",
+        "",
+        "impl Thing {",
+        '    #[tsuba(rename = "create")]',
+        "    pub fn new() -> Thing {",
+        "        Thing { delete: false, plain: 0 }",
+        "    }",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.functions.map((f) => [f.name, f.exportName])).to.deep.equal([["make_thing", "makeThing"]]);
+    expect(root.structs.map((st) => [st.name, st.exportName])).to.deep.equal([["Thing", "Gadget"]]);
+    expect(root.structs[0]!.fields.map((f) => [f.name, f.exportName])).to.deep.equal([
+      ["delete", "deleteFlag"],
+      ["plain", undefined],
+    ]);
+    expect(root.pendingMethods[0]!.methods.map((m) => [m.name, m.exportName])).to.deep.equal([["new", "create"]]);
+  });
+
+  it("resolves file-backed children of file-backed and inline modules", () => {
+    const output = extract([fixtureManifest("nested-modules")]);
+    expect(output.modules.map((m) => [m.parts.join("::"), m.file, m.functions.map((f) => f.name)])).to.deep.equal([
+      ["", "src/lib.rs", ["root_fn"]],
+      ["foo", "src/foo.rs", ["foo_fn"]],
+      ["foo::bar", "src/foo/bar.rs", ["bar_fn"]],
+      ["foo::inner", "src/foo.rs", []],
+      ["foo::inner::baz", "src/foo/inner/baz.rs", ["baz_fn"]],
+      ["foo::main", "src/foo/main.rs", ["main_fn"]],
+      ["foo::main::entry", "src/foo/main/entry.rs", ["entry_fn"]],
+      ["wrap", "src/lib.rs", ["wrap_fn"]],
+      ["wrap::deep", "src/wrap/deep.rs", ["deep_fn"]],
+    ]);
+  });
+
+  it("resolves a multi-level 2018 layout without any mod.rs", () => {
+    const output = extract([fixtureManifest("layout-2018")]);
+    expect(output.modules.map((m) => [m.parts.join("::"), m.file, m.functions.map((f) => f.name)])).to.deep.equal([
+      ["", "src/lib.rs", ["root_fn"]],
+      ["a", "src/a.rs", ["a_fn"]],
+      ["a::b", "src/a/b.rs", ["b_fn"]],
+      ["a::b::c", "src/a/b/c.rs", ["c_fn"]],
+      ["a::b::c::d", "src/a/b/c/d.rs", ["d_fn"]],
+      ["a::b::helpers", "src/a/b.rs", []],
+      ["a::b::helpers::format", "src/a/b/helpers/format.rs", ["format_fn"]],
+    ]);
+    expect(output.modules.flatMap((m) => m.issues)).to.deep.equal([]);
+  });
+
+  it("records item source spans relative to their module file", () => {
+    const lib = [
+      "pub mod shapes;",
+      "",
+      "/// Doubles a value.",
+      "pub fn double(x: i32) -> i32 {",
+      "    x * 2",
+      "}",
+      "",
+    ].join("\n");
+    const shapes = [
+      "pub struct Point {",
+      "    pub x: i32,",
+      "}",
+      "",
+      "impl Point {",
+      "    pub fn origin() -> Point {",
+      "        Point { x: 0 }",
+      "    }",
+      "}",
+      "",
+    ].join("\n");
+    const manifest = writeCrate({ "src/lib.rs": lib, "src/shapes.rs": shapes });
+    const [root, shapesModule] = extract([manifest]).modules;
+    const slice = (text: string, span: ExtractedSpan): string => text.slice(span.byteStart, span.byteEnd);
+
+    const double = root!.functions[0]!;
+    expect([double.sourceFile, double.lineStart, double.lineEnd]).to.deep.equal(["src/lib.rs", 3, 6]);
+    expect(slice(lib, double)).to.equal("/// Doubles a value.\npub fn double(x: i32) -> i32 {\n    x * 2\n}");
+
+    const point = shapesModule!.structs[0]!;
+    expect([point.sourceFile, point.lineStart, point.lineEnd]).to.deep.equal(["src/shapes.rs", 1, 3]);
+    expect(slice(shapes, point)).to.equal("pub struct Point {\n    pub x: i32,\n}");
+    const origin = shapesModule!.pendingMethods[0]!.methods[0]!;
+    expect([origin.lineStart, origin.lineEnd]).to.deep.equal([6, 8]);
+    expect(slice(shapes, origin).startsWith("pub fn origin() -> Point {")).to.equal(true);
+  });
+
+  it("extracts a standalone source file with --file", () => {
+    const manifest = writeCrate({
+      "scratch/solo.rs": "pub mod helper;\n\npub fn solo() {}\n",
+      "scratch/helper.rs": "pub fn help() {}\n",
+    });
+    const file = join(dirname(manifest), "scratch", "solo.rs");
+    const output = extract(["--file", file]);
+    expect(output.modules.map((m) => [m.parts, m.file, m.functions.map((f) => f.name)])).to.deep.equal([
+      [[], "solo.rs", ["solo"]],
+      [["helper"], "helper.rs", ["help"]],
+    ]);
+    const both = runExtractor(["--file", file, manifest]);
+    expect(both.status).to.equal(1);
+    expect(both.stderr).to.include("cannot be combined with --file");
+  });
+
+  it("flags methods that take self by value as consuming", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "use std::pin::Pin;",
+        "",
+        "pub struct Builder {",
+        "    pub size: u32,",
+        "}",
+        "",
+        "impl Builder {",
+        "    pub fn new() -> Builder {",
+        "        Builder { size: 0 }",
+        "    }",
+        "    pub fn size(&self) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn grow(&mut self) {",
+        "        self.size += 1;",
+        "    }",
+        "    pub fn with_size(mut self, size: u32) -> Builder {",
+        "        self.size = size;",
+        "        self",
+        "    }",
+        "    pub fn build(self) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn boxed(self: Box<Self>) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn peek(self: &Self) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn owned(self: Self) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn shared(self: std::rc::Rc<Self>) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn atomic(self: std::sync::Arc<Self>) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn poll(self: std::pin::Pin<&mut Self>) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn pinned(self: Pin<&Self>) -> u32 {",
+        "        self.size",
+        "    }",
+        "    pub fn pinned_box(self: Pin<Box<Self>>) -> u32 {",
+        "        self.size",
+        "    }",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const methods = extract([manifest]).modules[0]!.pendingMethods[0]!.methods;
+    expect(methods.map((m) => [m.name, m.consumesSelf])).to.deep.equal([
+      ["new", false],
+      ["size", false],
+      ["grow", false],
+      ["with_size", true],
+      ["build", true],
+      ["boxed", true],
+      ["peek", false],
+      ["owned", true],
+      ["shared", true],
+      ["atomic", true],
+      ["poll", false],
+      ["pinned", false],
+      ["pinned_box", true],
+    ]);
+  });
+
+  it("logs resolution decisions to stderr with --verbose without changing stdout", () => {
+    const manifest = fixtureManifest("nested-modules");
+    const quiet = runExtractor([manifest]);
+    const verbose = runExtractor(["--verbose", manifest]);
+    expect(verbose.status).to.equal(0);
+    expect(quiet.stderr).to.equal("");
+    expect(verbose.stdout).to.equal(quiet.stdout);
+    const lines = verbose.stderr.trim().split("\n");
+    expect(lines.every((line) => line.startsWith("[tsubabindgen-extractor] "))).to.equal(true);
+    expect(lines).to.include("[tsubabindgen-extractor] parsing src/foo/main/entry.rs as module foo::main::entry");
+    expect(lines.some((line) => line.startsWith("[tsubabindgen-extractor] resolved mod foo::bar -> "))).to.equal(true);
+    expect(lines.some((line) => line.startsWith("[tsubabindgen-extractor] collected module crate from src/lib.rs"))).to.equal(
+      true
+    );
+  });
+
+  it("counts struct fields omitted for being private", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Mixed {",
+        "    pub id: u32,",
+        "    secret: u32,",
+        "    pub(crate) cache: u32,",
+        "}",
+        "",
+        "pub struct Sealed {",
+        "    inner: u32,",
+        "}",
+        "",
+        "pub struct Marker;",
+        "",
+      ].join("\n"),
+    });
+    const structs = extract([manifest]).modules[0]!.structs;
+    expect(structs.map((st) => [st.name, st.fields.map((f) => f.name), st.skippedPrivateFields])).to.deep.equal([
+      ["Mixed", ["id"], 2],
+      ["Sealed", [], 1],
+      ["Marker", [], 0],
+    ]);
+  });
+
+  it("flags types with derived or hand-written Default impls", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod impls;",
+        "",
+        "#[derive(Clone, Default)]",
+        "pub struct Derived {",
+        "    pub a: u32,",
+        "}",
+        "",
+        "pub struct Manual {",
+        "    pub b: u32,",
+        "}",
+        "",
+        "pub struct Plain {",
+        "    pub c: u32,",
+        "}",
+        "",
+        "#[derive(std::default::Default)]",
+        "pub enum Mode {",
+        "    #[default]",
+        "    Off,",
+        "    On,",
+        "}",
+        "",
+      ].join("\n"),
+      "src/impls.rs": [
+        "impl Default for crate::Manual {",
+        "    fn default() -> Self {",
+        "        crate::Manual { b: 1 }",
+        "    }",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs.map((st) => [st.name, st.hasDefault])).to.deep.equal([
+      ["Derived", true],
+      ["Manual", true],
+      ["Plain", false],
+    ]);
+    expect(root.enums.map((e) => [e.name, e.hasDefault])).to.deep.equal([["Mode", true]]);
+  });
+
+  it("reports a module that resolves to both foo.rs and foo/mod.rs", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod foo;\n",
+      "src/foo.rs": "pub fn stale() {}\n",
+      "src/foo/mod.rs": "pub fn current() {}\n",
+    });
+    const output = extract([manifest]);
+    expect(output.modules.map((m) => [m.parts, m.functions.length])).to.deep.equal([
+      [[], 0],
+      [["foo"], 0],
+    ]);
+    const foo = output.modules[1]!;
+    expect(foo.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["module", "ambiguous_module_path", "src/foo.rs, src/foo/mod.rs"],
+    ]);
+  });
+
+  it("splits generic path types into base and argument texts", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Holder {",
+        "    pub items: Vec<i32>,",
+        "    pub table: std::collections::HashMap<String, Vec<u8>>,",
+        "    pub plain: u32,",
+        "}",
+        "",
+        "pub fn spread(source: Box<dyn Iterator<Item = i32>>) -> Result<Holder, String> {",
+        "    todo!()",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs[0]!.fields.map((f) => [f.name, f.genericBase, f.genericArgs])).to.deep.equal([
+      ["items", "Vec", ["i32"]],
+      ["table", "std::collections::HashMap", ["String", "Vec < u8 >"]],
+      ["plain", undefined, undefined],
+    ]);
+    const spread = root.functions[0]!;
+    expect([spread.params[0]!.genericBase, spread.params[0]!.genericArgs]).to.deep.equal([
+      "Box",
+      ["dyn Iterator < Item = i32 >"],
+    ]);
+    expect([spread.returnGenericBase, spread.returnGenericArgs]).to.deep.equal(["Result", ["Holder", "String"]]);
+  });
+
+  it("keeps struct fields and enum variants in source order", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Record {",
+        "    pub zeta: u8,",
+        "    pub alpha: u8,",
+        "    pub mid: u8,",
+        "}",
+        "",
+        "pub enum Level {",
+        "    Warn = 2,",
+        "    Debug = 0,",
+        "    Error = 3,",
+        "}",
+        "",
+        "pub enum Shape {",
+        "    Rect { w: f64, h: f64 },",
+        "    Circle(f64),",
+        "    Line(f64, f64),",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs[0]!.fields.map((f) => f.name)).to.deep.equal(["zeta", "alpha", "mid"]);
+    const [level, shape] = root.enums;
+    expect(level!.variants.map((v) => v.name)).to.deep.equal(["Warn", "Debug", "Error"]);
+    expect(shape!.variants.map((v) => [v.name, v.fields.map((f) => f.name)])).to.deep.equal([
+      ["Rect", ["w", "h"]],
+      ["Circle", ["_0"]],
+      ["Line", ["_0", "_1"]],
+    ]);
+  });
+
+  it("attaches methods of impls on reference targets to the underlying type", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Bag {",
+        "    pub items: Vec<u32>,",
+        "}",
+        "",
+        "pub trait Describe {",
+        "    fn describe(&self) -> String;",
+        "}",
+        "",
+        "impl Describe for &Bag {",
+        "    fn describe(&self) -> String {",
+        "        String::new()",
+        "    }",
+        "}",
+        "",
+        "impl Iterator for &mut Bag {",
+        "    type Item = u32;",
+        "    fn next(&mut self) -> Option<u32> {",
+        "        None",
+        "    }",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.pendingMethods.map((p) => [p.target, p.targetReference, p.methods.map((m) => m.name)])).to.deep.equal([
+      ["Bag", "&", ["describe"]],
+      ["Bag", "&mut", ["next"]],
+    ]);
+    expect(root.issues.filter((i) => i.kind === "impl")).to.deep.equal([]);
+  });
+
+  it("marks async functions, impl methods, and trait methods", () => {
+    const root = extract([fixtureManifest("async")]).modules[0]!;
+    const summary = (fns: readonly ExtractedFunction[]): unknown => fns.map((f) => [f.name, f.isAsync, f.returnType]);
+    expect(summary(root.traits[0]!.methods)).to.deep.equal([
+      ["fetch", true, "Data"],
+      ["ping", true, "()"],
+      ["name", false, "String"],
+    ]);
+    expect(summary(root.functions)).to.deep.equal([["fetch_all", true, "Vec < Data >"]]);
+    expect(root.pendingMethods.map((p) => summary(p.methods))).to.deep.equal([
+      [["connect", true, "Result < Client , String >"]],
+      [
+        ["fetch", true, "Data"],
+        ["ping", true, "()"],
+        ["name", false, "String"],
+      ],
+    ]);
+  });
+
+  it("canonicalizes local type paths with --resolve-paths", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod inner;",
+        "pub mod api;",
+        "",
+        "pub use inner::Thing as Alias;",
+        "",
+        "pub struct Holder<T> {",
+        "    pub direct: inner::Thing,",
+        "    pub aliased: Vec<Alias>,",
+        "    pub generic: Option<T>,",
+        "    pub foreign: std::collections::HashMap<String, u8>,",
+        "}",
+        "",
+      ].join("\n"),
+      "src/inner.rs": "pub struct Thing;\n\npub fn local(value: Thing) -> self::Thing {\n    value\n}\n",
+      "src/api.rs": [
+        "use crate::inner::Thing;",
+        "",
+        "pub fn make(value: Thing) -> Result<super::Alias, String> {",
+        "    Ok(value)",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const types = (output: ExtractedOutput): unknown =>
+      output.modules.map((m) => [
+        m.parts.join("::"),
+        ...m.structs.flatMap((st) => st.fields.map((f) => f.type)),
+        ...m.functions.flatMap((f) => [...f.params.map((p) => p.type), f.returnType]),
+      ]);
+    expect(types(extract([manifest]))).to.deep.equal([
+      ["", "inner :: Thing", "Vec < Alias >", "Option < T >", "std :: collections :: HashMap < String , u8 >"],
+      ["api", "Thing", "Result < super :: Alias , String >"],
+      ["inner", "Thing", "self :: Thing"],
+    ]);
+    const resolved = extract(["--resolve-paths", manifest]);
+    expect(types(resolved)).to.deep.equal([
+      [
+        "",
+        "crate :: inner :: Thing",
+        "Vec < crate :: inner :: Thing >",
+        "Option < T >",
+        "std :: collections :: HashMap < String , u8 >",
+      ],
+      ["api", "crate :: inner :: Thing", "Result < crate :: inner :: Thing , String >"],
+      ["inner", "crate :: inner :: Thing", "crate :: inner :: Thing"],
+    ]);
+    const aliased = resolved.modules[0]!.structs[0]!.fields[1]!;
+    expect([aliased.genericBase, aliased.genericArgs]).to.deep.equal(["Vec", ["crate :: inner :: Thing"]]);
+  });
+
+  it("emits structured types with Box, Rc, and Arc unwrapped as wrappers", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "use std::rc::Rc;",
+        "",
+        "pub struct Shared {",
+        "    pub plain: Box<u32>,",
+        "    pub nested: std::sync::Arc<Vec<String>>,",
+        "    pub stacked: Rc<Box<&'static str>>,",
+        "    pub custom_alloc: Box<u8, MyAlloc>,",
+        "}",
+        "",
+        "pub fn unit() {}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs[0]!.fields.map((f) => f.typeRepr)).to.deep.equal([
+      { kind: "path", path: ["u32"], wrappers: ["Box"] },
+      { kind: "path", path: ["Vec"], args: [{ kind: "path", path: ["String"] }], wrappers: ["Arc"] },
+      {
+        kind: "reference",
+        mutable: false,
+        lifetime: "'static",
+        inner: { kind: "path", path: ["str"] },
+        wrappers: ["Rc", "Box"],
+      },
+      {
+        kind: "path",
+        path: ["Box"],
+        args: [
+          { kind: "path", path: ["u8"] },
+          { kind: "path", path: ["MyAlloc"] },
+        ],
+      },
+    ]);
+    expect(root.functions[0]!.returnTypeRepr).to.deep.equal({ kind: "tuple", elements: [] });
+  });
+
+  it("unwraps Cow to its owned form and records the wrapper", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "use std::borrow::Cow;",
+        "",
+        "pub fn label<'a>(raw: Cow<'a, str>) -> std::borrow::Cow<'a, [u8]> {",
+        "    todo!()",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const label = extract([manifest]).modules[0]!.functions[0]!;
+    expect(label.params[0]!.typeRepr).to.deep.equal({ kind: "path", path: ["str"], wrappers: ["Cow"] });
+    expect(label.returnTypeRepr).to.deep.equal({
+      kind: "slice",
+      inner: { kind: "path", path: ["u8"] },
+      wrappers: ["Cow"],
+    });
+  });
+
+  it("streams one JSON line per item with --json-lines-per-item", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod shapes;",
+        "pub const ANSWER: u32 = 42;",
+        "pub fn answer() -> u32 { ANSWER }",
+        "",
+      ].join("\n"),
+      "src/shapes.rs": [
+        "pub struct Point { pub x: i32 }",
+        "impl Point {",
+        "    pub fn origin() -> Self { Point { x: 0 } }",
+        "}",
+        "pub use self::Point as Dot;",
+        "",
+      ].join("\n"),
+    });
+    expect(extract([manifest]).crate).to.deep.equal({ name: "extractor_case", package: "extractor-case" });
+
+    const result = runExtractor(["--json-lines-per-item", manifest]);
+    expect(result.status, result.stderr).to.equal(0);
+    const records = result.stdout
+      .trimEnd()
+      .split("\n")
+      .map((line) => JSON.parse(line) as Record<string, unknown>);
+    expect(records.map((r) => [r["kind"], r["path"]])).to.deep.equal([
+      ["const", "extractor_case::ANSWER"],
+      ["function", "extractor_case::answer"],
+      ["struct", "extractor_case::shapes::Point"],
+      ["reexport", "extractor_case::shapes::Dot"],
+      ["method", "extractor_case::shapes::Point::origin"],
+    ]);
+    const method = records[4]!;
+    expect(method["schema"]).to.equal(1);
+    expect(method["crate"]).to.equal("extractor_case");
+    expect(method["module"]).to.deep.equal(["shapes"]);
+    expect(method["file"]).to.equal("src/shapes.rs");
+    expect(method["target"]).to.equal("Point");
+    expect((method["item"] as Record<string, unknown>)["name"]).to.equal("origin");
+  });
+
+  it("records impl, target, and trait generics on pending methods", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait Service<T> { fn call(&self, v: T) -> T; }",
+        "pub struct Wrapper<T>(pub T);",
+        "impl<T> Service<T> for Wrapper<T> { fn call(&self, v: T) -> T { v } }",
+        "impl Service<i32> for Wrapper<i32> { fn call(&self, v: i32) -> i32 { v } }",
+        "impl<T> Wrapper<T> { pub fn inner(&self) -> &T { &self.0 } }",
+        "",
+      ].join("\n"),
+    });
+    const pending = extract([manifest]).modules[0]!.pendingMethods.map((p) => {
+      const { methods, ...rest } = p;
+      expect(methods).to.have.lengthOf(1);
+      return rest;
+    });
+    expect(pending).to.deep.equal([
+      {
+        target: "Wrapper",
+        targetGenericArgs: ["T"],
+        typeParams: ["T"],
+        generics: [{ kind: "type", name: "T" }],
+        totalGenericArity: 1,
+        trait: "Service < T >",
+        traitGenericBase: "Service",
+        traitGenericArgs: ["T"],
+      },
+      {
+        target: "Wrapper",
+        targetGenericArgs: ["i32"],
+        typeParams: [],
+        trait: "Service < i32 >",
+        traitGenericBase: "Service",
+        traitGenericArgs: ["i32"],
+      },
+      {
+        target: "Wrapper",
+        targetGenericArgs: ["T"],
+        typeParams: ["T"],
+        generics: [{ kind: "type", name: "T" }],
+        totalGenericArity: 1,
+      },
+    ]);
+  });
+
+  it("evaluates #[cfg] gates against default features, --feature, and --cfg", () => {
+    const crateManifest = writeCrate({
+      "src/lib.rs": [
+        '#[cfg(feature = "std")]',
+        "pub fn with_std() {}",
+        '#[cfg(feature = "extra")]',
+        "pub mod extra;",
+        '#[cfg(not(feature = "extra"))]',
+        "pub fn without_extra() {}",
+        "#[cfg(unix)]",
+        "pub fn on_unix() {}",
+        '#[cfg(all(unix, feature = "extra"))]',
+        "pub const UNIX_EXTRA: u32 = 1;",
+        "",
+      ].join("\n"),
+      "src/extra.rs": "pub fn bonus() {}\n",
+    });
+    appendFileSync(crateManifest, '\n[features]\ndefault = ["std"]\nstd = []\nextra = []\n');
+
+    const byDefault = extract([crateManifest]).modules;
+    expect(byDefault.map((m) => m.parts)).to.deep.equal([[]]);
+    expect(byDefault[0]!.functions.map((f) => f.name)).to.deep.equal(["with_std", "without_extra", "on_unix"]);
+    expect(byDefault[0]!.consts).to.deep.equal([]);
+    expect(byDefault[0]!.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["skip", "disabled_by_cfg", "extra"],
+      ["cfg", "unevaluated_cfg", "on_unix"],
+      ["skip", "disabled_by_cfg", "UNIX_EXTRA"],
+    ]);
+
+    const configured = extract(["--no-default-features", "--feature", "extra", "--cfg", "unix", crateManifest]).modules;
+    expect(configured.map((m) => m.parts)).to.deep.equal([[], ["extra"]]);
+    expect(configured[0]!.functions.map((f) => f.name)).to.deep.equal(["on_unix"]);
+    expect(configured[0]!.consts.map((c) => c.name)).to.deep.equal(["UNIX_EXTRA"]);
+    expect(configured[1]!.functions.map((f) => f.name)).to.deep.equal(["bonus"]);
+    expect(configured[0]!.issues.map((i) => i.snippet)).to.deep.equal(["with_std", "without_extra"]);
+  });
+
+  it("marks structs as named, tuple, or unit", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Named { pub x: i32 }",
+        "pub struct Hidden { x: i32 }",
+        "pub struct Braces {}",
+        "pub struct Pair(pub i32, pub i32);",
+        "pub struct Marker;",
+        "",
+      ].join("\n"),
+    });
+    const structs = extract([manifest]).modules[0]!.structs;
+    expect(structs.map((s) => [s.name, s.kind, s.fields.length])).to.deep.equal([
+      ["Named", "named", 1],
+      ["Hidden", "named", 0],
+      ["Braces", "named", 0],
+      ["Pair", "tuple", 0],
+      ["Marker", "unit", 0],
+    ]);
+  });
+
+  it("collects #[doc(alias)] values on items and fields", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        '#[doc(alias = "vec2")]',
+        '#[doc(alias("point", "coord"))]',
+        'pub struct Point { #[doc(alias = "horizontal")] pub x: i32 }',
+        "/// Unrelated docs are ignored.",
+        '#[doc(alias = "len")]',
+        "pub fn length() {}",
+        "pub fn plain() {}",
+        'pub enum Shape { A(#[doc(alias = "x")] u32), B { #[doc(alias = "y")] f: u32 } }',
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    const point = root.structs[0]!;
+    expect(point.docAliases).to.deep.equal(["vec2", "point", "coord"]);
+    expect(point.fields[0]!.docAliases).to.deep.equal(["horizontal"]);
+    expect(root.enums[0]!.variants.map((v) => v.fields.map((f) => f.docAliases))).to.deep.equal([[["x"]], [["y"]]]);
+    expect(root.functions.map((f) => f.docAliases)).to.deep.equal([["len"], undefined]);
+  });
+
+  it("skips module files over --max-file-bytes without parsing them", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod big;\npub fn small() {}\n",
+      "src/big.rs": `pub fn big() {}\n${"// padding\n".repeat(20)}`,
+    });
+    const modules = extract(["--max-file-bytes", "100", manifest]).modules;
+    expect(modules.map((m) => m.parts)).to.deep.equal([[], ["big"]]);
+    expect(modules[0]!.functions.map((f) => f.name)).to.deep.equal(["small"]);
+    expect(modules[1]!.functions).to.deep.equal([]);
+    expect(modules[1]!.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["parse", "file_too_large", "src/big.rs"],
+    ]);
+
+    const invalid = runExtractor(["--max-file-bytes", "0", manifest]);
+    expect(invalid.status).to.not.equal(0);
+    expect(invalid.stderr).to.contain("--max-file-bytes");
+  });
+
+  it("records associated consts and types of impls next to methods", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Grid;",
+        "impl Grid {",
+        "    pub const MAX: u32 = 8;",
+        "    pub const ORIGIN: Self = Grid;",
+        "    pub const CORNERS: [Self; 2] = [Grid, Grid];",
+        "    const HIDDEN: u32 = 1;",
+        "    pub fn new() -> Self { Grid }",
+        "}",
+        "impl std::ops::Neg for Grid {",
+        "    type Output = Self;",
+        "    fn neg(self) -> Grid { Grid }",
+        "}",
+        "macro_rules! nothing { () => {} }",
+        "pub struct Empty;",
+        "impl Empty {",
+        "    pub const ZERO: u8 = 0;",
+        "    nothing!();",
+        "}",
+        "pub struct Wrapper<T> { pub value: T }",
+        "impl<T> Wrapper<T> { pub const NONE: Option<Self> = None; }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(
+      root.pendingMethods.map((p) => ({
+        target: p.target,
+        methods: p.methods.map((m) => m.name),
+        consts: p.consts?.map((c) => [c.name, c.type]),
+        types: p.types?.map((t) => [t.name, t.type]),
+      }))
+    ).to.deep.equal([
+      { target: "Empty", methods: [], consts: [["ZERO", "u8"]], types: undefined },
+      {
+        target: "Grid",
+        methods: ["new"],
+        consts: [
+          ["MAX", "u32"],
+          ["ORIGIN", "Grid"],
+          ["CORNERS", "[Grid ; 2]"],
+        ],
+        types: undefined,
+      },
+      { target: "Grid", methods: ["neg"], consts: undefined, types: [["Output", "Grid"]] },
+      { target: "Wrapper", methods: [], consts: [["NONE", "Option < Wrapper < T > >"]], types: undefined },
+    ]);
+    expect(root.issues.map((i) => [i.kind, i.snippet])).to.deep.equal([["impl", "nothing ! () ;"]]);
+  });
+
+  it("infers read-only properties from getters with --infer-properties", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod extra;",
+        "pub struct Account { id: u64, pub owner: String, balance: i64 }",
+        "impl Account {",
+        "    pub fn id(&self) -> u64 { self.id }",
+        "    pub fn get_balance(&self) -> i64 { self.balance }",
+        "    pub fn owner(&self) -> &str { &self.owner }",
+        "    pub fn set_id(&mut self, id: u64) { self.id = id; }",
+        "    pub fn get(&self) -> i64 { 0 }",
+        "    pub fn scaled(&self, by: i64) -> i64 { self.balance * by }",
+        "}",
+        "",
+      ].join("\n"),
+      "src/extra.rs": "impl crate::Account { pub fn get_label(&self) -> String { String::new() } }\n",
+    });
+    expect(extract([manifest]).modules[0]!.structs[0]!.derivedProperties).to.equal(undefined);
+    const account = extract(["--infer-properties", manifest]).modules[0]!.structs[0]!;
+    expect(account.derivedProperties).to.deep.equal([
+      { name: "balance", getter: "get_balance", type: "i64", typeRepr: { kind: "path", path: ["i64"] } },
+      { name: "id", getter: "id", type: "u64", typeRepr: { kind: "path", path: ["u64"] } },
+      { name: "label", getter: "get_label", type: "String", typeRepr: { kind: "path", path: ["String"] } },
+    ]);
+  });
+
+  it("links trait-impl methods to the trait method they implement", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod other;",
+        "pub trait Named { fn label(&self) -> String; fn id(&self) -> u32 { 0 } }",
+        "pub struct Wrapper<T>(pub T);",
+        "impl Named for Wrapper<i32> { fn label(&self) -> String { String::new() } }",
+        "impl std::fmt::Display for Wrapper<u8> {",
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) }",
+        "}",
+        "impl<T> Wrapper<T> { pub fn inner(&self) -> &T { &self.0 } }",
+        "",
+      ].join("\n"),
+      "src/other.rs": [
+        "use crate::{Named, Wrapper};",
+        "impl Named for Wrapper<u64> { fn label(&self) -> String { String::new() } fn extra(&self) {} }",
+        "",
+      ].join("\n"),
+    });
+    const [root, other] = extract([manifest]).modules;
+    expect(root!.pendingMethods.map((p) => p.methods.map((m) => [m.name, m.implements]))).to.deep.equal([
+      [["label", { trait: "Named", method: "label" }]],
+      [["fmt", { trait: "std::fmt::Display", method: "fmt" }]],
+      [["inner", undefined]],
+    ]);
+    expect(other!.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["impl", "method_not_in_trait", "Wrapper::extra"],
+    ]);
+  });
+
+  it("checks trait-impl methods only against traits the path resolves to in the crate", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod other;",
+        "pub trait Display { fn show(&self) -> String; }",
+        "pub struct Wrapper;",
+        "impl std::fmt::Display for Wrapper {",
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) }",
+        "}",
+        "",
+      ].join("\n"),
+      "src/other.rs": [
+        "pub struct Other;",
+        "impl crate::Display for Other { fn show(&self) -> String { String::new() } fn extra(&self) {} }",
+        "impl fmt::Display for Other { fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { Ok(()) } }",
+        "",
+      ].join("\n"),
+    });
+    const [root, other] = extract([manifest]).modules;
+    expect(root!.issues).to.deep.equal([]);
+    expect(other!.issues.map((i) => [i.kind, i.code, i.snippet])).to.deep.equal([
+      ["impl", "method_not_in_trait", "Other::extra"],
+    ]);
+  });
+
+  it("pipes the output through --transform and validates the result", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod internal;\npub fn keep() {}\n",
+      "src/internal.rs": "pub fn hidden() {}\n",
+      "drop-internal.mjs": [
+        'let text = "";',
+        'process.stdin.on("data", (chunk) => (text += chunk));',
+        'process.stdin.on("end", () => {',
+        "  const doc = JSON.parse(text);",
+        '  doc.modules = doc.modules.filter((m) => m.parts[0] !== "internal");',
+        "  process.stdout.write(JSON.stringify(doc));",
+        "});",
+        "",
+      ].join("\n"),
+    });
+    const transform = join(dirname(manifest), "drop-internal.mjs");
+    const modules = extract(["--transform", `node ${transform}`, manifest]).modules;
+    expect(modules.map((m) => m.parts)).to.deep.equal([[]]);
+    expect(modules[0]!.functions.map((f) => f.name)).to.deep.equal(["keep"]);
+
+    const failing = runExtractor(["--transform", "exit 3", manifest]);
+    expect(failing.status).to.not.equal(0);
+    expect(failing.stderr).to.contain("Transform program 'exit 3' failed");
+
+    const broken = runExtractor(["--transform", `echo '{"schema":1,"crate":{"name":"x"},"modules":[{}]}'`, manifest]);
+    expect(broken.status).to.not.equal(0);
+    expect(broken.stderr).to.contain("modules[0].file is missing");
+  });
+
+  it("marks contiguous trailing Option params as optional", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub fn connect(host: String, port: Option<u16>, timeout: std::option::Option<u32>) {}",
+        "pub fn middle(a: Option<u8>, b: u8, c: Option<u8>) {}",
+        "pub struct Client;",
+        "impl Client { pub fn retry(&self, times: Option<u8>) {} }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    const optional = (fn: ExtractedFunction) => fn.params.map((p) => [p.name, p.optional ?? false]);
+    expect(root.functions.map(optional)).to.deep.equal([
+      [
+        ["host", false],
+        ["port", true],
+        ["timeout", true],
+      ],
+      [
+        ["a", false],
+        ["b", false],
+        ["c", true],
+      ],
+    ]);
+    expect(optional(root.pendingMethods[0]!.methods[0]!)).to.deep.equal([
+      ["&self", false],
+      ["times", true],
+    ]);
+  });
+
+  it("records associated type bounds on traits", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait IteratorLike { type Item; fn next(&mut self) -> Option<Self::Item>; }",
+        "pub trait Mapper<T>: IteratorLike { fn map_one(&self, value: T) -> Option<T>; }",
+        "pub trait Convert { type Out: Into<String> + Send; type Raw: 'static; }",
+        "",
+      ].join("\n"),
+    });
+    const traits = extract([manifest]).modules[0]!.traits;
+    expect(traits.map((t) => [t.name, t.typeParams, t.assocTypes.map((a) => [a.name, a.bounds])])).to.deep.equal([
+      ["IteratorLike", ["Item"], [["Item", []]]],
+      ["Mapper", ["T"], []],
+      [
+        "Convert",
+        ["Out", "Raw"],
+        [
+          ["Out", ["Into < String >", "Send"]],
+          ["Raw", ["'static"]],
+        ],
+      ],
+    ]);
+  });
+
+  it("stamps a deterministic SHA-256 contentHash of the document", () => {
+    const manifest = writeCrate({ "src/lib.rs": "pub fn one() -> u32 { 1 }\n" });
+    const first = runExtractor(["--print-hash", manifest]);
+    expect(first.status, first.stderr).to.equal(0);
+    const { contentHash, ...rest } = JSON.parse(first.stdout) as ExtractedOutput;
+    expect(contentHash).to.match(/^[0-9a-f]{64}$/);
+    expect(createHash("sha256").update(JSON.stringify(rest)).digest("hex")).to.equal(contentHash);
+    expect(first.stderr).to.contain(`contentHash ${contentHash}`);
+    expect(extract([manifest]).contentHash).to.equal(contentHash);
+
+    writeFileSync(join(dirname(manifest), "src", "lib.rs"), "pub fn two() -> u32 { 2 }\n");
+    expect(extract([manifest]).contentHash).to.not.equal(contentHash);
+  });
+
+  it("resolves self:: and super:: re-exports to their defining module", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod outer;",
+        "pub use self::outer::Widget;",
+        "pub use self::outer::inner::Gadget as RootGadget;",
+        "pub use crate::outer::Again;",
+        "pub use self::outer::Missing;",
+        "pub use std::fmt::Debug;",
+        "pub fn root_fn() {}",
+        "",
+      ].join("\n"),
+      "src/outer.rs": [
+        "pub mod inner;",
+        "pub use self::inner::Widget;",
+        "pub use inner::Gadget as Again;",
+        "",
+      ].join("\n"),
+      "src/outer/inner.rs": [
+        "pub struct Widget;",
+        "pub fn gadget_helper() {}",
+        "pub use super::super::root_fn as Gadget;",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules.find((m) => m.parts.length === 0)!;
+    expect(root.reexports.map((r) => [r.name, r.sourceModule, r.sourceName])).to.deep.equal([
+      ["Again", [], "root_fn"],
+      ["Debug", undefined, undefined],
+      ["Missing", undefined, undefined],
+      ["RootGadget", [], "root_fn"],
+      ["Widget", ["outer", "inner"], "Widget"],
+    ]);
+  });
+
+  it("prints per-phase and per-file timings to stderr with --stats", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod inner;\npub fn one() -> u32 { 1 }\n",
+      "src/inner.rs": "pub struct Thing;\n",
+    });
+    const plain = runExtractor([manifest]);
+    const timed = runExtractor(["--stats", manifest]);
+    expect(timed.status, timed.stderr).to.equal(0);
+    expect(timed.stdout).to.equal(plain.stdout);
+    const lines = timed.stderr.split("\n").filter((line) => line.includes("stats:"));
+    expect(lines[0]).to.match(
+      /stats: read [\d.]+ms, parse [\d.]+ms, collect [\d.]+ms, post-process [\d.]+ms, serialize [\d.]+ms, write [\d.]+ms, total [\d.]+ms$/
+    );
+    expect(lines.slice(1).map((line) => line.replace(/[\d.]+ms/g, "Nms"))).to.deep.equal([
+      "[tsubabindgen-extractor] stats: src/lib.rs (41 bytes): read Nms, parse Nms",
+      "[tsubabindgen-extractor] stats: src/inner.rs (18 bytes): read Nms, parse Nms",
+    ]);
+  });
+
+  it("models bare Self as a distinct self node in trait and impl signatures", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait CloneBox {",
+        "    type Item;",
+        "    fn clone_box(&self) -> Box<Self>;",
+        "    fn with_limit(self, limit: u32) -> Self where Self: Sized;",
+        "    fn first(&self) -> Option<Self::Item>;",
+        "}",
+        "pub struct Builder;",
+        "impl Builder { pub fn new() -> Self { Builder } }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.traits[0]!.methods.map((m) => [m.name, m.returnType, m.returnTypeRepr])).to.deep.equal([
+      ["clone_box", "Box < Self >", { kind: "self", wrappers: ["Box"] }],
+      ["with_limit", "Self", { kind: "self" }],
+      ["first", "Option < Self :: Item >", { kind: "path", path: ["Option"], args: [{ kind: "assoc_type", name: "Item" }] }],
+    ]);
+    expect(root.pendingMethods[0]!.methods[0]!.returnTypeRepr).to.deep.equal({ kind: "self" });
+  });
+
+  it("models Self associated type projections as assoc_type nodes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait Lend {",
+        "    type Output;",
+        "    type Item<'a> where Self: 'a;",
+        "    fn run(&self) -> Self::Output;",
+        "    fn lend<'a>(&'a self) -> Self::Item<'a>;",
+        "    fn qualified(&self) -> <Self as Lend>::Output;",
+        "    fn other(&self) -> <Vec<u8> as IntoIterator>::Item;",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const methods = extract([manifest]).modules[0]!.traits[0]!.methods;
+    expect(methods.map((m) => [m.name, m.returnTypeRepr])).to.deep.equal([
+      ["run", { kind: "assoc_type", name: "Output" }],
+      ["lend", { kind: "assoc_type", name: "Item", lifetimes: ["'a"] }],
+      ["qualified", { kind: "assoc_type", name: "Output", trait: { path: ["Lend"] } }],
+      ["other", { kind: "other", text: "< Vec < u8 > as IntoIterator > :: Item" }],
+    ]);
+  });
+
+  it("attaches impls on type aliases to the aliased type with --resolve-paths", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod inner;",
+        "pub struct Wrapper<T>(T);",
+        "pub type Id = Wrapper<inner::Local>;",
+        "pub type Generic<T> = Wrapper<T>;",
+        "impl Id { pub fn get(&self) -> u32 { 0 } }",
+        "impl<T> Generic<T> { pub fn generic(&self) {} }",
+        "",
+      ].join("\n"),
+      "src/inner.rs": ["pub struct Local;", "pub type Handle = super::Id;", "impl Handle { pub fn handle(&self) {} }", ""].join("\n"),
+    });
+    const targets = (output: ExtractedOutput): unknown =>
+      output.modules.flatMap((m) => m.pendingMethods.map((p) => [m.parts, p.target, p.targetGenericArgs ?? []]));
+
+    expect(targets(extract([manifest]))).to.deep.equal([
+      [[], "Generic", ["T"]],
+      [[], "Id", []],
+      [["inner"], "Handle", []],
+    ]);
+
+    const resolved = extract(["--resolve-paths", manifest]);
+    expect(targets(resolved)).to.deep.equal([
+      [[], "Generic", ["T"]],
+      [[], "Wrapper", ["crate :: inner :: Local"]],
+      [["inner"], "Wrapper", ["crate :: inner :: Local"]],
+    ]);
+    const issues = resolved.modules[0]!.issues.filter((i) => i.code === "unresolved_alias_target");
+    expect(issues.map((i) => [i.kind, i.snippet])).to.deep.equal([["impl", "Generic"]]);
+  });
+
+  it("flags functions that declare type, const, or lifetime generics with isGeneric", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub fn plain(x: u32) -> u32 { x }",
+        "pub fn typed<T: Clone>(x: T) -> T { x }",
+        "pub fn sized<const N: usize>() -> [u8; N] { [0; N] }",
+        "pub fn borrowed<'a>(x: &'a str) -> &'a str { x }",
+        "pub trait Visit { fn visit<V>(&self, v: V); fn name(&self) -> String; }",
+        "pub struct Holder<T>(T);",
+        "impl<T> Holder<T> { pub fn get(&self) -> &T { &self.0 } pub fn map<U>(self, u: U) -> U { u } }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    const flags = (fns: readonly ExtractedFunction[]): unknown => fns.map((f) => [f.name, f.isGeneric, f.typeParams]);
+    expect(flags(root.functions)).to.deep.equal([
+      ["plain", false, []],
+      ["typed", true, ["T"]],
+      ["sized", true, []],
+      ["borrowed", true, []],
+    ]);
+    expect(flags(root.traits[0]!.methods)).to.deep.equal([
+      ["visit", true, ["V"]],
+      ["name", false, []],
+    ]);
+    expect(flags(root.pendingMethods[0]!.methods)).to.deep.equal([
+      ["get", false, []],
+      ["map", true, ["U"]],
+    ]);
+  });
+
+  it("keeps associated-type equality bindings in captured where bounds", () => {
+    const root = extract([fixtureManifest("where-bounds")]).modules[0]!;
+    const bounds = (fns: readonly ExtractedFunction[]): unknown =>
+      fns.map((f) => [f.name, (f.genericBounds ?? []).map((b) => `${b.type}: ${b.bounds.join(" + ")}`)]);
+    expect(bounds(root.functions)).to.deep.equal([
+      ["sum_all", ["I: Iterator < Item = i32 >"]],
+      [
+        "collect_pairs",
+        ["K: Clone", "K: std :: hash :: Hash + Eq", "I: IntoIterator < Item = (K , String) >", "I :: IntoIter: ExactSizeIterator"],
+      ],
+      ["longest", ["'b: 'a"]],
+      ["apply", ["F: for < 'x > Fn (& 'x str) -> String"]],
+    ]);
+    expect(bounds(root.traits[0]!.methods)).to.deep.equal([["read_into", ["W: Extend < u8 >"]]]);
+    expect(bounds(root.pendingMethods[0]!.methods)).to.deep.equal([["extend_rows", ["I: IntoIterator < Item = String >"]]]);
+    expect(
+      root.pendingMethods.map((p) => [p.target, p.trait, (p.genericBounds ?? []).map((b) => `${b.type}: ${b.bounds.join(" + ")}`)])
+    ).to.deep.equal([
+      ["Table", undefined, []],
+      ["Wrapper", "Named", ["T: Clone", "T: std :: fmt :: Debug"]],
+    ]);
+  });
+
+  it("lists the module files extraction would parse with --list-files", () => {
+    const manifest = writeCrate({
+      "Cargo.toml": '[package]\nname = "listed"\nversion = "0.1.0"\n\n[features]\nextra = []\n',
+      "src/lib.rs": [
+        "pub mod a;",
+        "pub mod generated;",
+        '#[cfg(feature = "extra")]',
+        "pub mod extra;",
+        "pub mod inline { pub mod leaf; }",
+        "",
+      ].join("\n"),
+      "src/a.rs": "pub mod b;\n",
+      "src/a/b.rs": "pub fn b() {}\n",
+      "src/generated.rs": "// tsuba:skip\npub fn generated() {}\n",
+      "src/extra.rs": "pub fn extra() {}\n",
+      "src/inline/leaf.rs": "pub fn leaf() {}\n",
+    });
+    const listed = (args: readonly string[]): string[] => {
+      const result = runExtractor([...args, manifest]);
+      expect(result.status, result.stderr).to.equal(0);
+      return result.stdout.split("\n").filter((line) => line.length > 0);
+    };
+    expect(listed(["--list-files"])).to.deep.equal(["src/lib.rs", "src/a.rs", "src/a/b.rs", "src/inline/leaf.rs"]);
+    expect(listed(["--list-files", "--feature", "extra"])).to.deep.equal([
+      "src/lib.rs",
+      "src/a.rs",
+      "src/a/b.rs",
+      "src/extra.rs",
+      "src/inline/leaf.rs",
+    ]);
+    expect(listed(["--list-files", "--subtree", "a"])).to.deep.equal(["src/a.rs", "src/a/b.rs"]);
+    expect(listed(["--list-files", "--subtree", "inline"])).to.deep.equal(["src/lib.rs", "src/inline/leaf.rs"]);
+  });
+
+  it("maps public names to their defining modules with --emit-index", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod shapes;",
+        "pub mod util;",
+        "pub use shapes::Circle;",
+        "pub const VERSION: u32 = 1;",
+        "pub struct Config;",
+        "",
+      ].join("\n"),
+      "src/shapes.rs": "pub struct Circle;\npub trait Area {}\npub fn parse() {}\n",
+      "src/util.rs": "pub enum Level { Low }\npub fn parse() {}\nfn hidden() {}\n",
+    });
+    expect(extract([manifest]).index).to.equal(undefined);
+    const index = extract(["--emit-index", manifest]).index!;
+    const locations = Object.fromEntries(
+      Object.entries(index).map(([name, entries]) => [name, entries.map((e) => [e.module.join("::"), e.kind, e.sourceFile, e.lineStart])])
+    );
+    expect(locations).to.deep.equal({
+      Area: [["shapes", "trait", "src/shapes.rs", 2]],
+      Circle: [["shapes", "struct", "src/shapes.rs", 1]],
+      Config: [["", "struct", "src/lib.rs", 5]],
+      Level: [["util", "enum", "src/util.rs", 1]],
+      VERSION: [["", "const", "src/lib.rs", 4]],
+      parse: [
+        ["shapes", "function", "src/shapes.rs", 3],
+        ["util", "function", "src/util.rs", 2],
+      ],
+    });
+    const rejected = runExtractor(["--emit-index", "--list-files", manifest]);
+    expect(rejected.status).to.not.equal(0);
+    expect(rejected.stderr).to.contain("Option --emit-index only applies to the modules document output.");
+  });
+
+  it("models argument-position impl Trait in trait methods without disturbing named generics", () => {
+    const root = extract([fixtureManifest("impl-trait-args")]).modules[0]!;
+    const sink = root.traits[0]!;
+    expect([sink.name, sink.typeParams, sink.genericDefaults]).to.deep.equal(["Sink", ["T"], [{ type: "T", default: "i32" }]]);
+    const flags = (fns: readonly ExtractedFunction[]): unknown => fns.map((f) => [f.name, f.isGeneric, f.typeParams]);
+    expect(flags(sink.methods)).to.deep.equal([
+      ["process", true, []],
+      ["extend_where", true, ["F"]],
+      ["gather", true, ["C"]],
+      ["len", false, []],
+    ]);
+    const items = sink.methods[0]!.params[1]!;
+    expect(items.type).to.equal("impl IntoIterator < Item = T >");
+    expect(items.typeRepr).to.deep.equal({
+      kind: "impl",
+      bounds: [{ path: ["IntoIterator"], bindings: [{ name: "Item", type: { kind: "path", path: ["T"] } }] }],
+    });
+    expect(sink.methods[1]!.params[1]!.typeRepr).to.deep.equal({
+      kind: "impl",
+      bounds: [
+        { path: ["Iterator"], bindings: [{ name: "Item", type: { kind: "path", path: ["T"] } }] },
+        { path: ["Clone"] },
+      ],
+    });
+    expect(sink.methods[1]!.genericBounds!.map((b) => [b.type, b.bounds, b.paths!.map((p) => p.path)])).to.deep.equal([
+      ["F", ["Fn (& T) -> bool"], [["Fn"]]],
+    ]);
+    expect(root.structs[0]!.genericDefaults).to.deep.equal([{ type: "T", default: "u8" }]);
+    expect(flags(root.pendingMethods[0]!.methods)).to.deep.equal([
+      ["append", true, []],
+      ["iter", false, []],
+    ]);
+    expect(flags(root.functions)).to.deep.equal([
+      ["total", true, []],
+      ["describe", true, []],
+      ["evens", false, []],
+    ]);
+  });
+
+  it("applies [package.metadata.tsuba] defaults that CLI flags override", () => {
+    const manifest = writeCrate({
+      "Cargo.toml": [
+        "[package]",
+        'name = "pinned"',
+        'version = "0.1.0"',
+        "",
+        "[package.metadata.tsuba]",
+        "schema = 1",
+        'root-module-name = "pinned"',
+        "resolve-paths = true",
+        'skip-markers = ["@generated"]',
+        "include-private = true",
+        "",
+      ].join("\n"),
+      "src/lib.rs": "pub mod inner;\npub mod generated;\npub fn make() -> inner::Thing { inner::Thing }\n",
+      "src/inner.rs": "pub struct Thing;\n",
+      "src/generated.rs": "// @generated\npub fn generated() {}\n",
+    });
+    const pinned = runExtractor([manifest]);
+    expect(pinned.status, pinned.stderr).to.equal(0);
+    expect(pinned.stderr).to.contain("warning: ignoring unknown key package.metadata.tsuba.include-private");
+    const modules = (JSON.parse(pinned.stdout) as ExtractedOutput).modules;
+    expect(modules.map((m) => m.parts.join("::"))).to.deep.equal(["pinned", "pinned::generated", "pinned::inner"]);
+    expect(modules[0]!.functions[0]!.returnType).to.equal("crate :: inner :: Thing");
+    expect(modules[1]!.issues.map((i) => i.code)).to.deep.equal(["skipped_by_marker"]);
+
+    const overridden = extract(["--root-module-name", "cli", "--no-resolve-paths", manifest]).modules;
+    expect(overridden[0]!.parts).to.deep.equal(["cli"]);
+    expect(overridden[0]!.functions[0]!.returnType).to.equal("inner :: Thing");
+
+    const invalid = writeCrate({
+      "Cargo.toml": '[package]\nname = "invalid"\nversion = "0.1.0"\n\n[package.metadata.tsuba]\nschema = 2\n',
+      "src/lib.rs": "",
+    });
+    const rejected = runExtractor([invalid]);
+    expect(rejected.status).to.not.equal(0);
+    expect(rejected.stderr).to.contain("Invalid package.metadata.tsuba.schema");
+  });
+
+  it("lists the traits each struct and enum implements in implementedTraits", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod impls;",
+        "pub trait Shape { fn area(&self) -> f64; }",
+        "pub trait Convert<T> { fn convert(&self) -> T; }",
+        "pub struct Circle;",
+        "pub struct Plain;",
+        "pub enum Kind { A }",
+        "impl Shape for Circle { fn area(&self) -> f64 { 1.0 } }",
+        "impl Circle { pub fn new() -> Circle { Circle } }",
+        "impl std::fmt::Display for &Plain { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) } }",
+        "",
+      ].join("\n"),
+      "src/impls.rs": [
+        "use crate::{Circle, Convert, Kind};",
+        "impl Convert<u8> for Circle { fn convert(&self) -> u8 { 0 } }",
+        "impl Convert<i32> for Circle { fn convert(&self) -> i32 { 0 } }",
+        "impl std::fmt::Debug for Kind { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) } }",
+        "impl Clone for Kind { fn clone(&self) -> Kind { Kind::A } }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs.map((s) => [s.name, s.implementedTraits])).to.deep.equal([
+      ["Circle", ["Convert", "Shape"]],
+      ["Plain", undefined],
+    ]);
+    expect(root.enums.map((e) => [e.name, e.implementedTraits])).to.deep.equal([["Kind", ["Clone", "std::fmt::Debug"]]]);
+  });
+
+  it("extracts cargo expand output with --expand and falls back when expansion fails", () => {
+    const manifest = writeCrate({
+      "Cargo.toml": '[package]\nname = "expanded"\nversion = "0.1.0"\n\n[features]\nextra = []\n',
+      "src/lib.rs": "macro_rules! api { ($name:ident) => { pub fn $name() {} }; }\napi!(generated);\npub fn written() {}\n",
+    });
+    // A `cargo-expand` on PATH is what `cargo expand` runs; this stub prints canned expansion.
+    const binDir = dirname(manifest);
+    const stub = join(binDir, "cargo-expand");
+    writeFileSync(
+      stub,
+      [
+        "#!/bin/sh",
+        `echo "$@" > ${join(binDir, "expand-args")}`,
+        'if [ -n "$EXPAND_FAIL" ]; then echo "error: expansion needs nightly" >&2; exit 1; fi',
+        "echo 'pub mod inner { pub struct Made; }'",
+        "echo 'pub fn generated() {}'",
+        "echo 'pub fn written() {}'",
+        "",
+      ].join("\n"),
+      { mode: 0o755 }
+    );
+    const path = `${binDir}:${process.env.PATH ?? ""}`;
+
+    const expanded = runExtractor(["--expand", "--feature", "extra", manifest], { PATH: path });
+    expect(expanded.status, expanded.stderr).to.equal(0);
+    const modules = (JSON.parse(expanded.stdout) as ExtractedOutput).modules;
+    expect(modules.map((m) => [m.parts.join("::"), m.file])).to.deep.equal([
+      ["", "src/lib.rs"],
+      ["inner", "src/lib.rs"],
+    ]);
+    expect(modules[0]!.functions.map((f) => f.name)).to.deep.equal(["generated", "written"]);
+    expect(modules[1]!.structs.map((s) => s.name)).to.deep.equal(["Made"]);
+    expect(readFileSync(join(binDir, "expand-args"), "utf-8")).to.contain("--lib --color never --manifest-path");
+    expect(readFileSync(join(binDir, "expand-args"), "utf-8")).to.contain("--features extra");
+
+    const fallback = runExtractor(["--expand", manifest], { PATH: path, EXPAND_FAIL: "1" });
+    expect(fallback.status, fallback.stderr).to.equal(0);
+    expect(fallback.stderr).to.contain("warning: --expand failed");
+    expect(fallback.stderr).to.contain("error: expansion needs nightly");
+    expect((JSON.parse(fallback.stdout) as ExtractedOutput).modules[0]!.functions.map((f) => f.name)).to.deep.equal(["written"]);
+  });
+
+  it("records outer attributes as normalized text with --emit-attributes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "/// A point.",
+        "#[derive(Clone, Debug)]",
+        '#[repr(C)]',
+        "pub struct Point {",
+        '    #[doc(hidden)] pub x: i32,',
+        "}",
+        '#[deprecated(note = "use v2")]',
+        "#[must_use]",
+        "pub fn make() -> u32 { #![allow(unused)] 1 }",
+        "#[non_exhaustive] pub enum Mode { A }",
+        "#[allow(dead_code)] pub const LIMIT: u32 = 1;",
+        "pub trait Shape { #[inline] fn area(&self) -> f64 { 0.0 } }",
+        "",
+      ].join("\n"),
+    });
+    expect(extract([manifest]).modules[0]!.structs[0]!.attributes).to.equal(undefined);
+    const root = extract(["--emit-attributes", manifest]).modules[0]!;
+    expect(root.structs[0]!.attributes).to.deep.equal(["derive (Clone , Debug)", "repr (C)"]);
+    expect(root.structs[0]!.fields[0]!.attributes).to.deep.equal(["doc (hidden)"]);
+    expect(root.functions[0]!.attributes).to.deep.equal(['deprecated (note = "use v2")', "must_use"]);
+    expect(root.enums[0]!.attributes).to.deep.equal(["non_exhaustive"]);
+    expect(root.consts[0]!.attributes).to.deep.equal(["allow (dead_code)"]);
+    expect(root.traits[0]!.attributes).to.equal(undefined);
+    expect(root.traits[0]!.methods[0]!.attributes).to.deep.equal(["inline"]);
+  });
+
+  it("marks re-exports from other crates as foreign", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "mod private;",
+        "pub mod shapes;",
+        "use serde::de;",
+        "use crate::shapes as sh;",
+        "pub use serde_json::Value;",
+        "pub use ::log::info;",
+        "pub use de::Error;",
+        "pub use std::fmt::{Debug as Dbg, Display};",
+        "pub use private::Hidden;",
+        "pub use sh::Circle;",
+        "pub use self::shapes::Circle as Round;",
+        "pub enum Mode { A }",
+        "pub use Mode::A;",
+        "",
+      ].join("\n"),
+      "src/private.rs": "pub struct Hidden;\n",
+      "src/shapes.rs": "pub struct Circle;\n",
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.reexports.map((r) => [r.name, r.source, r.foreign ?? false])).to.deep.equal([
+      ["A", "Mode::A", false],
+      ["Circle", "sh::Circle", false],
+      ["Dbg", "std::fmt::Debug", true],
+      ["Display", "std::fmt::Display", true],
+      ["Error", "de::Error", true],
+      ["Hidden", "private::Hidden", false],
+      ["Round", "self::shapes::Circle", false],
+      ["Value", "serde_json::Value", true],
+      ["info", "log::info", true],
+    ]);
+  });
+
+  it("exposes the wrapped field of repr(transparent) newtypes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "use std::marker::PhantomData;",
+        "#[repr(transparent)] pub struct UserId(u64);",
+        "#[repr(transparent)] pub struct Meters { pub value: f64 }",
+        "#[repr(transparent)] pub struct Tagged<T>(pub Vec<u8>, PhantomData<T>);",
+        "pub struct Plain(u64);",
+        "#[repr(C)] pub struct Pair(u32, u32);",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs.map((s) => [s.name, s.transparentInner?.name, s.transparentInner?.type])).to.deep.equal([
+      ["UserId", "0", "u64"],
+      ["Meters", "value", "f64"],
+      ["Tagged", "0", "Vec < u8 >"],
+      ["Plain", undefined, undefined],
+      ["Pair", undefined, undefined],
+    ]);
+    expect(root.structs[0]!.transparentInner?.typeRepr).to.deep.equal({ kind: "path", path: ["u64"] });
+  });
+
+  it("orders impl blocks by target and then source position", () => {
+    const blocks = [
+      "impl Clone for Wrapper<u8> { fn clone(&self) -> Self { Wrapper(self.0) } }",
+      "impl Wrapper<u8> { pub fn second(&self) {} pub fn alpha(&self) {} }",
+      "impl Id { pub fn via_alias(&self) {} }",
+      "impl Wrapper<i32> { pub fn signed(&self) {} }",
+      "impl Alpha { pub fn first(&self) {} }",
+      "impl Debug for Wrapper<u8> { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { Ok(()) } }",
+    ];
+    const order = (lines: readonly string[], args: readonly string[]): unknown => {
+      const manifest = writeCrate({
+        "src/lib.rs": ["use std::fmt::Debug;", "pub struct Wrapper<T>(T);", "pub struct Alpha;", "pub type Id = Wrapper<u8>;", ...lines, ""].join(
+          "\n"
+        ),
+      });
+      return extract([...args, manifest]).modules[0]!.pendingMethods.map((p) => [
+        p.target,
+        p.trait ?? "",
+        p.methods.map((m) => m.name).join(","),
+      ]);
+    };
+    expect(order(blocks, [])).to.deep.equal([
+      ["Alpha", "", "first"],
+      ["Id", "", "via_alias"],
+      ["Wrapper", "Clone", "clone"],
+      ["Wrapper", "", "second,alpha"],
+      ["Wrapper", "", "signed"],
+      ["Wrapper", "Debug", "fmt"],
+    ]);
+    // Renamed alias targets fall into source position among the other blocks on that target, not
+    // wherever the alias name sorted.
+    expect(order(blocks, ["--resolve-paths"])).to.deep.equal([
+      ["Alpha", "", "first"],
+      ["Wrapper", "Clone", "clone"],
+      ["Wrapper", "", "second,alpha"],
+      ["Wrapper", "", "via_alias"],
+      ["Wrapper", "", "signed"],
+      ["Wrapper", "Debug", "fmt"],
+    ]);
+    const reversed = [...blocks].reverse();
+    expect(order(reversed, ["--resolve-paths"])).to.deep.equal([
+      ["Alpha", "", "first"],
+      ["Wrapper", "Debug", "fmt"],
+      ["Wrapper", "", "signed"],
+      ["Wrapper", "", "via_alias"],
+      ["Wrapper", "", "second,alpha"],
+      ["Wrapper", "Clone", "clone"],
+    ]);
+  });
+
+  it("records const values and whether they are literals", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub const ANSWER: i32 = 42;",
+        "pub const OFFSET: f64 = -1.5;",
+        'pub const NAME: &str = "tsuba";',
+        "pub const ON: bool = (true);",
+        "pub const MASK: u32 = 1 << 4;",
+        "pub const LIMIT: i32 = ANSWER * 2;",
+        "pub trait Sized2 {",
+        "    const WIDTH: u32 = 8;",
+        "    const HEIGHT: u32;",
+        "}",
+        "pub struct Grid;",
+        "impl Grid {",
+        "    pub const ZERO: u8 = b'0';",
+        "    pub const EMPTY: Grid = Grid;",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    const values = (fields: readonly ExtractedField[]) => fields.map((c) => [c.name, c.value, c.isLiteral ?? false]);
+    expect(values(root.consts)).to.deep.equal([
+      ["ANSWER", "42", true],
+      ["OFFSET", "-1.5", true],
+      ["NAME", '"tsuba"', true],
+      ["ON", "true", true],
+      ["MASK", "1 << 4", false],
+      ["LIMIT", "ANSWER * 2", false],
+    ]);
+    expect(values(root.traits[0]!.consts ?? [])).to.deep.equal([
+      ["WIDTH", "8", true],
+      ["HEIGHT", undefined, false],
+    ]);
+    expect(values(root.pendingMethods[0]!.consts ?? [])).to.deep.equal([
+      ["ZERO", "b'0'", true],
+      ["EMPTY", "Grid", false],
+    ]);
+    expect(root.issues.map((i) => [i.kind, i.reason])).to.deep.equal([
+      ["trait", "Associated const 'Sized2::WIDTH' is not representable in TS trait facades; it is recorded in `consts`."],
+      ["trait", "Associated const 'Sized2::HEIGHT' is not representable in TS trait facades; it is recorded in `consts`."],
+    ]);
+  });
+
+  it("orders modules and items by source position with --preserve-source-order", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod zeta;",
+        "pub mod alpha { pub fn a() {} }",
+        "pub use zeta::Zed as Zeta;",
+        "pub use alpha::a as Apple;",
+        "pub struct Zed;",
+        "pub struct Able;",
+        "impl Zed { pub fn z(&self) {} }",
+        "impl Able { pub fn a(&self) {} }",
+        "",
+      ].join("\n"),
+      "src/zeta.rs": "pub mod inner;\npub struct Zed;\n",
+      "src/zeta/inner.rs": "pub fn deep() {}\n",
+    });
+    const layout = (args: readonly string[]): unknown =>
+      extract([...args, manifest]).modules.map((m) => [
+        m.parts.join("::"),
+        m.structs.map((s) => s.name),
+        m.reexports.map((r) => r.name),
+        m.pendingMethods.map((p) => p.target),
+      ]);
+    expect(layout([])).to.deep.equal([
+      ["", ["Zed", "Able"], ["Apple", "Zeta"], ["Able", "Zed"]],
+      ["alpha", [], [], []],
+      ["zeta", ["Zed"], [], []],
+      ["zeta::inner", [], [], []],
+    ]);
+    expect(layout(["--preserve-source-order"])).to.deep.equal([
+      ["", ["Zed", "Able"], ["Zeta", "Apple"], ["Zed", "Able"]],
+      ["zeta", ["Zed"], [], []],
+      ["zeta::inner", [], [], []],
+      ["alpha", [], [], []],
+    ]);
+  });
+
+  it("flags constructors and self-consuming builders on trait and impl methods", () => {
+    const root = extract([fixtureManifest("builders")]).modules[0]!;
+    const signals = (fns: readonly ExtractedFunction[]): unknown =>
+      fns.map((f) => [f.name, f.isConstructor, f.consumesSelf, f.returnTypeRepr?.kind, f.returnTypeRepr?.wrappers ?? []]);
+    expect(signals(root.traits[0]!.methods)).to.deep.equal([
+      ["create", true, false, "self", []],
+      ["with", false, true, "self", []],
+      ["twice", false, true, "self", []],
+      ["boxed", false, true, "self", ["Box"]],
+      ["peek", false, false, "path", []],
+    ]);
+    expect(root.pendingMethods.map((p) => [p.trait ?? "", signals(p.methods)])).to.deep.equal([
+      [
+        "",
+        [
+          ["new", true, false, "path", []],
+          ["reset", false, false, "reference", []],
+        ],
+      ],
+      [
+        "Builder",
+        [
+          ["create", true, false, "self", []],
+          ["with", false, true, "self", []],
+          ["peek", false, false, "path", []],
+        ],
+      ],
+    ]);
+  });
+
+  it("marks enums whose variants are all unit variants as C-like", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub enum Color { Red, Green = 4, Blue }",
+        "pub enum Shape { Dot, Circle(f64) }",
+        "pub enum Empty {}",
+        "pub enum Marker { Bare() }",
+        "pub enum Named { Point { x: i32 } }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.enums.map((e) => [e.name, e.isCLike])).to.deep.equal([
+      ["Color", true],
+      ["Shape", false],
+      ["Empty", true],
+      ["Marker", false],
+      ["Named", false],
+    ]);
+  });
+
+  it("names a crate without a package name after its directory", () => {
+    const outer = writeCrate({
+      "src/lib.rs": "pub fn outer() {}\n",
+      "my-lib/Cargo.toml": "[lib]\npath = \"src/lib.rs\"\n",
+      "my-lib/src/lib.rs": "pub fn answer() -> i32 { 42 }\n",
+    });
+    const manifest = join(dirname(outer), "my-lib", "Cargo.toml");
+
+    const output = extract([manifest]);
+    expect(output.crate).to.deep.equal({ name: "my_lib" });
+    expect(output.modules[0]!.functions.map((f) => f.name)).to.deep.equal(["answer"]);
+    expect(output.modules[0]!.issues.map((i) => [i.file, i.kind, i.code, i.snippet])).to.deep.equal([
+      ["Cargo.toml", "crate", "crate_name_from_dir", "my-lib"],
+    ]);
+
+    // A named package is used as before, without an issue.
+    const named = extract([outer]);
+    expect(named.crate.name).to.equal("extractor_case");
+    expect(named.modules.flatMap((m) => m.issues)).to.deep.equal([]);
+  });
+
+  it("records doc comments on struct fields and enum variants", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Config {",
+        "    /// Port to listen on.",
+        "    pub port: u16,",
+        "    /// Host name.",
+        "    ///",
+        "    /// Defaults to localhost.",
+        "    pub host: String,",
+        "    pub plain: bool,",
+        "}",
+        "pub enum Event {",
+        "    /** Key press. */",
+        "    Key(/// Key code.",
+        "        u32),",
+        "    /// Mouse move.",
+        "    Move { /// Horizontal.",
+        "        x: i32, y: i32 },",
+        "    Quit,",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.structs[0]!.fields.map((f) => f.docs)).to.deep.equal([
+      "Port to listen on.",
+      "Host name.\n\nDefaults to localhost.",
+      undefined,
+    ]);
+    const variants = root.enums[0]!.variants;
+    expect(variants.map((v) => v.docs)).to.deep.equal(["Key press.", "Mouse move.", undefined]);
+    expect(variants[0]!.fields[0]!.docs).to.equal("Key code.");
+    expect(variants[1]!.fields.map((f) => f.docs)).to.deep.equal(["Horizontal.", undefined]);
+  });
+
+  it("flattens nested modules into the root with --flatten", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod inner;",
+        "pub use inner::Thing;",
+        "pub fn inner_make() {}",
+        "pub fn root() {}",
+        "",
+      ].join("\n"),
+      "src/inner.rs": [
+        "pub mod deep;",
+        "pub struct Thing;",
+        "impl Thing { pub fn new() -> Self { Thing } }",
+        "pub fn make() {}",
+        "",
+      ].join("\n"),
+      "src/inner/deep.rs": "pub const LIMIT: u32 = 8;\n",
+    });
+
+    const nested = extract([manifest]);
+    expect(nested.modules.map((m) => m.parts.join("::"))).to.deep.equal(["", "inner", "inner::deep"]);
+
+    const output = extract(["--flatten", manifest]);
+    expect(output.modules.length).to.equal(1);
+    const root = output.modules[0]!;
+    expect(root.parts).to.deep.equal([]);
+    expect(root.functions.map((f) => f.name)).to.deep.equal(["inner_make", "root"]);
+    expect(root.structs.map((st) => st.name)).to.deep.equal(["inner_Thing"]);
+    expect(root.pendingMethods.map((p) => p.target)).to.deep.equal(["inner_Thing"]);
+    expect(root.reexports.map((r) => [r.name, r.sourceModule, r.sourceName])).to.deep.equal([
+      ["Thing", [], "inner_Thing"],
+    ]);
+    expect(root.flattened).to.deep.equal([
+      { name: "inner_Thing", module: ["inner"], rustName: "Thing" },
+      { name: "inner_deep_LIMIT", module: ["inner", "deep"], rustName: "LIMIT" },
+    ]);
+    // `inner::make` collides with the root `inner_make` and is dropped with an issue.
+    expect(root.issues.map((i) => [i.file, i.kind, i.code, i.snippet])).to.deep.equal([
+      ["src/inner.rs", "module", "flatten_collision", "inner::make"],
+    ]);
+
+    const custom = extract(["--flatten", "--flatten-separator", "__", manifest]).modules[0]!;
+    expect(custom.flattened!.map((f) => f.name)).to.deep.equal(["inner__Thing", "inner__make", "inner__deep__LIMIT"]);
+    expect(custom.issues).to.deep.equal([]);
+
+    const orphan = runExtractor(["--flatten-separator", "__", manifest]);
+    expect(orphan.status).to.equal(1);
+    expect(orphan.stderr).to.contain("Option --flatten-separator requires --flatten.");
+  });
+
+  it("records const generic parameters of functions and links array lengths to them", () => {
+    const root = extract([fixtureManifest("const-generics")]).modules[0]!;
+    const consts = (fns: readonly ExtractedFunction[]): unknown =>
+      fns.map((f) => [f.name, (f.constParams ?? []).map((c) => `${c.name}: ${c.type}`)]);
+    expect(consts(root.functions)).to.deep.equal([
+      ["make", ["N: usize"]],
+      ["sum", ["N: usize"]],
+      ["split", ["ROWS: usize", "COLS: usize"]],
+      ["fixed", []],
+    ]);
+    const [make, sum, split, fixed] = root.functions;
+    expect(make!.returnTypeRepr).to.deep.equal({ kind: "array", inner: { kind: "path", path: ["u8"] }, len: "N", constParam: "N" });
+    expect(sum!.params[0]!.typeRepr!.constParam).to.equal("N");
+    const rows = split!.returnTypeRepr!.args![0]!;
+    expect([rows.constParam, rows.inner!.constParam]).to.deep.equal(["ROWS", "COLS"]);
+    expect(fixed!.returnTypeRepr!.constParam).to.equal(undefined);
+    expect(consts(root.pendingMethods[0]!.methods)).to.deep.equal([["header", ["SIZE: usize"]]]);
+    expect(consts(root.traits[0]!.methods)).to.deep.equal([["encode", ["N: usize"]]]);
+    expect(root.issues.map((i) => i.reason)).to.contain(
+      "Function 'make' const generic parameters are not representable in TS facades; they are recorded in `constParams`.",
+    );
+
+    // --resolve-paths rebuilds rewritten type nodes; the links must survive.
+    const resolved = extract([
+      "--resolve-paths",
+      writeCrate({
+        "src/lib.rs": "pub mod inner { pub struct Id; }\nuse inner::Id;\npub fn ids<const N: usize>(seed: [Id; N]) -> [Id; N] { seed }\n",
+      }),
+    ]).modules[0]!.functions[0]!;
+    expect(resolved.returnTypeRepr!.inner!.path).to.deep.equal(["crate", "inner", "Id"]);
+    expect([resolved.params[0]!.typeRepr!.constParam, resolved.returnTypeRepr!.constParam]).to.deep.equal(["N", "N"]);
+  });
+
+  it("flags items named after TS reserved words with ts_reserved_name", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub fn delete() {}",
+        "pub fn r#typeof() {}",
+        "pub struct Promise;",
+        '#[tsuba(rename = "remove")]',
+        "pub fn new() {}",
+        "pub const NULL: u8 = 0;",
+        "pub struct Thing { pub class: u8 }",
+        "impl Thing { pub fn new() -> Self { Thing { class: 0 } } }",
+        "pub use self::Thing as default;",
+        "pub fn namespace() {}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(root.issues.map((i) => [i.file, i.kind, i.code, i.snippet])).to.deep.equal([
+      ["src/lib.rs", "name", "ts_reserved_name", "Promise"],
+      ["src/lib.rs", "name", "ts_reserved_name", "delete"],
+      ["src/lib.rs", "name", "ts_reserved_name", "r#typeof"],
+      ["src/lib.rs", "name", "ts_reserved_name", "namespace"],
+      ["src/lib.rs", "name", "ts_reserved_name", "default"],
+    ]);
+    expect(root.issues[0]!.reason).to.equal(
+      "Struct name 'Promise' is a TS reserved word or global and cannot be declared by generated facades; rename it with #[tsuba(rename = \"...\")].",
+    );
+    expect(root.issues[1]!.reason).to.equal(
+      "Function name 'delete' is a TS reserved word; generated facades declare it as 'delete_'. Rename it with #[tsuba(rename = \"...\")] to choose the name.",
+    );
+    expect(root.issues[2]!.reason).to.include("cannot be declared by generated facades");
+    expect(root.issues[3]!.reason).to.include("declare it as 'namespace_'");
+    // The items are still emitted; the issue is only a warning.
+    expect(root.functions.map((f) => f.name)).to.deep.equal(["delete", "r#typeof", "new", "namespace"]);
+  });
+
+  it("checks plain re-exports of renamed items under the export name", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod inner {",
+        '    #[tsuba(rename = "remove")]',
+        "    pub fn delete() {}",
+        "}",
+        "pub use inner::delete;",
+        "pub use inner::delete as class;",
+        "",
+      ].join("\n"),
+    });
+    const modules = extract([manifest]).modules;
+    expect(modules.flatMap((m) => m.issues.map((i) => [m.parts.join("::"), i.code, i.snippet]))).to.deep.equal([
+      ["", "ts_reserved_name", "class"],
+    ]);
+  });
+
+  it("marks traits without members as isMarker", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait Marker {}",
+        "pub trait Both: Marker + Clone {}",
+        "pub trait Read { fn read(&self) -> u8; }",
+        "pub trait Typed { type Out; }",
+        "pub trait Limited { const MAX: u32; }",
+        "",
+      ].join("\n"),
+    });
+    const traits = extract([manifest]).modules[0]!.traits;
+    expect(traits.map((t) => [t.name, t.isMarker, t.superTraits])).to.deep.equal([
+      ["Marker", true, []],
+      ["Both", true, ["Marker", "Clone"]],
+      ["Read", false, []],
+      ["Typed", false, []],
+      ["Limited", false, []],
+    ]);
+  });
+
+  it("ignores restricted-visibility re-exports", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod inner {",
+        "    pub struct Shared;",
+        "    pub struct Internal;",
+        "    pub(super) use self::Internal as ParentOnly;",
+        "    pub(in crate::inner) use self::Internal as InnerOnly;",
+        "}",
+        "pub use inner::Shared;",
+        "pub(crate) use inner::Internal;",
+        "use inner::Internal as Private;",
+        "",
+      ].join("\n"),
+    });
+    const output = extract([manifest]);
+    expect(output.modules.map((m) => [m.parts.join("::"), m.reexports.map((r) => r.name)])).to.deep.equal([
+      ["", ["Shared"]],
+      ["inner", []],
+    ]);
+    expect(output.modules.flatMap((m) => m.issues)).to.deep.equal([]);
+  });
+
+  it("extracts restricted re-exports only under --include-private", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub mod shapes;",
+        "pub use shapes::Circle;",
+        "pub(crate) use shapes::Square;",
+        "pub(in crate::shapes) use shapes::Triangle as Tri;",
+        "use shapes::Hidden;",
+        "",
+      ].join("\n"),
+      "src/shapes.rs": "pub struct Circle;\npub struct Square;\npub struct Triangle;\npub struct Hidden;\n",
+    });
+    const reexports = (args: readonly string[]) =>
+      extract(args).modules[0]!.reexports.map((r) => [r.name, r.sourceModule, r.visibility ?? null]);
+    expect(reexports([manifest])).to.deep.equal([["Circle", ["shapes"], null]]);
+    expect(reexports(["--include-private", manifest])).to.deep.equal([
+      ["Circle", ["shapes"], null],
+      ["Square", ["shapes"], "pub(crate)"],
+      ["Tri", ["shapes"], "pub(in crate::shapes)"],
+    ]);
+  });
+
+  it("records trait bounds and supertraits as path nodes", () => {
+    const manifest = fixtureManifest("trait-paths");
+    const paths = (list: readonly ExtractedPath[] | undefined): unknown => (list ?? []).map((p) => p.path.join("::"));
+    const bounds = (fns: readonly ExtractedFunction[]): unknown =>
+      fns.map((f) => [f.name, (f.genericBounds ?? []).map((b) => [b.type, paths(b.paths)])]);
+
+    const root = extract([manifest]).modules[0]!;
+    expect(root.traits.map((t) => [t.name, paths(t.superTraitPaths)])).to.deep.equal([
+      ["Loggable", ["std::fmt::Debug", "crate::traits::Reader"]],
+      ["Pipe", ["traits::Source", "Send"]],
+    ]);
+    expect(root.traits[1]!.superTraitPaths![0]!.args!.map((a) => a.path)).to.deep.equal([["u8"]]);
+    // `?Sized` and lifetime bounds have no path node.
+    expect(bounds(root.functions)).to.deep.equal([
+      ["show", [["T", ["fmt::Display"]]]],
+      ["drain", [["R", ["traits::Reader", "std::io::Read"]]]],
+      ["boxed", [["F", ["std::ops::Fn"]]]],
+    ]);
+    expect(bounds(root.traits[1]!.methods)).to.deep.equal([["flush_into", [["E", ["Extend", "core::fmt::Debug"]]]]]);
+
+    // --resolve-paths canonicalizes local trait paths; texts and foreign paths stay as written.
+    const resolved = extract(["--resolve-paths", manifest]).modules[0]!;
+    expect(resolved.traits.map((t) => [t.superTraits, paths(t.superTraitPaths)])).to.deep.equal([
+      [["std :: fmt :: Debug", "crate :: traits :: Reader"], ["std::fmt::Debug", "crate::traits::Reader"]],
+      [["traits :: Source < u8 >", "Send", "'static"], ["crate::traits::Source", "Send"]],
+    ]);
+    expect(bounds(resolved.functions)).to.deep.equal([
+      ["show", [["T", ["fmt::Display"]]]],
+      ["drain", [["R", ["crate::traits::Reader", "std::io::Read"]]]],
+      ["boxed", [["F", ["std::ops::Fn"]]]],
+    ]);
+    expect(resolved.pendingMethods.map((p) => (p.genericBounds ?? []).map((b) => paths(b.paths)))).to.deep.equal([
+      [["crate::traits::Reader"]],
+    ]);
+  });
+
+  it("keeps trait impl members without pub while filtering inherent ones", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait Named { const ID: u32; type Tag; fn label(&self) -> String; }",
+        "pub struct Wrapper<T> { pub value: T }",
+        "impl Named for Wrapper<i32> {",
+        "    const ID: u32 = 1;",
+        "    type Tag = ();",
+        "    fn label(&self) -> String { String::new() }",
+        "}",
+        "impl Wrapper<i32> {",
+        "    pub const SHOWN: u8 = 0;",
+        "    const SECRET: u8 = 0;",
+        "    pub fn shown(&self) {}",
+        "    fn hidden(&self) {}",
+        "    pub(crate) fn internal(&self) {}",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const pending = extract([manifest]).modules[0]!.pendingMethods;
+    expect(
+      pending.map((p) => [p.trait ?? "", p.methods.map((m) => m.name), (p.consts ?? []).map((c) => c.name), (p.types ?? []).map((t) => t.name)]),
+    ).to.deep.equal([
+      ["Named", ["label"], ["ID"], ["Tag"]],
+      ["", ["shown"], ["SHOWN"], []],
+    ]);
+  });
+
+  it("roots the crate at [lib] path when the manifest sets one", () => {
+    const manifest = writeCrate({
+      "Cargo.toml": '[package]\nname = "custom-root"\nversion = "0.1.0"\n\n[lib]\npath = "src/custom_root.rs"\n',
+      "src/custom_root.rs": "pub mod child;\npub fn top() {}\n",
+      "src/child.rs": "pub fn nested() {}\n",
+      "src/lib.rs": "pub fn ignored() {}\n",
+    });
+    const output = extract([manifest]);
+    expect(output.modules.map((m) => [m.file, m.parts.join("::"), m.functions.map((f) => f.name)])).to.deep.equal([
+      ["src/custom_root.rs", "", ["top"]],
+      ["src/child.rs", "child", ["nested"]],
+    ]);
+    expect(runExtractor(["--list-files", manifest]).stdout).to.equal("src/custom_root.rs\nsrc/child.rs\n");
+
+    const missing = runExtractor([
+      writeCrate({ "Cargo.toml": '[package]\nname = "gone"\nversion = "0.1.0"\n\n[lib]\npath = "lib/root.rs"\n' }),
+    ]);
+    expect(missing.status).to.equal(1);
+    expect(missing.stderr).to.contain('(expected [lib] path = "lib/root.rs").');
+  });
+
+  it("roots a crate without a library target at its first [[bin]] path", () => {
+    const bin = '[[bin]]\nname = "tool"\npath = "tools/main.rs"\n\n[[bin]]\nname = "other"\npath = "tools/other.rs"\n';
+    const manifest = writeCrate({
+      "Cargo.toml": `[package]\nname = "bin-only"\nversion = "0.1.0"\n\n${bin}`,
+      "tools/main.rs": "pub fn run() {}\n",
+      "tools/other.rs": "pub fn other() {}\n",
+    });
+    expect(extract([manifest]).modules.map((m) => [m.file, m.functions.map((f) => f.name)])).to.deep.equal([
+      ["tools/main.rs", ["run"]],
+    ]);
+
+    // A library target still wins over the binaries.
+    const withLib = writeCrate({
+      "Cargo.toml": `[package]\nname = "both"\nversion = "0.1.0"\n\n${bin}`,
+      "src/lib.rs": "pub fn library() {}\n",
+      "tools/main.rs": "pub fn run() {}\n",
+    });
+    expect(extract([withLib]).modules[0]!.functions.map((f) => f.name)).to.deep.equal(["library"]);
+
+    const missing = runExtractor([writeCrate({ "Cargo.toml": `[package]\nname = "gone"\nversion = "0.1.0"\n\n${bin}` })]);
+    expect(missing.status).to.equal(1);
+    expect(missing.stderr).to.contain('(expected [[bin]] path = "tools/main.rs").');
+  });
+
+  it("collects inline, cold, and track_caller into fnAttrs with --emit-attributes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "#[inline(always)]",
+        "#[must_use]",
+        "#[track_caller]",
+        "pub fn hot() -> u32 { 1 }",
+        "#[cold] #[inline(never)] pub fn fail() {}",
+        "#[inline] pub fn plain() {}",
+        "pub fn bare() {}",
+        "pub struct Grid;",
+        "impl Grid { #[inline] pub fn new() -> Self { Grid } }",
+        "pub trait Shape { #[cold] fn area(&self) -> f64 { 0.0 } }",
+        "",
+      ].join("\n"),
+    });
+    expect(extract([manifest]).modules[0]!.functions[0]!.fnAttrs).to.equal(undefined);
+    const root = extract(["--emit-attributes", manifest]).modules[0]!;
+    expect(root.functions.map((f) => [f.name, f.fnAttrs])).to.deep.equal([
+      ["hot", ["inline(always)", "track_caller"]],
+      ["fail", ["cold", "inline(never)"]],
+      ["plain", ["inline"]],
+      ["bare", undefined],
+    ]);
+    expect(root.functions.find((f) => f.name === "hot")!.attributes).to.deep.equal([
+      "inline (always)",
+      "must_use",
+      "track_caller",
+    ]);
+    expect(root.pendingMethods[0]!.methods[0]!.fnAttrs).to.deep.equal(["inline"]);
+    expect(root.traits[0]!.methods[0]!.fnAttrs).to.deep.equal(["cold"]);
+  });
+
+  it("treats test as off unless --cfg test is given", () => {
+    const manifest = fixtureManifest("cfg-combinators");
+    const names = (args: readonly string[]): unknown => {
+      const root = extract([...args, manifest]).modules.find((m) => m.parts.length === 0)!;
+      return [root.functions.map((f) => f.name), root.consts.map((c) => c.name), root.structs.map((st) => st.name)];
+    };
+    expect(names([])).to.deep.equal([
+      ["clock", "fast_or_test", "platform", "platform_or_test", "always"],
+      ["MODE"],
+      ["Engine", "Gauge"],
+    ]);
+    expect(names(["--cfg", "test"])).to.deep.equal([
+      ["clock", "fast_test_only", "fast_or_test", "slow_or_test", "platform_or_test", "always"],
+      [],
+      ["Gauge"],
+    ]);
+    expect(extract(["--cfg", "test", manifest]).modules.map((m) => m.file)).to.deep.equal(["src/lib.rs", "src/fixtures.rs"]);
+    const issues = extract([manifest]).modules[0]!.issues;
+    expect(issues.filter((i) => i.code === "unevaluated_cfg").map((i) => i.snippet)).to.deep.equal([
+      "platform",
+      "platform_or_test",
+    ]);
+  });
+
+  it("reads attributed parameters and records their attributes with --emit-attributes", () => {
+    const manifest = fixtureManifest("edge");
+    const params = (args: readonly string[], name: string): unknown => {
+      const root = extract([...args, manifest]).modules.find((m) => m.parts.length === 0)!;
+      const fn = root.functions.find((f) => f.name === name) ?? root.pendingMethods.flatMap((p) => p.methods).find((f) => f.name === name)!;
+      return fn.params.map((p) => [p.name, p.type, p.attributes]);
+    };
+    expect(params([], "annotated")).to.deep.equal([
+      ["value", "i32", undefined],
+      ["count", "u32", undefined],
+      ["unsupported", "bool", undefined],
+    ]);
+    expect(params(["--emit-attributes"], "annotated")).to.deep.equal([
+      ["value", "i32", ["allow (unused_variables)"]],
+      ["count", "u32", ["allow (unused_mut)"]],
+      ["unsupported", "bool", undefined],
+    ]);
+    expect(params(["--emit-attributes"], "bump")).to.deep.equal([
+      ["&self", "self", ["allow (unused)"]],
+      ["by", "u32", ["allow (unused)"]],
+    ]);
+  });
+
+  it("diffs the public items of an old crate against the extracted one with --diff", () => {
+    const oldManifest = writeCrate({
+      "src/lib.rs": [
+        "/// Area.",
+        "pub fn area(w: u32, h: u32) -> u32 { w * h }",
+        "pub fn legacy() {}",
+        "pub struct Circle { pub r: f64 }",
+        "impl Circle { pub fn radius(&self) -> f64 { self.r } }",
+        "",
+      ].join("\n"),
+    });
+    const newManifest = writeCrate({
+      "src/lib.rs": [
+        "// A leading comment moves every span.",
+        "/// The area.",
+        "pub fn area(w: u64, h: u32) -> u64 { w * h as u64 }",
+        "pub struct Circle { pub r: f64 }",
+        "impl Circle { pub fn radius(&self) -> f64 { self.r } pub fn new(r: f64) -> Self { Circle { r } } }",
+        "pub mod extra { pub const LIMIT: u32 = 1; }",
+        "",
+      ].join("\n"),
+    });
+    const result = runExtractor(["--diff", oldManifest, newManifest]);
+    expect(result.status, result.stderr).to.equal(0);
+    expect(JSON.parse(result.stdout)).to.deep.equal({
+      schema: 1,
+      old: { name: "extractor_case", package: "extractor-case" },
+      new: { name: "extractor_case", package: "extractor-case" },
+      added: [
+        { kind: "method", path: "crate::Circle::new" },
+        { kind: "const", path: "crate::extra::LIMIT" },
+      ],
+      removed: [{ kind: "function", path: "crate::legacy" }],
+      changed: [{ kind: "function", path: "crate::area", changes: ["params", "returnType", "returnTypeRepr"] }],
+    });
+
+    const same = runExtractor(["--diff", newManifest, newManifest]);
+    expect(JSON.parse(same.stdout)).to.deep.include({ added: [], removed: [], changed: [] });
+    const combined = runExtractor(["--diff", oldManifest, "--list-files", newManifest]);
+    expect(combined.status).to.not.equal(0);
+    expect(combined.stderr).to.include("Option --list-files cannot be combined with another output mode.");
+  });
+
+  it("records the Output of boxed-future returns with --recognize-futures", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "use std::future::Future;",
+        "use std::pin::Pin;",
+        "pub mod inner { pub struct Data; }",
+        "pub trait Service {",
+        "    fn call(&self) -> Pin<Box<dyn Future<Output = Result<u32, String>> + Send + '_>>;",
+        "}",
+        "pub fn boxed() -> Box<dyn Future<Output = u8>> { todo!() }",
+        "pub fn spelled() -> std::pin::Pin<Box<dyn std::future::Future<Output = inner::Data>>> { todo!() }",
+        "pub fn closure() -> Box<dyn Fn() -> u8> { todo!() }",
+        "pub async fn native() -> u8 { 1 }",
+        "",
+      ].join("\n"),
+    });
+    const outputs = (args: readonly string[]): unknown => {
+      const root = extract([...args, manifest]).modules.find((m) => m.parts.length === 0)!;
+      return [...root.functions, ...root.traits[0]!.methods].map((f) => [f.name, f.futureOutput, f.isAsync]);
+    };
+    expect(outputs([])).to.deep.equal([
+      ["boxed", undefined, false],
+      ["spelled", undefined, false],
+      ["closure", undefined, false],
+      ["native", undefined, true],
+      ["call", undefined, false],
+    ]);
+    expect(outputs(["--recognize-futures"])).to.deep.equal([
+      ["boxed", "u8", false],
+      ["spelled", "inner :: Data", false],
+      ["closure", undefined, false],
+      ["native", undefined, true],
+      ["call", "Result < u32 , String >", false],
+    ]);
+    const resolved = extract(["--recognize-futures", "--resolve-paths", manifest]).modules[0]!;
+    const spelled = resolved.functions.find((f) => f.name === "spelled")!;
+    expect([spelled.futureOutput, spelled.futureOutputRepr]).to.deep.equal([
+      "crate :: inner :: Data",
+      { kind: "path", path: ["crate", "inner", "Data"] },
+    ]);
+  });
+
+  it("evaluates #[cfg] gates on impl blocks like items", () => {
+    const manifest = fixtureManifest("cfg-combinators");
+    const gauge = (args: readonly string[]): unknown => {
+      const root = extract([...args, manifest]).modules[0]!;
+      const struct = root.structs.find((st) => st.name === "Gauge")!;
+      const methods = root.pendingMethods.filter((p) => p.target === "Gauge").flatMap((p) => p.methods.map((m) => m.name));
+      const disabled = root.issues.filter((i) => i.code === "disabled_by_cfg" && i.reason.startsWith("Impl ")).map((i) => i.snippet);
+      return [methods, struct.hasDefault, struct.implementedTraits, disabled];
+    };
+    expect(gauge([])).to.deep.equal([
+      ["read", "read_fast", "fmt"],
+      false,
+      ["std::fmt::Display"],
+      ["Gauge", "Default for Gauge"],
+    ]);
+    expect(gauge(["--no-default-features", "--feature", "slow"])).to.deep.equal([
+      ["read", "read_slow", "default"],
+      true,
+      ["Default"],
+      ["Gauge", "std :: fmt :: Display for Gauge"],
+    ]);
+  });
+
+  it("keeps impl block docs and locations with --preserve-impl-groups", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Grid;",
+        "/// Construction.",
+        "impl Grid {",
+        "    pub fn new() -> Self { Grid }",
+        "}",
+        "/// Queries.",
+        "///",
+        "/// Read-only.",
+        "impl Grid {",
+        "    pub fn width(&self) -> u32 { 0 }",
+        "    pub fn height(&self) -> u32 { 0 }",
+        "}",
+        "impl Clone for Grid { fn clone(&self) -> Self { Grid } }",
+        "",
+      ].join("\n"),
+    });
+    const groups = (args: readonly string[]): unknown =>
+      extract([...args, manifest]).modules[0]!.pendingMethods.map((p) => [
+        p.methods.map((m) => m.name),
+        p.docs,
+        p.sourceFile,
+        p.lineStart,
+      ]);
+    expect(groups([])).to.deep.equal([
+      [["new"], undefined, undefined, undefined],
+      [["width", "height"], undefined, undefined, undefined],
+      [["clone"], undefined, undefined, undefined],
+    ]);
+    expect(groups(["--preserve-impl-groups"])).to.deep.equal([
+      [["new"], "Construction.", "src/lib.rs", 2],
+      [["width", "height"], "Queries.\n\nRead-only.", "src/lib.rs", 6],
+      [["clone"], undefined, "src/lib.rs", 13],
+    ]);
+  });
+
+  it("loads options from a --config file that CLI flags override", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod inner;\npub fn make() -> inner::Thing { inner::Thing }\n",
+      "src/inner.rs": "pub struct Thing;\n",
+      "tsuba.toml": [
+        'manifest = "Cargo.toml"',
+        'root-module-name = "configured"',
+        "resolve-paths = true",
+        "stats = false",
+        "emit-private = true",
+        "",
+      ].join("\n"),
+      "tsuba.json": JSON.stringify({ "root-module-name": "json", "resolve-paths": false, future: { on: true } }),
+    });
+    const crateDir = dirname(manifest);
+    const fromToml = runExtractor(["--config", join(crateDir, "tsuba.toml")]);
+    expect(fromToml.status, fromToml.stderr).to.equal(0);
+    expect(fromToml.stderr).to.contain("warning: ignoring unknown key emit-private in config");
+    const modules = (JSON.parse(fromToml.stdout) as ExtractedOutput).modules;
+    expect(modules.map((m) => m.parts.join("::"))).to.deep.equal(["configured", "configured::inner"]);
+    expect(modules[0]!.functions[0]!.returnType).to.equal("crate :: inner :: Thing");
+
+    const overridden = extract(["--config", join(crateDir, "tsuba.toml"), "--root-module-name", "cli", "--no-resolve-paths"]);
+    expect(overridden.modules[0]!.parts).to.deep.equal(["cli"]);
+    expect(overridden.modules[0]!.functions[0]!.returnType).to.equal("inner :: Thing");
+
+    const fromJson = runExtractor(["--config", join(crateDir, "tsuba.json"), manifest]);
+    expect(fromJson.status, fromJson.stderr).to.equal(0);
+    expect(fromJson.stderr).to.contain("warning: ignoring unknown key future in config");
+    const jsonModules = (JSON.parse(fromJson.stdout) as ExtractedOutput).modules;
+    expect(jsonModules[0]!.parts).to.deep.equal(["json"]);
+    expect(jsonModules[0]!.functions[0]!.returnType).to.equal("inner :: Thing");
+
+    writeFileSync(join(crateDir, "bad.toml"), 'verbose = "yes"\n');
+    const rejected = runExtractor(["--config", join(crateDir, "bad.toml"), manifest]);
+    expect(rejected.status).to.not.equal(0);
+    expect(rejected.stderr).to.contain("Invalid verbose in config");
+  });
+
+  it("records the total generic arity and the names a facade cannot express", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub struct Bytes<const N: usize>([u8; N]);",
+        "pub enum Slice<'a, T> { Borrowed(&'a [T]), Empty }",
+        "pub struct Plain;",
+        "impl<const N: usize> Bytes<N> { pub fn len<'b>(&'b self) -> usize { N } }",
+        "pub fn pair<A, B>(a: A, b: B) -> (A, B) { (a, b) }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    const arity = (item: { readonly totalGenericArity?: number; readonly unrepresentableGenerics?: readonly string[] }) => [
+      item.totalGenericArity,
+      item.unrepresentableGenerics,
+    ];
+    expect(root.structs.map((s) => [s.name, ...arity(s)])).to.deep.equal([
+      ["Bytes", 1, ["N"]],
+      ["Plain", undefined, undefined],
+    ]);
+    expect(arity(root.enums[0]!)).to.deep.equal([2, ["'a"]]);
+    expect(arity(root.pendingMethods[0]!)).to.deep.equal([1, ["N"]]);
+    expect(arity(root.pendingMethods[0]!.methods[0]!)).to.deep.equal([1, ["'b"]]);
+    expect(arity(root.functions[0]!)).to.deep.equal([2, undefined]);
+    expect(root.issues.filter((i) => i.kind === "generic").length).to.equal(4);
+  });
+
+  it("extracts Rust code blocks from doc comments with --extract-examples", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "/// Adds two numbers.",
+        "///",
+        "/// ```",
+        "/// # use extractor_case::add;",
+        "/// assert_eq!(add(1, 2), 3);",
+        "/// ```",
+        "///",
+        "/// ```text",
+        "/// not rust",
+        "/// ```",
+        "///",
+        "/// ```rust,no_run",
+        "/// let x = add(0, 0);",
+        "///     // indented",
+        "/// ```",
+        "pub fn add(a: i32, b: i32) -> i32 { a + b }",
+        "",
+        "/// A point.",
+        "///",
+        "/// ~~~ignore",
+        "/// let p = Point;",
+        "/// /// ```",
+        "/// ~~~",
+        "pub struct Point;",
+        "",
+        "/// No examples here.",
+        "pub fn plain() {}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract(["--extract-examples", manifest]).modules[0]!;
+    expect(root.functions.map((f) => [f.name, f.examples])).to.deep.equal([
+      ["add", ["# use extractor_case::add;\nassert_eq!(add(1, 2), 3);", "let x = add(0, 0);\n    // indented"]],
+      ["plain", undefined],
+    ]);
+    expect(root.structs[0]!.examples).to.deep.equal(["let p = Point;\n/// ```"]);
+    expect(extract([manifest]).modules[0]!.functions[0]!.examples).to.equal(undefined);
+  });
+
+  it("marks the [package.metadata.tsuba] prelude module as the primary API", () => {
+    const files = {
+      "src/lib.rs": "pub mod shapes;\npub mod prelude { pub use crate::shapes::Circle; }\n",
+      "src/shapes.rs": "pub struct Circle;\n",
+    };
+    const withPrelude = (value: string) =>
+      writeCrate({
+        ...files,
+        "Cargo.toml": `[package]\nname = "curated"\nversion = "0.1.0"\n\n[package.metadata.tsuba]\nprelude = ${value}\n`,
+      });
+    const manifest = withPrelude('"prelude"');
+    const output = extract([manifest]);
+    expect(output.crate.prelude).to.deep.equal(["prelude"]);
+    expect(output.modules.map((m) => [m.parts.join("::"), m.prelude])).to.deep.equal([
+      ["", undefined],
+      ["prelude", true],
+      ["shapes", undefined],
+    ]);
+    expect(output.modules[1]!.reexports[0]!.sourceModule).to.deep.equal(["shapes"]);
+
+    const renamed = extract(["--root-module-name", "curated", manifest]);
+    expect(renamed.crate.prelude).to.deep.equal(["curated", "prelude"]);
+    expect(extract(["--subtree", "shapes", manifest]).crate.prelude).to.equal(undefined);
+    expect(extract([writeCrate(files)]).crate.prelude).to.equal(undefined);
+
+    const missing = runExtractor([withPrelude('"api::prelude"')]);
+    expect(missing.status).to.not.equal(0);
+    expect(missing.stderr).to.contain("Prelude module 'api::prelude' (package.metadata.tsuba.prelude) was not found");
+    const invalid = runExtractor([withPrelude("true")]);
+    expect(invalid.status).to.not.equal(0);
+    expect(invalid.stderr).to.contain("Invalid package.metadata.tsuba.prelude");
+  });
+
+  it("emits opaque tool-attribute types without field details", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "#[tsuba(opaque)]",
+        "#[repr(transparent)]",
+        "pub struct Handle(pub u64);",
+        "",
+        "#[tsuba(opaque)]",
+        "pub struct Connection {",
+        "    pub url: String,",
+        "    socket: i32,",
+        "}",
+        "",
+        "impl Connection {",
+        "    pub fn socket(&self) -> i32 {",
+        "        self.socket",
+        "    }",
+        "}",
+        "",
+        "#[tsuba(opaque)]",
+        "pub enum State {",
+        "    Idle,",
+        "    Busy(u32),",
+        "}",
+        "",
+        "pub struct Plain {",
+        "    pub value: i32,",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    const root = extract(["--infer-properties", manifest]).modules[0]!;
+    expect(
+      root.structs.map((st) => [st.name, st.opaque, st.fields.length, st.skippedPrivateFields, st.transparentInner])
+    ).to.deep.equal([
+      ["Handle", true, 0, 0, undefined],
+      ["Connection", true, 0, 0, undefined],
+      ["Plain", undefined, 1, 0, undefined],
+    ]);
+    expect(root.structs[1]!.derivedProperties).to.equal(undefined);
+    expect(root.enums.map((e) => [e.name, e.opaque, e.variants.length, e.isCLike])).to.deep.equal([
+      ["State", true, 0, false],
+    ]);
+    expect(root.issues).to.deep.equal([]);
+    expect(root.pendingMethods[0]!.methods.map((m) => m.name)).to.deep.equal(["socket"]);
+  });
+
+  it("copies items re-exported from path dependencies with --with-deps", () => {
+    const manifest = fixtureManifest("facade");
+    const plain = extract([manifest]);
+    expect(plain.modules[0]!.reexports.map((r) => [r.name, r.foreign])).to.deep.equal([
+      ["Round", true],
+      ["Serialize", true],
+      ["VERSION", true],
+      ["circle_area", true],
+      ["shapes", true],
+    ]);
+    const [root, geometry] = extract(["--with-deps", manifest]).modules;
+    expect(root!.reexports.map((r) => r.source)).to.deep.equal(["serde::Serialize", "facade_core::shapes"]);
+    expect(root!.dependencyItems).to.deep.equal([
+      { name: "Round", crate: "facade_core", module: ["shapes"], rustName: "Circle" },
+      { name: "VERSION", crate: "facade_core", module: [], rustName: "VERSION" },
+      { name: "circle_area", crate: "facade_core", module: [], rustName: "area_of" },
+    ]);
+    expect(root!.structs.map((st) => [st.name, st.fields.map((f) => f.name), st.implementedTraits])).to.deep.equal([
+      ["Round", ["radius"], ["Scale"]],
+    ]);
+    expect(root!.functions.map((f) => f.name)).to.deep.equal(["circle_area"]);
+    expect(root!.pendingMethods.map((p) => [p.target, p.trait, p.methods.map((m) => m.name)])).to.deep.equal([
+      ["Round", undefined, ["new", "area"]],
+      ["Round", "Scale", ["scale"]],
+    ]);
+    // Imported module paths are followed; names the dependency does not define stay foreign.
+    expect(geometry!.reexports.map((r) => [r.name, r.foreign])).to.deep.equal([["Missing", true]]);
+    expect(geometry!.dependencyItems!.map((item) => [item.name, item.module])).to.deep.equal([
+      ["Circle", ["shapes"]],
+      ["Scale", ["shapes"]],
+      ["Shape", ["shapes"]],
+    ]);
+    expect(geometry!.structs.map((st) => st.name)).to.deep.equal(["Canvas", "Circle"]);
+    expect(geometry!.enums.map((e) => e.name)).to.deep.equal(["Shape"]);
+    expect(geometry!.traits.map((t) => t.name)).to.deep.equal(["Scale"]);
+
+    const file = runExtractor(["--with-deps", "--file", join(dirname(manifest), "src", "lib.rs")]);
+    expect(file.status).to.equal(1);
+    expect(file.stderr).to.include("Option --with-deps requires a manifest path");
+  });
+
+  it("records serde enum tagging with --serde-aware", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "#[derive(Serialize)]",
+        "pub enum External {",
+        "    A(u32),",
+        "}",
+        "",
+        '#[serde(tag = "type")]',
+        "pub enum Internal {",
+        "    A { x: u32 },",
+        "}",
+        "",
+        "#[derive(Serialize)]",
+        '#[serde(tag = "t", content = "c", rename_all = "camelCase")]',
+        "pub enum Adjacent {",
+        "    A(u32),",
+        "}",
+        "",
+        "#[serde(deny_unknown_fields)]",
+        "#[serde(untagged)]",
+        "pub enum Untagged {",
+        "    A(u32),",
+        "    B(String),",
+        "}",
+        "",
+      ].join("\n"),
+    });
+    expect(extract([manifest]).modules[0]!.enums.map((e) => e.serdeTagging)).to.deep.equal([
+      undefined,
+      undefined,
+      undefined,
+      undefined,
+    ]);
+    const enums = extract(["--serde-aware", manifest]).modules[0]!.enums;
+    expect(enums.map((e) => [e.name, e.serdeTagging])).to.deep.equal([
+      ["External", { mode: "external" }],
+      ["Internal", { mode: "internal", tag: "type" }],
+      ["Adjacent", { mode: "adjacent", tag: "t", content: "c" }],
+      ["Untagged", { mode: "untagged" }],
+    ]);
+  });
+
+  it("collects every unresolved module and exits non-zero with --require-all-modules", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": ["pub mod gone;", "pub mod present;", "pub fn root() {}", ""].join("\n"),
+      "src/present.rs": ["pub mod missing;", "pub fn here() {}", ""].join("\n"),
+    });
+    const failFast = runExtractor([manifest]);
+    expect(failFast.status).to.equal(1);
+    expect(failFast.stdout).to.equal("");
+    expect(failFast.stderr).to.include("Could not resolve pub mod 'gone'");
+
+    const result = runExtractor(["--require-all-modules", manifest]);
+    expect(result.status).to.equal(1);
+    const output = JSON.parse(result.stdout) as ExtractedOutput;
+    expect(output.modules.map((m) => [m.parts.join("::"), m.functions.map((f) => f.name)])).to.deep.equal([
+      ["", ["root"]],
+      ["gone", []],
+      ["present", ["here"]],
+      ["present::missing", []],
+    ]);
+    expect(output.modules.flatMap((m) => m.issues.map((i) => [i.file, i.code, i.snippet, i.reason]))).to.deep.equal([
+      ["src/lib.rs", "unresolved_module", "mod gone", "Module 'gone' has no file (src/gone.rs or src/gone/mod.rs); declarations were not extracted."],
+      [
+        "src/present.rs",
+        "unresolved_module",
+        "mod missing",
+        "Module 'present::missing' has no file (src/present/missing.rs or src/present/missing/mod.rs); declarations were not extracted.",
+      ],
+    ]);
+    expect(result.stderr).to.include("2 module declaration(s) could not be resolved (--require-all-modules):");
+    expect(result.stderr).to.include("src/present.rs: Module 'present::missing' has no file");
+
+    const files = runExtractor(["--require-all-modules", "--list-files", manifest]);
+    expect(files.status).to.equal(1);
+    expect(files.stdout).to.equal("src/lib.rs\nsrc/present.rs\n");
+  });
+
+  it("extracts macro_rules macros without #[macro_export] under --include-private", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "macro_rules! local_one { () => {}; }",
+        "macro_rules! crate_wide { () => {}; }",
+        "pub(crate) use crate_wide;",
+        "#[macro_export]",
+        "macro_rules! exported { () => {}; }",
+        "pub fn root() {}",
+        "",
+      ].join("\n"),
+    });
+    const names = (args: readonly string[]) =>
+      extract(args).modules[0]!.functions.map((f) => [f.name, f.kind, f.visibility ?? null]);
+    expect(names([manifest])).to.deep.equal([
+      ["exported", "macro", null],
+      ["root", "fn", null],
+    ]);
+    expect(names(["--include-private", manifest])).to.deep.equal([
+      ["local_one", "macro", "local"],
+      ["crate_wide", "macro", "pub(crate)"],
+      ["exported", "macro", null],
+      ["root", "fn", null],
+    ]);
+
+    const nested = writeCrate({
+      "src/lib.rs": "pub mod inner;\n",
+      "src/inner.rs": [
+        "macro_rules! via_self { () => {}; }",
+        "pub(crate) use self::via_self;",
+        "macro_rules! via_crate { () => {}; }",
+        "pub(super) use crate::inner::via_crate;",
+        "macro_rules! elsewhere { () => {}; }",
+        "pub(crate) use crate::elsewhere;",
+        "",
+      ].join("\n"),
+    });
+    expect(
+      extract(["--include-private", nested]).modules[1]!.functions.map((f) => [f.name, f.visibility]),
+    ).to.deep.equal([
+      ["via_self", "pub(crate)"],
+      ["via_crate", "pub(super)"],
+      ["elsewhere", "local"],
+    ]);
+  });
+
+  it("records the declared features and the default set in the crate metadata", () => {
+    const manifest = writeCrate({
+      "Cargo.toml": [
+        "[package]",
+        'name = "featured"',
+        'version = "0.1.0"',
+        "",
+        "[dependencies]",
+        'serde = { version = "1", optional = true }',
+        "",
+        "[features]",
+        'default = ["std"]',
+        'std = ["alloc"]',
+        "alloc = []",
+        'serde = ["dep:serde", "std"]',
+        "",
+      ].join("\n"),
+      "src/lib.rs": "pub fn root() {}\n",
+    });
+    const expected = {
+      name: "featured",
+      features: { alloc: [], default: ["std"], serde: ["dep:serde", "std"], std: ["alloc"] },
+      defaultFeatures: ["alloc", "std"],
+    };
+    expect(extract([manifest]).crate).to.deep.equal(expected);
+    expect(extract(["--no-default-features", "--feature", "serde", manifest]).crate).to.deep.equal(expected);
+
+    const plain = writeCrate({ "src/lib.rs": "pub fn root() {}\n" });
+    expect(extract([plain]).crate).to.deep.equal({ name: "extractor_case", package: "extractor-case" });
+  });
+
+  it("qualifies trait-impl methods that share a name with an inherent method", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": [
+        "pub trait Reset { fn reset(&mut self); fn clear(&mut self); }",
+        "pub struct Counter { count: u32 }",
+        "impl Counter { pub fn reset(&mut self) {} pub fn value(&self) -> u32 { self.count } }",
+        "impl Reset for Counter { fn reset(&mut self) {} fn clear(&mut self) {} }",
+        "impl Reset for &Counter { fn reset(&mut self) {} fn clear(&mut self) {} }",
+        "",
+      ].join("\n"),
+    });
+    const root = extract([manifest]).modules[0]!;
+    expect(
+      root.pendingMethods.map((p) => [p.trait ?? null, p.targetReference ?? null, p.methods.map((m) => [m.name, m.qualifiedId ?? null])])
+    ).to.deep.equal([
+      [null, null, [["reset", null], ["value", null]]],
+      ["Reset", null, [["reset", "<Counter as Reset>::reset"], ["clear", null]]],
+      ["Reset", "&", [["reset", null], ["clear", null]]],
+    ]);
+    expect(root.issues.map((i) => [i.kind, i.code, i.snippet, i.reason])).to.deep.equal([
+      [
+        "impl",
+        "method_name_collision",
+        "Counter::reset",
+        "Method 'reset' in impl Reset for 'Counter' has the same name as an inherent method; it is recorded as '<Counter as Reset>::reset'.",
+      ],
+    ]);
+  });
+
+  it("caps the recorded issues with --max-issues", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": ["pub mod shapes;", "pub mod gone;", "pub struct First(u8);", "pub struct Second(u8);", ""].join("\n"),
+      "src/shapes.rs": ["pub struct Third(u8);", "pub struct Fourth(u8);", ""].join("\n"),
+    });
+    const summary = (output: ExtractedOutput) =>
+      output.modules.map((m) => [m.parts.join("::"), m.issues.map((i) => [i.file, i.code ?? i.snippet])]);
+    const all = JSON.parse(runExtractor(["--require-all-modules", manifest]).stdout) as ExtractedOutput;
+    expect(all.modules.flatMap((m) => m.issues).length).to.equal(5);
+
+    const capped = runExtractor(["--require-all-modules", "--max-issues", "1", manifest]);
+    expect(capped.status).to.equal(1);
+    expect(capped.stderr).to.include("1 module declaration(s) could not be resolved");
+    const output = JSON.parse(capped.stdout) as ExtractedOutput;
+    expect(summary(output)).to.deep.equal([
+      [
+        "",
+        [
+          ["src/lib.rs", "First"],
+          ["src/lib.rs", "issues_suppressed"],
+        ],
+      ],
+      ["gone", [["src/lib.rs", "unresolved_module"]]],
+      ["shapes", []],
+    ]);
+    const marker = output.modules[0]!.issues[1]!;
+    expect([marker.kind, marker.snippet, marker.reason]).to.deep.equal([
+      "issues",
+      "--max-issues 1",
+      "3 more issue(s) suppressed by --max-issues 1.",
+    ]);
+
+    const codes = (args: readonly string[]) =>
+      (JSON.parse(runExtractor(["--require-all-modules", ...args, manifest]).stdout) as ExtractedOutput).modules.flatMap((m) =>
+        m.issues.map((i) => i.code ?? null)
+      );
+    expect(codes(["--max-issues", "0"])).to.deep.equal(["issues_suppressed", "unresolved_module"]);
+    expect(codes(["--max-issues", "10"])).to.deep.equal([null, null, "unresolved_module", null, null]);
+
+    const invalid = runExtractor(["--max-issues", "-1", manifest]);
+    expect(invalid.status).to.equal(1);
+    expect(invalid.stderr).to.include("Invalid --max-issues '-1' (expected a non-negative issue count).");
+  });
+
+  it("reuses the --out document with --since until a module file or the manifest changes", () => {
+    const manifest = writeCrate({
+      "src/lib.rs": "pub mod shapes;\npub fn root() {}\n",
+      "src/shapes.rs": "pub struct Circle;\n",
+      "tests/smoke.rs": "#[test]\nfn smoke() {}\n",
+    });
+    const crateDir = dirname(manifest);
+    gitIn(crateDir, ["init", "-q"]);
+    gitIn(crateDir, ["add", "-A"]);
+    gitIn(crateDir, ["commit", "-q", "-m", "base"]);
+    const out = join(crateDir, "bindings.json");
+    const since = (extra: readonly string[]) => {
+      const result = runExtractor(["--since", "HEAD", "--out", out, ...extra, manifest]);
+      expect(result.status, result.stderr).to.equal(0);
+      return result.stderr;
+    };
+    const structs = () =>
+      (JSON.parse(readFileSync(out, "utf-8")) as ExtractedOutput).modules.flatMap((m) => m.structs.map((st) => st.name));
+
+    expect(since([])).to.include("since: re-extracted (no cached output for these options)");
+    const written = readFileSync(out, "utf-8");
+    expect(since([])).to.include(`since: reused ${out} (no module file changed since HEAD)`);
+    expect(readFileSync(out, "utf-8")).to.equal(written);
+    expect(since(["--stats"])).to.include("since: reused");
+
+    writeFileSync(join(crateDir, "tests", "smoke.rs"), "#[test]\nfn smoke_again() {}\n");
+    expect(since([])).to.include("since: reused");
+
+    writeFileSync(join(crateDir, "src", "shapes.rs"), "pub struct Circle;\npub struct Square;\n");
+    expect(since([])).to.include("since: re-extracted (src/shapes.rs changed since HEAD); parsed 1 of 2 module files");
+    expect(structs()).to.deep.equal(["Circle", "Square"]);
+    const full = join(crateDir, "full.json");
+    expect(runExtractor(["--out", full, manifest]).status).to.equal(0);
+    expect(readFileSync(out, "utf-8")).to.equal(readFileSync(full, "utf-8"));
+    gitIn(crateDir, ["commit", "-q", "-a", "-m", "square"]);
+    expect(since([])).to.include("since: reused");
+
+    writeFileSync(join(crateDir, "src", "extra.rs"), "pub fn extra() {}\n");
+    expect(since([])).to.include("since: re-extracted (src/extra.rs changed since HEAD)");
+    rmSync(join(crateDir, "src", "extra.rs"));
+
+    expect(since(["--infer-properties"])).to.include("since: re-extracted (no cached output for these options)");
+    expect(since(["--infer-properties"])).to.include("since: reused");
+
+    appendFileSync(manifest, "\n[features]\nfast = []\n");
+    expect(since(["--infer-properties"])).to.include("since: re-extracted (Cargo.toml changed since HEAD)");
+
+    const badRef = runExtractor(["--since", "no-such-ref", "--out", out, "--infer-properties", manifest]);
+    expect(badRef.status).to.equal(1);
+    expect(badRef.stderr).to.include("--since: git diff --name-only --relative no-such-ref --:");
+    const noOut = runExtractor(["--since", "HEAD", manifest]);
+    expect(noOut.status).to.equal(1);
+    expect(noOut.stderr).to.include("Option --since requires --out.");
+  });
+
+  it("re-extracts with --since when a module file outside the crate directory changed", () => {
+    const outsideDir = mkdtempSync(join(tmpdir(), "tsubabindgen-extractor-outside-"));
+    const outside = join(outsideDir, "away.rs");
+    writeFileSync(outside, "pub fn away() {}\n");
+    const manifest = writeCrate({ "src/lib.rs": "pub mod away;\npub fn root() {}\n" });
+    const crateDir = dirname(manifest);
+    symlinkSync(outside, join(crateDir, "src", "away.rs"));
+    gitIn(crateDir, ["init", "-q"]);
+    gitIn(crateDir, ["add", "-A"]);
+    gitIn(crateDir, ["commit", "-q", "-m", "base"]);
+    const out = join(crateDir, "bindings.json");
+    const since = () => {
+      const result = runExtractor(["--since", "HEAD", "--out", out, manifest]);
+      expect(result.status, result.stderr).to.equal(0);
+      return result.stderr;
+    };
+
+    expect(since()).to.include("since: re-extracted (no cached output for these options)");
+    expect(since()).to.include("since: reused");
+
+    appendFileSync(outside, "pub fn back() {}\n");
+    expect(since()).to.include(
+      `since: re-extracted (${realpathSync(outside)} changed since the cached run); parsed 1 of 2 module files`
+    );
+    expect(readFileSync(out, "utf-8")).to.include('"back"');
+    expect(since()).to.include("since: reused");
+  });
+
+  it("does not reuse a --since document after a plain --out run rewrote it", () => {
+    const manifest = writeCrate({ "src/lib.rs": "pub fn root() {}\n" });
+    const crateDir = dirname(manifest);
+    gitIn(crateDir, ["init", "-q"]);
+    gitIn(crateDir, ["add", "-A"]);
+    gitIn(crateDir, ["commit", "-q", "-m", "base"]);
+    const out = join(crateDir, "o.json");
+    const run = (args: readonly string[]) => {
+      const result = runExtractor([...args, "--out", out, manifest]);
+      expect(result.status, result.stderr).to.equal(0);
+      return result.stderr;
+    };
+    const rootParts = () => (JSON.parse(readFileSync(out, "utf-8")) as ExtractedOutput).modules[0]!.parts;
+
+    expect(run(["--since", "HEAD", "--root-module-name", "api"])).to.include("since: re-extracted");
+    expect(existsSync(`${out}.since`)).to.equal(true);
+    run([]);
+    expect(existsSync(`${out}.since`)).to.equal(false);
+    expect(rootParts()).to.deep.equal([]);
+    expect(run(["--since", "HEAD", "--root-module-name", "api"])).to.include(
+      "since: re-extracted (no cached output for these options)",
+    );
+    expect(rootParts()).to.deep.equal(["api"]);
+  });
+});
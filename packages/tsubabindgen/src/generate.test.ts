@@ -14,7 +14,7 @@ describe("@tsuba/tsubabindgen", () => {
   }
 
   function runTempFixture(options: {
-    readonly fixture: "simple" | "traits" | "advanced" | "edge" | "reexports" | "parse-failure";
+    readonly fixture: "simple" | "traits" | "advanced" | "edge" | "reexports" | "renames" | "parse-failure";
     readonly packageName?: string;
     readonly bundleCrate?: boolean;
   }): string {
@@ -256,6 +256,33 @@ describe("@tsuba/tsubabindgen", () => {
         (entry) => entry.kind === "reexport" && entry.phase === "resolve" && entry.code === "TBB2000"
       )
     ).to.equal(true);
+    expect(
+      report.skipped.some(
+        (entry) =>
+          entry.kind === "reexport" &&
+          entry.reason.includes("external crate item") &&
+          entry.file === "src/lib.rs"
+      )
+    ).to.equal(true);
+  });
+
+  it("declares items under their #[tsuba(rename)] export names", () => {
+    const out = runTempFixture({ fixture: "renames", packageName: "@tsuba/renames", bundleCrate: false });
+    const rootDts = read(join(out, "index.d.ts"));
+    const shapesDts = read(join(out, "shapes.d.ts"));
+
+    expect(rootDts).to.include("export const LIMIT: f64;");
+    expect(rootDts).to.include("export function unitRadius(): f64;");
+    expect(rootDts).to.include("export declare class Disc {");
+    expect(rootDts).to.not.include("MAX_RADIUS");
+    expect(rootDts).to.not.include("unit_radius");
+    expect(rootDts).to.not.include("class Circle");
+
+    expect(shapesDts).to.include("export declare class Disc {");
+    expect(shapesDts).to.include("r: f64;");
+    expect(shapesDts).to.include("constructor(radius: f64);");
+    expect(shapesDts).to.include("surface(): f64;");
+    expect(shapesDts).to.not.include("area(");
   });
 
   it("records Rust parser failures as skip issues without failing generation", () => {
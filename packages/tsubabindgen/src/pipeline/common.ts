@@ -4,6 +4,7 @@ export const MANIFEST_TEMPLATE = `// Generated by tsubabindgen (v0)
 throw new Error("This package is type-only and intended to be compiled by Tsuba, not executed in Node.");
 `;
 
+// Declared with a trailing `_`. Keep in sync with FACADE_RENAMED_NAMES in rust-extractor/src/lib.rs.
 const JS_KEYWORDS = new Set([
   "class",
   "delete",
@@ -75,9 +76,12 @@ export type GenerateBindings = {
 
 export type RustType = string;
 
+// `name` is the declared TS name (the `#[tsuba(rename = ...)]` export name when set); `rustName`
+// keeps the Rust identifier, when it differs, for impl targets and `pub use` paths to match.
 export type RustField = {
   readonly kind: "field";
   readonly name: string;
+  readonly rustName?: string;
   readonly type: RustType;
 };
 
@@ -86,6 +90,7 @@ export type RustFunctionKind = "fn" | "macro" | "attr_macro" | "derive";
 export type RustFunction = {
   readonly kind: RustFunctionKind;
   readonly name: string;
+  readonly rustName?: string;
   readonly typeParams: readonly string[];
   readonly params: readonly RustField[];
   readonly returnType: RustType;
@@ -110,6 +115,7 @@ export type RustTraitMethod = {
 
 export type RustTrait = {
   readonly name: string;
+  readonly rustName?: string;
   readonly typeParams: readonly string[];
   readonly superTraits: readonly RustType[];
   readonly methods: readonly RustTraitMethod[];
@@ -118,10 +124,14 @@ export type RustTrait = {
 export type RustReexport = {
   readonly name: string;
   readonly source: string;
+  readonly sourceModule?: readonly string[];
+  readonly sourceName?: string;
+  readonly foreign?: boolean;
 };
 
 export type RustStruct = {
   readonly name: string;
+  readonly rustName?: string;
   readonly typeParams: readonly string[];
   readonly fields: readonly RustField[];
   methods: RustMethod[];
@@ -130,6 +140,7 @@ export type RustStruct = {
 
 export type RustEnum = {
   readonly name: string;
+  readonly rustName?: string;
   readonly typeParams: readonly string[];
   readonly variants: readonly {
     readonly name: string;
@@ -151,35 +162,242 @@ export type ParsedModule = {
   issues: SkipIssue[];
 };
 
-export type ExtractedPendingMethods = {
-  readonly target: string;
-  readonly methods: readonly RustFunction[];
+// The Rust extractor's modules document (schema 1). Fields marked optional are left out of the
+// JSON when empty, false, or unset.
+
+export type ExtractedSpan = {
+  readonly sourceFile: string;
+  readonly byteStart: number;
+  readonly byteEnd: number;
+  readonly lineStart: number;
+  readonly lineEnd: number;
+};
+
+export type ExtractedTypeBinding = {
+  readonly name: string;
+  readonly args?: readonly ExtractedTypeRepr[];
+  readonly lifetimes?: readonly string[];
+  readonly type: ExtractedTypeRepr;
+};
+
+export type ExtractedPath = {
+  readonly path: readonly string[];
+  readonly args?: readonly ExtractedTypeRepr[];
+  readonly bindings?: readonly ExtractedTypeBinding[];
+  readonly lifetimes?: readonly string[];
+  readonly otherArgs?: readonly string[];
+};
+
+// `kind` names the shape (`path`, `reference`, `array`, `assoc_type`, ...); the other fields
+// belong to the shapes that use them.
+export type ExtractedTypeRepr = Partial<ExtractedPath> & {
+  readonly kind: string;
+  readonly mutable?: boolean;
+  readonly lifetime?: string;
+  readonly inner?: ExtractedTypeRepr;
+  readonly elements?: readonly ExtractedTypeRepr[];
+  readonly len?: string;
+  readonly constParam?: string;
+  readonly params?: readonly ExtractedTypeRepr[];
+  readonly returnType?: ExtractedTypeRepr;
+  readonly bounds?: readonly ExtractedPath[];
+  readonly name?: string;
+  readonly trait?: ExtractedPath;
+  readonly text?: string;
+  readonly wrappers?: readonly string[];
+};
+
+export type ExtractedGenericParam = {
+  readonly kind: "lifetime" | "type" | "const";
+  readonly name: string;
+  readonly type?: string;
+  readonly default?: string;
+};
+
+export type ExtractedGenericBound = {
+  readonly type: string;
+  readonly bounds: readonly string[];
+  readonly paths?: readonly ExtractedPath[];
+};
+
+export type ExtractedGenerics = {
+  readonly typeParams: readonly string[];
+  readonly lifetimes?: readonly string[];
+  readonly generics?: readonly ExtractedGenericParam[];
+  readonly totalGenericArity?: number;
+  readonly unrepresentableGenerics?: readonly string[];
+  readonly genericDefaults?: readonly { readonly type: string; readonly default: string }[];
+};
+
+export type ExtractedItemInfo = {
+  readonly exportName?: string;
+  readonly docAliases?: readonly string[];
+  readonly attributes?: readonly string[];
+  readonly examples?: readonly string[];
+};
+
+export type ExtractedField = Partial<ExtractedSpan> &
+  ExtractedItemInfo & {
+    readonly name: string;
+    readonly docs?: string;
+    readonly generics?: readonly ExtractedGenericParam[];
+    readonly type: string;
+    readonly genericBase?: string;
+    readonly genericArgs?: readonly string[];
+    readonly typeRepr?: ExtractedTypeRepr;
+    readonly optional?: boolean;
+    readonly value?: string;
+    readonly isLiteral?: boolean;
+  };
+
+export type ExtractedFunction = ExtractedSpan &
+  ExtractedItemInfo &
+  ExtractedGenerics & {
+    readonly kind: RustFunctionKind;
+    readonly name: string;
+    readonly fnAttrs?: readonly string[];
+    readonly constParams?: readonly { readonly name: string; readonly type: string }[];
+    readonly params: readonly ExtractedField[];
+    readonly returnType: string;
+    readonly returnGenericBase?: string;
+    readonly returnGenericArgs?: readonly string[];
+    readonly returnTypeRepr?: ExtractedTypeRepr;
+    readonly consumesSelf: boolean;
+    readonly isConstructor: boolean;
+    readonly isAsync: boolean;
+    readonly variadic?: boolean;
+    readonly futureOutput?: string;
+    readonly futureOutputRepr?: ExtractedTypeRepr;
+    readonly isGeneric: boolean;
+    readonly genericBounds?: readonly ExtractedGenericBound[];
+    readonly implements?: { readonly trait: string; readonly method: string };
+    readonly visibility?: string;
+    readonly qualifiedId?: string;
+    readonly callPath?: string;
+  };
+
+export type ExtractedStruct = ExtractedSpan &
+  ExtractedItemInfo &
+  ExtractedGenerics & {
+    readonly kind: string;
+    readonly name: string;
+    readonly fields: readonly ExtractedField[];
+    readonly skippedPrivateFields: number;
+    readonly hasDefault: boolean;
+    readonly implementedTraits?: readonly string[];
+    readonly recursive?: boolean;
+    readonly opaque?: boolean;
+    readonly transparentInner?: ExtractedField;
+    readonly derivedProperties?: readonly {
+      readonly name: string;
+      readonly getter: string;
+      readonly type: string;
+      readonly typeRepr?: ExtractedTypeRepr;
+    }[];
+  };
+
+export type ExtractedEnum = ExtractedSpan &
+  ExtractedItemInfo &
+  ExtractedGenerics & {
+    readonly name: string;
+    readonly variants: readonly {
+      readonly name: string;
+      readonly docs?: string;
+      readonly fields: readonly ExtractedField[];
+    }[];
+    readonly isCLike: boolean;
+    readonly serdeTagging?: { readonly mode: string; readonly tag?: string; readonly content?: string };
+    readonly hasDefault: boolean;
+    readonly implementedTraits?: readonly string[];
+    readonly recursive?: boolean;
+    readonly opaque?: boolean;
+  };
+
+export type ExtractedAssocType = ExtractedSpan & {
+  readonly name: string;
+  readonly bounds: readonly string[];
+  readonly default?: string;
+  readonly generics?: readonly ExtractedGenericParam[];
+  readonly genericBounds?: readonly ExtractedGenericBound[];
 };
 
+export type ExtractedTrait = ExtractedSpan &
+  ExtractedItemInfo &
+  ExtractedGenerics & {
+    readonly name: string;
+    readonly superTraits: readonly string[];
+    readonly superTraitPaths?: readonly ExtractedPath[];
+    readonly assocTypes: readonly ExtractedAssocType[];
+    readonly consts?: readonly ExtractedField[];
+    readonly methods: readonly ExtractedFunction[];
+    readonly isMarker: boolean;
+  };
+
+export type ExtractedReexport = {
+  readonly name: string;
+  readonly source: string;
+  readonly sourceModule?: readonly string[];
+  readonly sourceName?: string;
+  readonly foreign?: boolean;
+  readonly visibility?: string;
+};
+
+export type ExtractedPendingMethods = Partial<ExtractedSpan> &
+  ExtractedGenerics & {
+    readonly target: string;
+    readonly targetReference?: string;
+    readonly targetGenericArgs?: readonly string[];
+    readonly genericBounds?: readonly ExtractedGenericBound[];
+    readonly trait?: string;
+    readonly traitGenericBase?: string;
+    readonly traitGenericArgs?: readonly string[];
+    readonly methods: readonly ExtractedFunction[];
+    readonly consts?: readonly ExtractedField[];
+    readonly types?: readonly ExtractedField[];
+    readonly docs?: string;
+  };
+
 export type ExtractedModule = {
   readonly file: string;
   readonly parts: readonly string[];
-  readonly consts: readonly RustField[];
-  readonly enums: readonly RustEnum[];
-  readonly structs: readonly RustStruct[];
-  readonly traits: readonly RustTrait[];
-  readonly functions: readonly RustFunction[];
-  readonly reexports?: readonly {
-    readonly name: string;
-    readonly source: string;
-  }[];
+  readonly consts: readonly ExtractedField[];
+  readonly enums: readonly ExtractedEnum[];
+  readonly structs: readonly ExtractedStruct[];
+  readonly traits: readonly ExtractedTrait[];
+  readonly functions: readonly ExtractedFunction[];
+  readonly reexports: readonly ExtractedReexport[];
   readonly pendingMethods: readonly ExtractedPendingMethods[];
   readonly issues: readonly SkipIssue[];
+  readonly flattened?: readonly { readonly name: string; readonly module: readonly string[]; readonly rustName: string }[];
+  readonly dependencyItems?: readonly {
+    readonly name: string;
+    readonly crate: string;
+    readonly module: readonly string[];
+    readonly rustName: string;
+  }[];
+  readonly prelude?: boolean;
 };
 
 export type ExtractedOutput = {
   readonly schema: number;
+  readonly contentHash?: string;
+  readonly crate: {
+    readonly name: string;
+    readonly package?: string;
+    readonly prelude?: readonly string[];
+    readonly features?: Readonly<Record<string, readonly string[]>>;
+    readonly defaultFeatures?: readonly string[];
+  };
   readonly modules: readonly ExtractedModule[];
+  readonly index?: Readonly<
+    Record<string, readonly (ExtractedSpan & { readonly module: readonly string[]; readonly kind: string })[]>
+  >;
 };
 
 export type SkipIssue = {
   readonly file: string;
   readonly kind: string;
+  readonly code?: string;
   readonly snippet: string;
   readonly reason: string;
 };
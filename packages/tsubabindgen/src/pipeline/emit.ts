@@ -220,6 +220,18 @@ function classifySkipIssue(kind: string): { readonly phase: "extract" | "resolve
   switch (kind) {
     case "parse":
       return { phase: "extract", code: "TBB1000" };
+    case "skip":
+      return { phase: "extract", code: "TBB1001" };
+    case "module":
+      return { phase: "extract", code: "TBB1002" };
+    case "cfg":
+      return { phase: "extract", code: "TBB1003" };
+    case "crate":
+      return { phase: "extract", code: "TBB1004" };
+    case "name":
+      return { phase: "extract", code: "TBB1005" };
+    case "issues":
+      return { phase: "extract", code: "TBB1006" };
     case "reexport":
       return { phase: "resolve", code: "TBB2000" };
     case "generic":
@@ -4,6 +4,7 @@ import { basename, dirname, join, resolve } from "node:path";
 import { fileURLToPath } from "node:url";
 
 import type {
+  ExtractedFunction,
   ExtractedModule,
   ExtractedOutput,
   ParsedModule,
@@ -68,10 +69,19 @@ function collectModulesLegacy(manifestPath: string): ParsedModule[] {
   return out;
 }
 
-function mapExtractedFunction(fn: RustFunction, file: string, issues: SkipIssue[]): RustFunction {
+function declaredName(item: { readonly name: string; readonly exportName?: string }): {
+  readonly name: string;
+  readonly rustName?: string;
+} {
+  const name = normalizeIdentifier(item.exportName ?? item.name);
+  const rustName = normalizeIdentifier(item.name);
+  return name === rustName ? { name } : { name, rustName };
+}
+
+function mapExtractedFunction(fn: ExtractedFunction, file: string, issues: SkipIssue[]): RustFunction {
   return {
     kind: fn.kind ?? "fn",
-    name: normalizeIdentifier(fn.name),
+    ...declaredName(fn),
     typeParams: [...(fn.typeParams ?? [])].map((p) => normalizeIdentifier(p)),
     params: fn.params.map((p) => ({
       kind: "field",
@@ -86,11 +96,11 @@ function mapExtractedModule(module: ExtractedModule): ParsedModule {
   const issues: SkipIssue[] = [...module.issues];
   const source = module.file;
   const structs = module.structs.map((s) => ({
-    name: normalizeIdentifier(s.name),
+    ...declaredName(s),
     typeParams: [...(s.typeParams ?? [])].map((p) => normalizeIdentifier(p)),
     fields: s.fields.map((f) => ({
       kind: "field" as const,
-      name: normalizeIdentifier(f.name),
+      ...declaredName(f),
       type: parseType(f.type, source, issues),
     })),
     methods: [],
@@ -102,28 +112,28 @@ function mapExtractedModule(module: ExtractedModule): ParsedModule {
     moduleParts: [...module.parts],
     consts: module.consts.map((c) => ({
       kind: "field",
-      name: normalizeIdentifier(c.name),
+      ...declaredName(c),
       type: parseType(c.type, source, issues),
     })),
     enums: module.enums.map((e) => ({
-      name: normalizeIdentifier(e.name),
+      ...declaredName(e),
       typeParams: [...(e.typeParams ?? [])].map((p) => normalizeIdentifier(p)),
       variants: e.variants.map((v) => ({
         name: normalizeIdentifier(v.name),
         fields: (v.fields ?? []).map((f) => ({
           kind: "field",
-          name: normalizeIdentifier(f.name),
+          ...declaredName(f),
           type: parseType(f.type, source, issues),
         })),
       })),
     })),
     structs,
     traits: module.traits.map((t) => ({
-      name: normalizeIdentifier(t.name),
+      ...declaredName(t),
       typeParams: [...(t.typeParams ?? [])].map((p) => normalizeIdentifier(p)),
       superTraits: t.superTraits.map((st) => parseType(st, source, issues)),
       methods: t.methods.map((m) => ({
-        name: normalizeIdentifier(m.name),
+        name: normalizeIdentifier(m.exportName ?? m.name),
         typeParams: [...(m.typeParams ?? [])].map((p) => normalizeIdentifier(p)),
         params: m.params.map((p) => ({
           kind: "field",
@@ -135,7 +145,13 @@ function mapExtractedModule(module: ExtractedModule): ParsedModule {
     })),
     functions: module.functions.map((f) => mapExtractedFunction(f, source, issues)),
     reexports: [...(module.reexports ?? [])]
-      .map((r) => ({ name: normalizeIdentifier(r.name), source: normalizeTypeText(r.source) }))
+      .map((r) => ({
+        name: normalizeIdentifier(r.name),
+        source: normalizeTypeText(r.source),
+        ...(r.sourceModule ? { sourceModule: r.sourceModule.map((p) => normalizeIdentifier(p)) } : {}),
+        ...(r.sourceName ? { sourceName: normalizeIdentifier(r.sourceName) } : {}),
+        ...(r.foreign ? { foreign: true } : {}),
+      }))
       .sort((a, b) => {
         const byName = compareText(a.name, b.name);
         if (byName !== 0) return byName;
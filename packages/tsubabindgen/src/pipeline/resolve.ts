@@ -5,6 +5,7 @@ import type {
   RustFunction,
   RustMethod,
   RustMethodKind,
+  RustReexport,
   RustStruct,
   RustTrait,
 } from "./common.js";
@@ -29,7 +30,7 @@ function methodKey(method: {
 export function attachMethods(modules: ParsedModule[]): void {
   const structByName = new Map<string, RustStruct>();
   for (const m of modules) {
-    for (const s of m.structs) structByName.set(s.name, s);
+    for (const s of m.structs) structByName.set(s.rustName ?? s.name, s);
   }
   for (const m of modules) {
     for (const [targetRaw, methods] of m.pendingMethods) {
@@ -148,8 +149,14 @@ function findModuleByParts(modules: readonly ParsedModule[], parts: readonly str
 function resolveReexportSourceModule(
   modules: readonly ParsedModule[],
   from: ParsedModule,
-  sourcePath: readonly string[]
+  reexport: RustReexport
 ): { readonly module: ParsedModule; readonly symbol: string } | undefined {
+  if (reexport.sourceModule && reexport.sourceName) {
+    // The extractor already followed self/super/crate prefixes and re-export chains.
+    const module = findModuleByParts(modules, reexport.sourceModule);
+    return module ? { module, symbol: reexport.sourceName } : undefined;
+  }
+  const sourcePath = reexport.source.split("::").filter((s) => s.length > 0);
   if (sourcePath.length === 0) return undefined;
   const [head, ...rest] = sourcePath;
   if (!head) return undefined;
@@ -179,41 +186,54 @@ function resolveReexportSourceModule(
   return { module, symbol };
 }
 
+function rustNamed<T extends { readonly name: string; readonly rustName?: string }>(
+  declarations: readonly T[],
+  symbol: string
+): T | undefined {
+  return declarations.find((d) => (d.rustName ?? d.name) === symbol);
+}
+
+// `exportedName` is undefined for a `pub use` without `as`, which keeps the source's declared name.
 function addReexportedDeclaration(
   toModule: ParsedModule,
   fromModule: ParsedModule,
   sourceSymbol: string,
-  exportedName: string
+  exportedName: string | undefined
 ): boolean {
-  if (hasDeclarationNamed(toModule, exportedName)) return true;
+  if (exportedName !== undefined && hasDeclarationNamed(toModule, exportedName)) return true;
 
-  const constDecl = fromModule.consts.find((d) => d.name === sourceSymbol);
+  const constDecl = rustNamed(fromModule.consts, sourceSymbol);
   if (constDecl) {
-    toModule.consts.push(cloneAsConst(constDecl, exportedName));
+    const name = exportedName ?? constDecl.name;
+    if (!hasDeclarationNamed(toModule, name)) toModule.consts.push(cloneAsConst(constDecl, name));
     return true;
   }
 
-  const enumDecl = fromModule.enums.find((d) => d.name === sourceSymbol);
+  const enumDecl = rustNamed(fromModule.enums, sourceSymbol);
   if (enumDecl) {
-    toModule.enums.push(cloneAsEnum(enumDecl, exportedName));
+    const name = exportedName ?? enumDecl.name;
+    if (!hasDeclarationNamed(toModule, name)) toModule.enums.push(cloneAsEnum(enumDecl, name));
     return true;
   }
 
-  const structDecl = fromModule.structs.find((d) => d.name === sourceSymbol);
+  const structDecl = rustNamed(fromModule.structs, sourceSymbol);
   if (structDecl) {
-    toModule.structs.push(cloneAsStruct(structDecl, exportedName));
+    const name = exportedName ?? structDecl.name;
+    if (!hasDeclarationNamed(toModule, name)) toModule.structs.push(cloneAsStruct(structDecl, name));
     return true;
   }
 
-  const traitDecl = fromModule.traits.find((d) => d.name === sourceSymbol);
+  const traitDecl = rustNamed(fromModule.traits, sourceSymbol);
   if (traitDecl) {
-    toModule.traits.push(cloneAsTrait(traitDecl, exportedName));
+    const name = exportedName ?? traitDecl.name;
+    if (!hasDeclarationNamed(toModule, name)) toModule.traits.push(cloneAsTrait(traitDecl, name));
     return true;
   }
 
-  const fnDecl = fromModule.functions.find((d) => d.name === sourceSymbol);
+  const fnDecl = rustNamed(fromModule.functions, sourceSymbol);
   if (fnDecl) {
-    toModule.functions.push(cloneAsFunction(fnDecl, exportedName));
+    const name = exportedName ?? fnDecl.name;
+    if (!hasDeclarationNamed(toModule, name)) toModule.functions.push(cloneAsFunction(fnDecl, name));
     return true;
   }
 
@@ -235,8 +255,16 @@ function sortModuleDeclarations(module: ParsedModule): void {
 export function applyReexports(modules: ParsedModule[]): void {
   for (const module of modules) {
     for (const reexport of module.reexports) {
-      const segments = reexport.source.split("::").filter((s) => s.length > 0);
-      const resolved = resolveReexportSourceModule(modules, module, segments);
+      if (reexport.foreign) {
+        module.issues.push({
+          file: module.source,
+          kind: "reexport",
+          snippet: `${reexport.name} <- ${reexport.source}`,
+          reason: "Re-export of an external crate item; there is no local declaration to mirror.",
+        });
+        continue;
+      }
+      const resolved = resolveReexportSourceModule(modules, module, reexport);
       if (!resolved) {
         module.issues.push({
           file: module.source,
@@ -246,7 +274,13 @@ export function applyReexports(modules: ParsedModule[]): void {
         });
         continue;
       }
-      const ok = addReexportedDeclaration(module, resolved.module, resolved.symbol, reexport.name);
+      const renamed = reexport.name !== normalizeIdentifier(resolved.symbol);
+      const ok = addReexportedDeclaration(
+        module,
+        resolved.module,
+        resolved.symbol,
+        renamed ? reexport.name : undefined
+      );
       if (!ok) {
         module.issues.push({
           file: module.source,
@@ -0,0 +1,53 @@
+#[derive(Clone, Copy)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    /// Number of coordinates.
+    pub const DIM: usize = 2;
+    pub const NAME: &'static str = "point";
+    pub const SCALE: f64 = -0.5;
+    pub const MASK: u32 = 1 << 4;
+    pub const CELLS: usize = Self::DIM * Self::DIM;
+    pub const ORIGIN: Self = Point::origin();
+    pub const UNIT: Self = Self { x: 1, y: 1 };
+    pub const CORNERS: [Self; 2] = [Self::ORIGIN, Self::UNIT];
+    pub const NEXT: Option<Self> = Some(Self::UNIT);
+    const HIDDEN: u8 = 0;
+
+    pub const fn origin() -> Self {
+        Point { x: 0, y: 0 }
+    }
+}
+
+pub struct Grid<T> {
+    pub cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub const EMPTY: Self = Self::new();
+    pub const WIDTH: u16 = (16);
+
+    pub const fn new() -> Self {
+        Grid { cells: Vec::new() }
+    }
+}
+
+pub trait Bounded {
+    const MIN: Self;
+    const MAX: Self;
+    const STEPS: u32 = 10;
+}
+
+impl Bounded for Point {
+    const MIN: Self = Self { x: i32::MIN, y: i32::MIN };
+    const MAX: Self = <Self as Bounded>::MIN;
+    const STEPS: u32 = 4;
+}
+
+impl Bounded for &Point {
+    const MIN: Self = &Point::ORIGIN;
+    const MAX: Self = &Point::UNIT;
+}
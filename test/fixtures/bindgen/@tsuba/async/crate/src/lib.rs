@@ -0,0 +1,35 @@
+pub struct Data {
+    pub id: u64,
+}
+
+pub trait Source {
+    async fn fetch(&self) -> Data;
+    async fn ping(&self);
+    fn name(&self) -> String;
+}
+
+pub struct Client {
+    pub base: String,
+}
+
+impl Client {
+    pub async fn connect(base: String) -> Result<Client, String> {
+        Ok(Client { base })
+    }
+}
+
+impl Source for Client {
+    async fn fetch(&self) -> Data {
+        Data { id: 1 }
+    }
+
+    async fn ping(&self) {}
+
+    fn name(&self) -> String {
+        self.base.clone()
+    }
+}
+
+pub async fn fetch_all(source: &Client) -> Vec<Data> {
+    vec![source.fetch().await]
+}
@@ -0,0 +1,45 @@
+pub trait Builder: Sized {
+    fn create() -> Self;
+
+    fn with(self, x: i32) -> Self;
+
+    fn twice(self, x: i32) -> Self {
+        self.with(x).with(x)
+    }
+
+    fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+
+    fn peek(&self) -> i32;
+}
+
+pub struct Config {
+    pub total: i32,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config { total: 0 }
+    }
+
+    pub fn reset(&mut self) -> &mut Self {
+        self.total = 0;
+        self
+    }
+}
+
+impl Builder for Config {
+    fn create() -> Self {
+        Config::new()
+    }
+
+    fn with(mut self, x: i32) -> Self {
+        self.total += x;
+        self
+    }
+
+    fn peek(&self) -> i32 {
+        self.total
+    }
+}
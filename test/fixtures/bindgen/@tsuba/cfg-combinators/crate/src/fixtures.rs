@@ -0,0 +1,3 @@
+pub fn sample() -> u64 {
+    7
+}
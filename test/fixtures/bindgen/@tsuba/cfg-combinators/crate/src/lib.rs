@@ -0,0 +1,78 @@
+#[cfg(not(test))]
+pub fn clock() -> u64 {
+    0
+}
+
+#[cfg(test)]
+pub fn clock() -> u64 {
+    42
+}
+
+#[cfg(test)]
+pub mod fixtures;
+
+#[cfg(all(test, feature = "fast"))]
+pub fn fast_test_only() {}
+
+#[cfg(any(test, feature = "fast"))]
+pub fn fast_or_test() {}
+
+#[cfg(any(test, feature = "slow"))]
+pub fn slow_or_test() {}
+
+#[cfg(all(not(test), any(feature = "fast", feature = "slow")))]
+pub struct Engine;
+
+#[cfg(not(any(test, feature = "slow")))]
+pub const MODE: &str = "fast";
+
+#[cfg(all(not(test), not(all(feature = "fast", not(feature = "slow")))))]
+pub const FALLBACK: bool = true;
+
+#[cfg(all(not(test), unix))]
+pub fn platform() {}
+
+#[cfg(any(test, unix))]
+pub fn platform_or_test() {}
+
+#[cfg(all())]
+pub fn always() {}
+
+#[cfg(any())]
+pub fn never() {}
+
+pub struct Gauge;
+
+impl Gauge {
+    pub fn read(&self) -> u32 {
+        0
+    }
+}
+
+#[cfg(feature = "fast")]
+impl Gauge {
+    pub fn read_fast(&self) -> u32 {
+        1
+    }
+}
+
+#[cfg(feature = "slow")]
+impl Gauge {
+    pub fn read_slow(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "slow")]
+impl Default for Gauge {
+    fn default() -> Self {
+        Gauge
+    }
+}
+
+#[cfg(all(feature = "fast", not(test)))]
+impl std::fmt::Display for Gauge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("gauge")
+    }
+}
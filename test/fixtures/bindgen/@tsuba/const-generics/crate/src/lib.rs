@@ -0,0 +1,48 @@
+pub fn make<const N: usize>() -> [u8; N] {
+    [0; N]
+}
+
+pub fn sum<const N: usize>(values: [i32; N]) -> i32 {
+    values.iter().sum()
+}
+
+pub fn split<T: Copy + Default, const ROWS: usize, const COLS: usize>(value: T) -> Option<[[T; COLS]; ROWS]> {
+    Some([[value; COLS]; ROWS])
+}
+
+pub fn fixed() -> [u8; 4] {
+    [0; 4]
+}
+
+pub struct Packet {
+    pub len: usize,
+}
+
+impl Packet {
+    pub fn header<const SIZE: usize>(&self) -> [u8; SIZE] {
+        [self.len as u8; SIZE]
+    }
+}
+
+pub trait Encode {
+    fn encode<const N: usize>(&self) -> [u8; N];
+}
+
+pub struct Window<'a, T, const N: usize = 8> {
+    pub items: &'a [T; N],
+}
+
+impl<'a, T, const N: usize> Window<'a, T, N> {
+    pub fn len(&self) -> usize {
+        N
+    }
+}
+
+pub enum Slot<T = u8, const N: usize = 2> {
+    Empty,
+    Full([T; N]),
+}
+
+pub trait Chunked<'a, const N: usize> {
+    fn chunk(&self) -> &'a [u8; N];
+}
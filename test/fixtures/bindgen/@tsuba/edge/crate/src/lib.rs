@@ -21,3 +21,14 @@ pub fn first<'a>(value: &'a str) -> &'a str {
 pub fn take_iter(value: impl Iterator<Item = i32>) -> i32 {
     value.count() as i32
 }
+
+pub fn annotated(#[allow(unused_variables)] value: i32, #[allow(unused_mut)] mut count: u32, _: bool) -> u32 {
+    count += 1;
+    count
+}
+
+pub struct Counter;
+
+impl Counter {
+    pub fn bump(#[allow(unused)] &self, #[allow(unused)] by: u32) {}
+}
@@ -0,0 +1,9 @@
+pub mod shapes;
+
+pub use shapes::Circle as Round;
+
+pub const VERSION: u32 = 1;
+
+pub fn area_of(circle: &shapes::Circle) -> f64 {
+    circle.area()
+}
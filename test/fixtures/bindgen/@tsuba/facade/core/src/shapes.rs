@@ -0,0 +1,28 @@
+pub struct Circle {
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(radius: f64) -> Self {
+        Circle { radius }
+    }
+
+    pub fn area(&self) -> f64 {
+        3.14 * self.radius * self.radius
+    }
+}
+
+pub enum Shape {
+    Circle(Circle),
+    Square(f64),
+}
+
+pub trait Scale {
+    fn scale(&mut self, by: f64);
+}
+
+impl Scale for Circle {
+    fn scale(&mut self, by: f64) {
+        self.radius *= by;
+    }
+}
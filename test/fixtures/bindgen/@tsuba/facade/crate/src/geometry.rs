@@ -0,0 +1,8 @@
+use facade_core::shapes;
+
+pub use facade_core::shapes::Missing;
+pub use shapes::{Circle, Scale, Shape};
+
+pub struct Canvas {
+    pub width: u32,
+}
@@ -0,0 +1,7 @@
+pub mod geometry;
+
+pub use facade_core::area_of as circle_area;
+pub use facade_core::shapes;
+pub use facade_core::Round;
+pub use facade_core::VERSION;
+pub use serde::Serialize;
@@ -0,0 +1,7 @@
+pub type Vector = crate::Point;
+
+impl Vector {
+    pub fn unit() -> Self {
+        crate::Point::new(1.0, 0.0)
+    }
+}
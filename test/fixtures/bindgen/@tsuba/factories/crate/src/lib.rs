@@ -0,0 +1,60 @@
+pub mod geometry;
+
+#[derive(Clone, Copy)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub const fn origin() -> Point {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let (x, y) = text.split_once(',')?;
+        Some(Point::new(x.parse().ok()?, y.parse().ok()?))
+    }
+
+    pub fn dimensions() -> usize {
+        2
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    pub fn into_pair(self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Point::origin()
+    }
+}
+
+pub struct Grid<T> {
+    pub cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new() -> Self {
+        Grid { cells: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Grid<T> {
+        Grid {
+            cells: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn boxed(self: Box<Self>) -> Self {
+        *self
+    }
+}
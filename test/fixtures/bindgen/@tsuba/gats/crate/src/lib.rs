@@ -0,0 +1,48 @@
+/// A lending container: `Iter` borrows from `self`.
+pub trait Container {
+    type Item;
+    type Iter<'a>: Iterator<Item = &'a Self::Item>
+    where
+        Self: 'a;
+
+    fn iter<'a>(&'a self) -> Self::Iter<'a>;
+}
+
+/// A type-parameterized GAT with a default-free bound, and a const-parameterized one.
+pub trait Family {
+    type Member<T: Clone>;
+    type Array<const N: usize>;
+
+    fn wrap<T: Clone>(value: T) -> Self::Member<T>;
+}
+
+/// A plain associated type stays a plain entry.
+pub trait Plain {
+    type Output;
+}
+
+pub struct Stack<T> {
+    pub items: Vec<T>,
+}
+
+impl<T> Container for Stack<T> {
+    type Item = T;
+    type Iter<'a> = std::slice::Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter<'a>(&'a self) -> Self::Iter<'a> {
+        self.items.iter()
+    }
+}
+
+pub struct OptionFamily;
+
+impl Family for OptionFamily {
+    type Member<T: Clone> = Option<T>;
+    type Array<const N: usize> = [u8; N];
+
+    fn wrap<T: Clone>(value: T) -> Option<T> {
+        Some(value)
+    }
+}
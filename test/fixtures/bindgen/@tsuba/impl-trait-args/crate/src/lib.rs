@@ -0,0 +1,41 @@
+use std::fmt::Display;
+
+pub trait Sink<T = i32> {
+    fn process(&self, items: impl IntoIterator<Item = T>) -> usize;
+
+    fn extend_where<F>(&mut self, items: impl Iterator<Item = T> + Clone, keep: F)
+    where
+        F: Fn(&T) -> bool;
+
+    fn gather<C: FromIterator<T>>(&self, items: &mut impl Iterator<Item = T>) -> C {
+        items.collect()
+    }
+
+    fn len(&self) -> usize;
+}
+
+pub struct Buffer<T = u8> {
+    pub items: Vec<T>,
+}
+
+impl<T> Buffer<T> {
+    pub fn append(&mut self, items: impl IntoIterator<Item = T>) {
+        self.items.extend(items);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.items.iter()
+    }
+}
+
+pub fn total(values: impl IntoIterator<Item = u32>) -> u32 {
+    values.into_iter().sum()
+}
+
+pub fn describe(label: Option<impl Display>) -> String {
+    label.map(|l| l.to_string()).unwrap_or_default()
+}
+
+pub fn evens(limit: u32) -> impl Iterator<Item = u32> {
+    (0..limit).filter(|n| n % 2 == 0)
+}
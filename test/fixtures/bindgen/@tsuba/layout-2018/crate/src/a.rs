@@ -0,0 +1,5 @@
+pub mod b;
+
+pub fn a_fn() -> i32 {
+    1
+}
@@ -0,0 +1,9 @@
+pub mod c;
+
+pub mod helpers {
+    pub mod format;
+}
+
+pub fn b_fn() -> i32 {
+    2
+}
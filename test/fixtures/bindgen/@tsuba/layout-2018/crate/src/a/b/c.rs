@@ -0,0 +1,5 @@
+pub mod d;
+
+pub fn c_fn() -> i32 {
+    3
+}
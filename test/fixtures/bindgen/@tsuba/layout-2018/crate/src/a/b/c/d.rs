@@ -0,0 +1,7 @@
+pub struct Leaf {
+    pub depth: u32,
+}
+
+pub fn d_fn() -> Leaf {
+    Leaf { depth: 4 }
+}
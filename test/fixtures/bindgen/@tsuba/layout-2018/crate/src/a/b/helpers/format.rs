@@ -0,0 +1,3 @@
+pub fn format_fn() -> String {
+    String::new()
+}
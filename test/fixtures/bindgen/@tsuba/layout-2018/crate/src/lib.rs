@@ -0,0 +1,5 @@
+pub mod a;
+
+pub fn root_fn() -> i32 {
+    0
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub struct Registry {
+    pub handlers: HashMap<String, Vec<Box<dyn Fn(i32) -> Result<(), String> + Send>>>,
+}
+
+pub fn points() -> impl Iterator<Item = Result<Point, String>> {
+    std::iter::empty()
+}
+
+pub fn pairs<'a>(items: &'a [(u8, &'a str)]) -> Box<dyn Iterator<Item = Vec<Option<(u8, &'a str)>>> + 'a> {
+    Box::new(items.iter().map(|pair| vec![Some(*pair)]))
+}
+
+pub fn mappers() -> impl Iterator<Item = impl Fn(u8) -> Option<u8>> {
+    std::iter::once(|value: u8| value.checked_add(1))
+}
+
+pub fn entries<I: Iterator<Item = (String, u8)>>() -> Option<impl IntoIterator<Item = (String, u8), IntoIter = I>> {
+    None::<Vec<(String, u8)>>.map(|_| unreachable!())
+}
+
+pub fn consume(batches: impl IntoIterator<Item = Result<Vec<Point>, Box<dyn std::error::Error>>>) -> usize {
+    batches.into_iter().count()
+}
+
+pub fn cloned() -> impl Iterator<Item: Clone> {
+    std::iter::once(1)
+}
+
+pub trait Lending {
+    type Item<'a>
+    where
+        Self: 'a;
+
+    fn next<'a>(&'a mut self) -> Option<Self::Item<'a>>;
+}
+
+pub fn lend(source: impl for<'a> Lending<Item<'a> = &'a str>) -> usize {
+    drop(source);
+    0
+}
+
+pub trait Source {
+    type Batch: IntoIterator<Item = Result<Point, String>>;
+
+    fn batches(&self) -> Vec<Self::Batch>;
+}
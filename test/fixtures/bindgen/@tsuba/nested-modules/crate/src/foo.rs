@@ -0,0 +1,10 @@
+pub mod bar;
+pub mod main;
+
+pub mod inner {
+    pub mod baz;
+}
+
+pub fn foo_fn() -> i32 {
+    1
+}
@@ -0,0 +1,3 @@
+pub fn bar_fn() -> i32 {
+    2
+}
@@ -0,0 +1,3 @@
+pub fn baz_fn() -> i32 {
+    3
+}
@@ -0,0 +1,5 @@
+pub mod entry;
+
+pub fn main_fn() -> i32 {
+    4
+}
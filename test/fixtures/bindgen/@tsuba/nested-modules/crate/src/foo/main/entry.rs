@@ -0,0 +1,3 @@
+pub fn entry_fn() -> i32 {
+    5
+}
@@ -0,0 +1,13 @@
+pub mod foo;
+
+pub mod wrap {
+    pub mod deep;
+
+    pub fn wrap_fn() -> i32 {
+        0
+    }
+}
+
+pub fn root_fn() -> i32 {
+    0
+}
@@ -0,0 +1,3 @@
+pub fn deep_fn() -> i32 {
+    6
+}
@@ -0,0 +1,22 @@
+//! A mutually recursive pair across an enum and a struct, plus one through the parent module.
+
+pub enum Expr {
+    Literal(i64),
+    Block(Box<Block>),
+}
+
+pub struct Block {
+    pub statements: Vec<Stmt>,
+    pub tail: Option<Expr>,
+}
+
+pub enum Stmt {
+    Expr(Expr),
+    Empty,
+}
+
+/// Reaches the recursive `Expr` but is not reached from it.
+pub struct Program {
+    pub main: Block,
+    pub origin: super::Document,
+}
@@ -0,0 +1,47 @@
+pub mod ast;
+
+/// A singly linked list node: refers to itself directly.
+pub struct Node {
+    pub value: i32,
+    pub next: Option<Box<Node>>,
+}
+
+/// Refers to itself through `Self`.
+pub struct Chain {
+    pub rest: Vec<Self>,
+}
+
+/// A generic tree: the type arguments do not matter for recursion.
+pub struct Tree<T> {
+    pub value: T,
+    pub children: Vec<Tree<T>>,
+}
+
+/// Recursion through a type alias.
+pub type Link = Option<Box<Cons>>;
+
+pub struct Cons {
+    pub head: u8,
+    pub tail: Link,
+}
+
+/// Holds recursive types without being recursive itself.
+pub struct Document {
+    pub root: Node,
+    pub items: Vec<ast::Expr>,
+}
+
+pub enum Json {
+    Null,
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Recursive through a private field, which is not extracted.
+pub struct Cursor {
+    pub depth: u32,
+    parent: Option<Box<Cursor>>,
+}
+
+/// Recursive through a tuple field.
+pub struct Wrap(pub Option<Box<Wrap>>);
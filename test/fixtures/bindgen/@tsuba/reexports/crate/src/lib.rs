@@ -4,3 +4,4 @@ pub mod inner;
 pub use extra::Helper as RootHelper;
 pub use inner::{ANSWER as ROOT_ANSWER, Thing, make_thing};
 pub use inner::*;
+pub use std::fmt::Display;
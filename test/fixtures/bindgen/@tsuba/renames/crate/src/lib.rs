@@ -0,0 +1,11 @@
+pub mod shapes;
+
+pub use shapes::Circle;
+
+#[tsuba(rename = "LIMIT")]
+pub const MAX_RADIUS: f64 = 10.0;
+
+#[tsuba(rename = "unitRadius")]
+pub fn unit_radius() -> f64 {
+    1.0
+}
@@ -0,0 +1,16 @@
+#[tsuba(rename = "Disc")]
+pub struct Circle {
+    #[tsuba(rename = "r")]
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(radius: f64) -> Circle {
+        Circle { radius }
+    }
+
+    #[tsuba(rename = "surface")]
+    pub fn area(&self) -> f64 {
+        3.14 * self.radius * self.radius
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt;
+
+pub mod traits {
+    pub trait Reader {
+        fn read(&mut self) -> u8;
+    }
+
+    pub trait Source<T> {
+        fn next(&mut self) -> Option<T>;
+    }
+}
+
+pub trait Loggable: std::fmt::Debug + crate::traits::Reader {}
+
+pub trait Pipe: traits::Source<u8> + Send + 'static {
+    fn flush_into<E>(&mut self, sink: &mut E)
+    where
+        E: Extend<u8> + core::fmt::Debug;
+}
+
+pub fn show<T: fmt::Display + ?Sized>(value: &T) -> String {
+    value.to_string()
+}
+
+pub fn drain<R>(mut reader: R) -> Vec<u8>
+where
+    R: traits::Reader + std::io::Read,
+{
+    vec![reader.read()]
+}
+
+pub fn boxed<F>(callback: F) -> Box<dyn Fn(u8) -> u8>
+where
+    F: std::ops::Fn(u8) -> u8 + 'static,
+{
+    Box::new(callback)
+}
+
+pub struct Wrapper<T>(pub T);
+
+impl<T: traits::Reader> traits::Source<u8> for Wrapper<T> {
+    fn next(&mut self) -> Option<u8> {
+        Some(self.0.read())
+    }
+}
@@ -0,0 +1,23 @@
+#![feature(c_variadic)]
+
+use std::ffi::{c_char, c_int, VaList};
+
+/// Formats like C `printf`.
+pub unsafe extern "C" fn log_format(fmt: *const c_char, mut args: ...) -> c_int {
+    let list: VaList = args.as_va_list();
+    forward(fmt, list)
+}
+
+pub unsafe extern "C" fn sum_ints(count: c_int, args: ...) -> c_int {
+    let _ = args;
+    count
+}
+
+pub unsafe extern "C" fn forward(fmt: *const c_char, args: VaList) -> c_int {
+    let _ = (fmt, args);
+    0
+}
+
+pub extern "C" fn fixed(value: c_int) -> c_int {
+    value
+}
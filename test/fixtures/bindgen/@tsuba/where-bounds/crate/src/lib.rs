@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+pub fn sum_all<I>(items: I) -> i32
+where
+    I: Iterator<Item = i32>,
+{
+    items.sum()
+}
+
+pub fn collect_pairs<K: Clone, I>(items: I) -> HashMap<K, String>
+where
+    K: std::hash::Hash + Eq,
+    I: IntoIterator<Item = (K, String)>,
+    I::IntoIter: ExactSizeIterator,
+{
+    items.into_iter().collect()
+}
+
+pub fn longest<'a, 'b: 'a>(left: &'a str, right: &'b str) -> &'a str {
+    if left.len() >= right.len() { left } else { right }
+}
+
+pub fn apply<F>(f: F) -> String
+where
+    F: for<'x> Fn(&'x str) -> String,
+{
+    f("value")
+}
+
+pub trait Source {
+    fn read_into<W>(&self, out: &mut W) -> usize
+    where
+        W: Extend<u8>;
+}
+
+pub struct Table {
+    pub rows: Vec<String>,
+}
+
+impl Table {
+    pub fn extend_rows<I>(&mut self, rows: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        self.rows.extend(rows);
+    }
+}
+
+pub trait Named {
+    fn name(&self) -> String;
+}
+
+pub struct Wrapper<T>(pub T);
+
+impl<T: Clone> Named for Wrapper<T>
+where
+    T: std::fmt::Debug,
+{
+    fn name(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}